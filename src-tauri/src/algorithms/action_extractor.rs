@@ -1,6 +1,6 @@
 use crate::{
-    Clause, ClauseDecomposer, CompressedConcurrentActions, Conflict, ContractUtil, LogType, Logger,
-    RelativizationType, RelativizedAction, RunConfiguration,
+    ActionSet, Clause, ClauseDecomposer, ClauseVisitor, CompressedConcurrentActions, Conflict,
+    ContractUtil, LogType, Logger, RelativizationType, RelativizedAction, RunConfiguration,
 };
 use rustc_hash::{FxHashMap, FxHashSet};
 use std::sync::Arc;
@@ -38,7 +38,8 @@ impl ActionExtractor {
         config: &RunConfiguration,
         logger: &mut Logger,
     ) -> CompressedConcurrentActions {
-        let processed = ClauseDecomposer::process_composed_actions(clause);
+        // Chave canônica: cláusulas equivalentes a menos de ordem compartilham a entrada
+        let processed = ClauseDecomposer::process_composed_actions(clause).canonicalize();
 
         if let Some(cached) = self.cache.get(&processed) {
             return cached.clone();
@@ -65,24 +66,70 @@ impl ActionExtractor {
             logger,
         );
 
-        if compressed_result.source_map.len() == 1 {
-            let negation = {
-                let action = &compressed_result.source_map[0];
-                Arc::new(RelativizedAction::negation(action))
-            };
-
-            Arc::make_mut(&mut compressed_result.source_map).push(negation);
-            let new_index = compressed_result.source_map.len() - 1;
-            let new_mask: u32 = 1 << new_index;
-            compressed_result.valid_masks.push(new_mask);
+        if !config.is_no_idle() {
+            Self::add_idle_branch(&mut compressed_result);
         }
 
         self.cache.insert(processed, compressed_result.clone());
         compressed_result
     }
 
+    /// Garante que o conjunto de ações concorrentes possui o ramo ocioso ("não fazer nada")
+    ///
+    /// O conjunto vazio nunca é considerado válido por `ContractUtil::is_valid`, então sem
+    /// este ramo não existiria transição representando o descumprimento por omissão
+    /// (uma obrigação não executada ou uma proibição respeitada).
+    ///
+    /// Semântica:
+    /// * Sem ações: nada é adicionado (a cláusula não gera transições).
+    /// * Se já existe uma máscara ociosa (vazia ou composta apenas por negações), nada muda.
+    /// * Com uma única ação `a`: adiciona `!a` ao `source_map`, rotulando o ramo ocioso.
+    /// * Com duas ou mais ações: adiciona o conjunto vazio (máscara `0`), isto é, a
+    ///   transição sem ações (exportada como `ε`).
+    ///
+    /// Com `--no-idle` este passo é omitido e o autômato só tem transições pelas ações
+    /// declaradas: uma obrigação `O(a)` isolada passa a ter apenas a transição por `a`,
+    /// sem nenhuma que represente o descumprimento, e a violação deixa de ser alcançável.
+    ///
+    /// # Argumentos
+    /// * `compressed` - Ações concorrentes calculadas para a cláusula
+    pub fn add_idle_branch(compressed: &mut CompressedConcurrentActions) {
+        let n = compressed.source_map.len();
+        if n == 0 {
+            return;
+        }
+
+        let source_map = &compressed.source_map;
+        let has_idle = compressed.valid_sets.iter().any(|set| {
+            set.indices()
+                .all(|idx| source_map.get(idx).is_none_or(|act| act.negation))
+        });
+
+        if has_idle {
+            return;
+        }
+
+        if n == 1 {
+            let negation = Arc::new(RelativizedAction::negation(&compressed.source_map[0]));
+            Arc::make_mut(&mut compressed.source_map).push(negation);
+            let len = compressed.source_map.len();
+            compressed
+                .valid_sets
+                .push(ActionSet::from_indices(&[len - 1], len));
+        } else {
+            compressed.valid_sets.push(ActionSet::from_indices(&[], n));
+        }
+    }
+
     /// Calcula ações relativizadas para uma cláusula
     ///
+    /// Ações de um indivíduo para si mesmo só são descartadas quando há mais de um
+    /// indivíduo; num contrato de uma só parte `p`, cláusulas relativizadas e globais
+    /// geram a ação `(p, a, p)`. Sem nenhum indivíduo (contrato só com cláusulas
+    /// globais), uma cláusula global gera a ação sem partes `(-1, a, -1)`, para que o
+    /// conjunto não fique vazio e a cláusula possa ser cumprida. A mesma regra vale em
+    /// `ClauseDecomposer::generate_relativized_actions`.
+    ///
     /// # Argumentos
     /// * `clause` - A cláusula para extrair ações
     ///
@@ -95,10 +142,6 @@ impl ActionExtractor {
     ) -> FxHashSet<Arc<RelativizedAction>> {
         let mut actions = FxHashSet::default();
 
-        if let Clause::Boolean { .. } = clause {
-            return actions;
-        }
-
         match clause {
             Clause::Deontic {
                 sender,
@@ -156,12 +199,19 @@ impl ActionExtractor {
                                 }
                             }
                         }
+
+                        if indiv.is_empty() {
+                            for ba in basic_actions {
+                                actions.insert(Arc::new(RelativizedAction::new(-1, ba, -1)));
+                            }
+                        }
                     }
                 }
             }
 
             Clause::Boolean { .. } => {
-                // Já tratado acima
+                // Sem ações próprias, mas a composição ainda é visitada: `O(a*)` vira
+                // `T` e pode estar composto com outras cláusulas
             }
         }
 
@@ -189,22 +239,177 @@ impl ActionExtractor {
     }
 
     fn extract_individuals(clause: &Clause) -> FxHashSet<i32> {
-        let mut i = FxHashSet::default();
-        let receiver = clause.get_receiver().clone();
-        let sender = clause.get_sender().clone();
+        let mut collector = IndividualsCollector::default();
+        clause.visit(&mut collector);
+        collector.individuals
+    }
+}
+
+/// Coleta os indivíduos da cadeia de composições (sem penalidades e cláusulas internas)
+#[derive(Default)]
+struct IndividualsCollector {
+    individuals: FxHashSet<i32>,
+}
+
+impl ClauseVisitor for IndividualsCollector {
+    fn visit_clause(&mut self, clause: &Clause) {
+        let receiver = *clause.get_receiver();
+        let sender = *clause.get_sender();
 
         if receiver > 0 {
-            i.insert(receiver);
+            self.individuals.insert(receiver);
         }
 
         if sender > 0 {
-            i.insert(sender);
+            self.individuals.insert(sender);
         }
+    }
 
-        if let Some(composition) = clause.get_composition() {
-            i.extend(Self::extract_individuals(&composition.other));
+    fn visit_nested(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{build, parse, serial};
+    use crate::utils::SymbolTable;
+    use crate::{BasicAction, CompressedConcurrentActions, Transition};
+
+    fn compressed(n: usize, sets: &[&[usize]]) -> CompressedConcurrentActions {
+        let source_map = (0..n)
+            .map(|i| Arc::new(RelativizedAction::new(1, BasicAction::with_value(10 + i as i32), 2)))
+            .collect();
+        CompressedConcurrentActions {
+            source_map: Arc::new(source_map),
+            valid_sets: sets.iter().map(|s| ActionSet::from_indices(s, n)).collect(),
+        }
+    }
+
+    fn idle_sets(c: &CompressedConcurrentActions) -> Vec<&ActionSet> {
+        c.valid_sets
+            .iter()
+            .filter(|set| set.indices().all(|idx| c.source_map[idx].negation))
+            .collect()
+    }
+
+    #[test]
+    fn idle_branch_with_one_action_is_its_negation() {
+        let mut c = compressed(1, &[&[0]]);
+        ActionExtractor::add_idle_branch(&mut c);
+
+        assert_eq!(c.source_map.len(), 2);
+        assert!(c.source_map[1].negation);
+        assert_eq!(c.source_map[1].action, c.source_map[0].action);
+        assert_eq!(idle_sets(&c), vec![&ActionSet::from_indices(&[1], 2)]);
+    }
+
+    #[test]
+    fn idle_branch_with_two_actions_is_the_empty_set() {
+        let mut c = compressed(2, &[&[0, 1], &[0], &[1]]);
+        ActionExtractor::add_idle_branch(&mut c);
+
+        assert_eq!(c.source_map.len(), 2);
+        assert_eq!(c.valid_sets.len(), 4);
+        assert_eq!(idle_sets(&c), vec![&ActionSet::from_indices(&[], 2)]);
+    }
+
+    #[test]
+    fn idle_branch_with_three_actions_is_the_empty_set() {
+        let mut c = compressed(3, &[&[0, 1, 2], &[0, 1], &[2]]);
+        ActionExtractor::add_idle_branch(&mut c);
+
+        assert_eq!(c.source_map.len(), 3);
+        assert_eq!(c.valid_sets.len(), 4);
+        assert_eq!(idle_sets(&c), vec![&ActionSet::from_indices(&[], 3)]);
+    }
+
+    #[test]
+    fn idle_branch_is_not_added_twice() {
+        let mut c = compressed(1, &[&[0]]);
+        ActionExtractor::add_idle_branch(&mut c);
+        ActionExtractor::add_idle_branch(&mut c);
+        assert_eq!(idle_sets(&c).len(), 1);
+
+        let mut c = compressed(3, &[&[0], &[]]);
+        ActionExtractor::add_idle_branch(&mut c);
+        assert_eq!(c.valid_sets.len(), 2);
+    }
+
+    #[test]
+    fn seventy_actions_use_the_sparse_path() {
+        let mut c = compressed(70, &[&[0, 35, 69], &[1], &[68, 2]]);
+        assert!(c.valid_sets.iter().all(|set| matches!(set, ActionSet::Sparse(_))));
+        ActionExtractor::add_idle_branch(&mut c);
+        assert_eq!(idle_sets(&c), vec![&ActionSet::Sparse(Vec::new())]);
+
+        // Each transition gets back exactly its actions, in source map order
+        for set in &c.valid_sets {
+            let transition = Transition::new(0, 1, set.clone(), c.source_map.clone());
+            let expected: Vec<i32> = set.indices().map(|idx| 10 + idx as i32).collect();
+            let actual: Vec<i32> = transition.actions().iter().map(|a| a.action.value).collect();
+            assert_eq!(actual, expected);
         }
+        let first = Transition::new(0, 1, c.valid_sets[0].clone(), c.source_map.clone());
+        assert_eq!(first.action_indices().collect::<Vec<_>>(), [0, 35, 69]);
+        assert_eq!(first.actions()[2].action.value, 79);
+    }
 
-        i
+    #[test]
+    fn idle_branch_needs_actions() {
+        let mut c = compressed(0, &[]);
+        ActionExtractor::add_idle_branch(&mut c);
+        assert!(c.source_map.is_empty());
+        assert!(c.valid_sets.is_empty());
+    }
+
+    /// Positive actions of every clause of `src`, as `(sender, action, receiver)` names
+    /// (`-` for no party), with the individuals computed as the constructor does.
+    fn relativized_actions(src: &str) -> Vec<(String, String, String)> {
+        let contract = parse(src);
+        let extractor = ActionExtractor::new(contract.get_all_conflicts());
+
+        let table = SymbolTable::lock();
+        let name = |id: i32| table.get_symbol_by_id(id).map_or("-".to_string(), |s| s.value.clone());
+        let mut actions: Vec<(String, String, String)> = contract
+            .clauses
+            .iter()
+            .flat_map(|clause| extractor.calculate_relativized_actions(clause, &contract.individuals))
+            .filter(|a| !a.negation)
+            .map(|a| (name(a.sender), name(a.action.value), name(a.receiver)))
+            .collect();
+        actions.sort();
+        actions.dedup();
+        actions
+    }
+
+    fn triple(sender: &str, action: &str, receiver: &str) -> (String, String, String) {
+        (sender.to_string(), action.to_string(), receiver.to_string())
+    }
+
+    #[test]
+    fn single_party_clauses_use_self_actions() {
+        let _table = serial();
+        assert_eq!(relativized_actions("{seller}O(pay);"), [triple("seller", "pay", "seller")]);
+        assert_eq!(
+            relativized_actions("{seller}O(pay);\nO(ship);"),
+            [triple("seller", "pay", "seller"), triple("seller", "ship", "seller")]
+        );
+        // No individual at all: the global clause still has an action to fulfil it
+        assert_eq!(relativized_actions("O(ship);"), [triple("-", "ship", "-")]);
+    }
+
+    #[test]
+    fn single_party_contracts_can_be_satisfied() {
+        let _table = serial();
+        for src in ["{seller}O(pay);", "{seller}O(pay);\nO(ship);", "O(ship);"] {
+            let automaton = build(src);
+            assert!(!automaton.conflict_found, "{}", src);
+            assert!(automaton.situation_counts().satisfaction > 0, "{}", src);
+
+            let initial = automaton.initial.as_ref().unwrap().id;
+            assert!(!automaton.successors(initial).is_empty(), "{}", src);
+        }
     }
 }
@@ -1,12 +1,60 @@
+use crate::error::AnalysisError;
 use crate::{
-    ActionExtractor, Automaton, Clause, ClauseDecomposer, CompressedConcurrentActions,
-    ConflictSearcher, Contract, LogLevel, LogType, Logger, RelativizedAction, RunConfiguration,
-    State, StateSituation, SymbolTable, Transition,
+    conflict_json, ActionExtractor, Automaton, Clause, ClauseDecomposer,
+    CompressedConcurrentActions, ConflictSearcher, Contract, LogLevel, LogType, Logger,
+    MemoryGuard, RelativizedAction, RunConfiguration, State, StateSituation, SymbolTable,
+    Transition, SPARSE_ACTION_LIMIT,
 };
 use rayon::iter::IntoParallelRefIterator;
 use rayon::iter::ParallelIterator;
 use rustc_hash::FxHashSet;
+use serde::Serialize;
+use std::fmt;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Estimativa barata do tamanho da análise, calculada sem construir o autômato
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SizeEstimate {
+    pub clauses: usize,
+    pub individuals: usize,
+    pub actions: usize,
+    /// Maior número de ações relativizadas entre o estado inicial e cada cláusula
+    pub relativized_actions: usize,
+    /// Limite `2^n` de conjuntos de ações concorrentes por estado
+    pub concurrent_bound: u64,
+    /// Teto grosseiro de estados: cada cláusula pendente, cumprida ou violada
+    pub state_ceiling: u64,
+    /// Teto grosseiro de transições: estados vezes conjuntos concorrentes
+    pub transition_ceiling: u64,
+    /// `true` se `relativized_actions` passa de `SPARSE_ACTION_LIMIT`
+    pub too_complex: bool,
+}
+
+impl fmt::Display for SizeEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Size estimate (upper bounds, no automaton built):")?;
+        writeln!(f, "   - Clauses: {}", self.clauses)?;
+        writeln!(f, "   - Individuals: {}", self.individuals)?;
+        writeln!(f, "   - Actions: {}", self.actions)?;
+        writeln!(f, "   - Relativized actions: {}", self.relativized_actions)?;
+        writeln!(
+            f,
+            "   - Concurrent action sets per state: <= {}",
+            self.concurrent_bound
+        )?;
+        writeln!(f, "   - States: <= {}", self.state_ceiling)?;
+        write!(f, "   - Transitions: <= {}", self.transition_ceiling)?;
+        if self.too_complex {
+            write!(
+                f,
+                "\n   - Too complex: more than {} relativized actions",
+                SPARSE_ACTION_LIMIT
+            )?;
+        }
+        Ok(())
+    }
+}
 
 /// Construtor de autômatos para contratos
 pub struct AutomataConstructor {
@@ -18,6 +66,30 @@ pub struct AutomataConstructor {
     // searcher: Option<ConflictSearcher>,
     config: RunConfiguration,
     current_contract: Option<Contract>,
+    pool: Option<Arc<rayon::ThreadPool>>,
+    /// Construção interrompida por `--break-halt` ou por `error`
+    halted: bool,
+    /// Limite excedido durante a construção (ver `--max-clause-size`, `--timeout`)
+    error: Option<AnalysisError>,
+    /// Instante em que a construção excede `--timeout`
+    deadline: Option<Instant>,
+    /// Lotes de conjuntos de ações processados em paralelo (ver `--batch-size`)
+    chunks: usize,
+}
+
+/// Linha da tabela de decisão: uma escolha de ações no estado inicial e seu resultado
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecisionRow {
+    /// Transição que sai do estado inicial
+    pub transition: usize,
+    /// Ações executadas juntas (vazio para a transição ε)
+    pub actions: Vec<RelativizedAction>,
+    /// Estado alcançado após o primeiro passo
+    pub target: usize,
+    /// Situação do estado alcançado
+    pub first_step: StateSituation,
+    /// Resultado final a partir do estado alcançado, ver `decision_table`
+    pub outcome: StateSituation,
 }
 
 impl AutomataConstructor {
@@ -33,22 +105,134 @@ impl AutomataConstructor {
             extractor: None,
             // decomposer: None,
             // searcher: None,
+            pool: Self::build_pool(config.threads()),
             config,
             current_contract: None,
+            halted: false,
+            error: None,
+            deadline: None,
+            chunks: 0,
         }
     }
 
+    /// Número de lotes de conjuntos de ações processados até agora
+    pub fn chunks_processed(&self) -> usize {
+        self.chunks
+    }
+
+    /// Resume o autômato em uma tabela de decisão sobre o primeiro passo
+    ///
+    /// Para cada transição que sai do estado inicial, indica a situação do estado
+    /// alcançado e o resultado final considerando tudo o que ainda pode acontecer a
+    /// partir dele:
+    /// * `Conflicting` se algum estado em conflito é alcançável;
+    /// * `Violating` se a violação é alcançável e a satisfação não (violação inevitável);
+    /// * `Satisfaction` se a satisfação é alcançável e a violação não;
+    /// * `ConflictFree` se o resultado ainda depende das escolhas seguintes.
+    ///
+    /// # Argumentos
+    /// * `automaton` - Autômato já construído
+    ///
+    /// # Retorna
+    /// Uma linha por transição inicial, em ordem de id da transição
+    pub fn decision_table(automaton: &Automaton) -> Vec<DecisionRow> {
+        let Some(ref initial) = automaton.initial else {
+            return Vec::new();
+        };
+        let successors = automaton.successor_map();
+
+        let mut transitions: Vec<&Transition> = automaton
+            .transitions
+            .iter()
+            .filter(|t| t.from == initial.id)
+            .collect();
+        transitions.sort_by_key(|t| t.id);
+
+        transitions
+            .into_iter()
+            .map(|transition| {
+                let situation = |id: usize| {
+                    automaton
+                        .get_state_by_id(id)
+                        .map_or(StateSituation::NotChecked, |s| s.situation)
+                };
+
+                let mut reached: FxHashSet<usize> = FxHashSet::default();
+                let mut stack = vec![transition.to];
+                let (mut conflict, mut violation, mut satisfaction) = (false, false, false);
+                while let Some(id) = stack.pop() {
+                    if !reached.insert(id) {
+                        continue;
+                    }
+                    match situation(id) {
+                        StateSituation::Conflicting => conflict = true,
+                        StateSituation::Violating => violation = true,
+                        StateSituation::Satisfaction => satisfaction = true,
+                        _ => {}
+                    }
+                    if let Some(next) = successors.get(&id) {
+                        stack.extend(next.iter().filter(|n| !reached.contains(n)));
+                    }
+                }
+
+                let outcome = if conflict {
+                    StateSituation::Conflicting
+                } else if violation && !satisfaction {
+                    StateSituation::Violating
+                } else if satisfaction && !violation {
+                    StateSituation::Satisfaction
+                } else {
+                    StateSituation::ConflictFree
+                };
+
+                DecisionRow {
+                    transition: transition.id,
+                    actions: transition.action_refs().map(|a| (**a).clone()).collect(),
+                    target: transition.to,
+                    first_step: situation(transition.to),
+                    outcome,
+                }
+            })
+            .collect()
+    }
+
+    /// Cria o pool de threads próprio da análise (`--threads`)
+    ///
+    /// # Argumentos
+    /// * `threads` - Número de threads; 0 usa o pool global do rayon (todos os núcleos)
+    ///
+    /// # Retorna
+    /// O pool, ou `None` para usar o pool global
+    fn build_pool(threads: usize) -> Option<Arc<rayon::ThreadPool>> {
+        if threads == 0 {
+            return None;
+        }
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .ok()
+            .map(Arc::new)
+    }
+
     /// Processa um contrato e constrói seu autômato
     ///
     /// # Argumentos
     /// * `contract` - O contrato a ser processado
     ///
     /// # Retorna
-    /// O autômato construído
-    pub fn process(&mut self, contract: Contract, logger: &mut Logger) -> Automaton {
+    /// O autômato construído, ou o erro que interrompeu a construção:
+    /// `AnalysisError::ClauseSizeLimit` se algum estado exceder `--max-clause-size`,
+    /// `Timeout` após `--timeout` e `MemoryExceeded` se o `MemoryGuard` sinalizar excesso
+    pub fn process(&mut self, contract: Contract, logger: &mut Logger) -> Result<Automaton, AnalysisError> {
         self.current_contract = Some(contract.clone());
         self.relativized_actions = None;
         self.concurrent_actions = None;
+        self.halted = false;
+        self.error = None;
+        self.deadline = self
+            .config
+            .timeout()
+            .map(|seconds| Instant::now() + Duration::from_secs(seconds));
 
         if self.extractor.is_none() {
             if let Some(ref contract) = self.current_contract {
@@ -67,14 +251,68 @@ impl AutomataConstructor {
         // Constrói o autômato a partir do estado inicial
         if let Some(initial_state) = automaton.initial.clone() {
             self.automaton = Some(automaton.clone());
-            self.construct_automaton(initial_state.id, logger);
+            // Dentro do pool, todos os par_iter da construção (inclusive o cálculo das
+            // ações concorrentes) ficam limitados ao número de threads configurado
+            match self.pool.clone() {
+                Some(pool) => pool.install(|| self.construct_automaton(initial_state.id, logger)),
+                None => self.construct_automaton(initial_state.id, logger),
+            }
             automaton = self.automaton.take().unwrap();
         }
 
         // Limpa referências
         self.current_contract = None;
 
-        automaton
+        match self.error.take() {
+            Some(error) => Err(error),
+            None => Ok(automaton),
+        }
+    }
+
+    /// Estima o tamanho da análise sem construir o autômato
+    ///
+    /// Conta as ações relativizadas do estado inicial e de cada cláusula isolada
+    /// (com o mesmo critério de poda da construção) e deriva tetos grosseiros de
+    /// estados e transições.
+    ///
+    /// # Argumentos
+    /// * `contract` - O contrato a estimar
+    ///
+    /// # Retorna
+    /// A estimativa de tamanho
+    pub fn estimate(&self, contract: &Contract) -> SizeEstimate {
+        let extractor = ActionExtractor::new(contract.get_all_conflicts());
+        let relativized_count = |clause: &Clause| {
+            let processed = ClauseDecomposer::process_composed_actions(clause);
+            let individuals = clause_individuals(&processed, contract, self.config.is_use_prunning());
+            extractor
+                .calculate_relativized_actions(&processed, &individuals)
+                .len()
+        };
+
+        let relativized_actions = contract
+            .get_full_contract()
+            .iter()
+            .chain(contract.clauses.iter())
+            .map(relativized_count)
+            .max()
+            .unwrap_or(0);
+
+        let concurrent_bound = 1u64
+            .checked_shl(relativized_actions as u32)
+            .unwrap_or(u64::MAX);
+        let state_ceiling = 3u64.saturating_pow(contract.clauses.len() as u32);
+
+        SizeEstimate {
+            clauses: contract.clauses.len(),
+            individuals: contract.individuals.len(),
+            actions: contract.actions.len(),
+            relativized_actions,
+            concurrent_bound,
+            state_ceiling,
+            transition_ceiling: state_ceiling.saturating_mul(concurrent_bound),
+            too_complex: relativized_actions > SPARSE_ACTION_LIMIT,
+        }
     }
 
     /// Registra informações do contrato no log
@@ -88,8 +326,7 @@ impl AutomataConstructor {
         }
 
         info.push_str("\nIndividuals:\n");
-        let symbol_table = SymbolTable::instance();
-        let table = symbol_table.lock().unwrap();
+        let table = SymbolTable::lock();
         for &individual in &contract.individuals {
             if let Some(symbol) = table.get_symbol_by_id(individual) {
                 info.push_str(&format!("{} ", symbol.value()));
@@ -99,11 +336,32 @@ impl AutomataConstructor {
         logger.log(LogType::Necessary, &format!("{}", info));
     }
 
+    /// Verifica o tempo (`--timeout`) e a memória (`MemoryGuard`) da construção
+    ///
+    /// # Retorna
+    /// `true` se algum limite foi excedido; a construção é interrompida com o erro
+    fn out_of_budget(&mut self, logger: &mut Logger) -> bool {
+        let error = if MemoryGuard::limit_exceeded() {
+            AnalysisError::MemoryExceeded
+        } else if self.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            AnalysisError::Timeout { seconds: self.config.timeout().unwrap_or(0) }
+        } else {
+            return false;
+        };
+        logger.log(LogType::Necessary, &error.to_string());
+        self.error = Some(error);
+        self.halted = true;
+        true
+    }
+
     /// Algoritmo 1 - Construção do Autômato
     ///
     /// # Argumentos
     /// * `state_id` - ID do estado a ser analisado
     fn construct_automaton(&mut self, state_id: usize, logger: &mut Logger) {
+        if self.halted || self.out_of_budget(logger) {
+            return;
+        }
         let clause = if let Some(ref automaton) = self.automaton {
             automaton
                 .get_state_by_id(state_id)
@@ -129,6 +387,29 @@ impl AutomataConstructor {
         if has_conflict {
             if let Some(ref mut automaton) = self.automaton {
                 automaton.conflict_found = true;
+
+                // Avisa imediatamente, sem esperar o fim da construção (útil com -c)
+                if let Some(state) = automaton.get_state_by_id(state_id) {
+                    let table = SymbolTable::lock();
+                    let conflict = conflict_json(state, &table);
+                    drop(table);
+                    let conflicting: Vec<&str> = conflict["conflicting_tags"]
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|t| t.as_str())
+                        .collect();
+                    logger.log(
+                        LogType::Additional,
+                        &format!(
+                            "Conflict found in state {}: {} conflicts with {}",
+                            state_id,
+                            conflict["tag"].as_str().unwrap_or(""),
+                            conflicting.join(", ")
+                        ),
+                    );
+                    logger.event("conflict-found", conflict);
+                }
             }
         }
 
@@ -146,45 +427,68 @@ impl AutomataConstructor {
         let compressed_actions = self.generate_actions(&clause, &individuals, logger);
 
         let source_map = &compressed_actions.source_map;
-        let masks = &compressed_actions.valid_masks;
+        let action_sets = &compressed_actions.valid_sets;
 
-        const BATCH_SIZE: usize = 500;
+        let batch_size = self.config.effective_batch_size();
 
-        for chunk in masks.chunks(BATCH_SIZE) {
+        for chunk in action_sets.chunks(batch_size) {
+            if self.out_of_budget(logger) {
+                return;
+            }
+            self.chunks += 1;
             let batch_results: Vec<_> = {
                 let decomposer = Some(ClauseDecomposer::new(individuals.clone(), true));
 
                 chunk
                     .par_iter()
-                    .map(|&mask| {
-                        let mut temp_set_for_logic = FxHashSet::default();
-                        let mut temp_mask = mask;
-                        while temp_mask > 0 {
-                            let idx = temp_mask.trailing_zeros();
-                            if let Some(act) = source_map.get(idx as usize) {
-                                temp_set_for_logic.insert(act.clone());
-                            }
-                            temp_mask &= temp_mask - 1;
-                        }
-
-                        // Calcula próxima cláusula usando o Set (lógica booleana)
+                    .map(|action_set| {
+                        let temp_set_for_logic: FxHashSet<_> = action_set
+                            .indices()
+                            .filter_map(|idx| source_map.get(idx).cloned())
+                            .collect();
+
+                        // Calcula próxima cláusula usando o Set (lógica booleana), já na
+                        // forma canônica para que estados equivalentes não sejam duplicados
                         let next_clause = decomposer
                             .as_ref()
                             .unwrap()
-                            .decompose(&clause, &temp_set_for_logic);
+                            .decompose(&clause, &temp_set_for_logic)
+                            .canonicalize();
 
-                        (mask, next_clause)
+                        (action_set.clone(), next_clause)
                     })
                     .collect()
             };
 
-            for (mask, next_clause) in batch_results {
+            for (action_set, next_clause) in batch_results {
+                if self.halted {
+                    return;
+                }
+                if let Some(limit) = self.config.max_clause_size() {
+                    let size = next_clause.node_count();
+                    if size > limit {
+                        let error = AnalysisError::ClauseSizeLimit { state: state_id, size, limit };
+                        logger.log(LogType::Necessary, &error.to_string());
+                        self.error = Some(error);
+                        self.halted = true;
+                        return;
+                    }
+                }
+
                 if let Some(ref mut automaton) = self.automaton {
                     if let Some(existing_state) = automaton.get_state_by_clause(&next_clause) {
-                        let transition =
-                            Transition::new(state_id, existing_state.id, mask, source_map.clone());
+                        let transition = Transition::new(
+                            state_id,
+                            existing_state.id,
+                            action_set,
+                            source_map.clone(),
+                        );
                         automaton.add_transition(transition);
                     } else {
+                        let watched = self
+                            .config
+                            .break_on()
+                            .is_some_and(|text| next_clause.to_string().contains(text));
                         let new_state = State::with_auto_id(Some(next_clause.clone()));
                         let new_state_id = new_state.id;
 
@@ -193,7 +497,7 @@ impl AutomataConstructor {
                         automaton.add_state(new_state);
 
                         let transition =
-                            Transition::new(state_id, new_state_id, mask, source_map.clone());
+                            Transition::new(state_id, new_state_id, action_set, source_map.clone());
                         let transition_id = transition.id;
                         automaton.add_transition(transition);
 
@@ -201,6 +505,13 @@ impl AutomataConstructor {
                             s.push_trace(transition_id);
                         });
 
+                        if watched {
+                            self.log_break(new_state_id, transition_id, &individuals, logger);
+                            if self.halted {
+                                return;
+                            }
+                        }
+
                         self.construct_automaton(new_state_id, logger);
                     }
                 }
@@ -208,13 +519,83 @@ impl AutomataConstructor {
         }
     }
 
+    /// Registra o estado que casou com `--break-on`: cláusula, tags deônticas, transição
+    /// de entrada e as ações concorrentes dela; com `--break-halt`, interrompe a construção
+    ///
+    /// # Argumentos
+    /// * `state_id` - Estado recém-criado
+    /// * `transition_id` - Transição que o criou
+    /// * `indiv` - Indivíduos considerados no estado de origem
+    fn log_break(
+        &mut self,
+        state_id: usize,
+        transition_id: usize,
+        indiv: &FxHashSet<i32>,
+        logger: &mut Logger,
+    ) {
+        let (Some(automaton), Some(contract)) = (&self.automaton, &self.current_contract) else {
+            return;
+        };
+        let Some(clause) = automaton
+            .get_state_by_id(state_id)
+            .and_then(|s| s.clause.as_ref())
+        else {
+            return;
+        };
+
+        let searcher = ConflictSearcher::new(indiv.clone(), contract.get_all_conflicts())
+            .with_party_matched(self.config.is_party_matched_conflicts());
+        // Cláusula formatada antes de travar a tabela (o Display também a trava)
+        let clause_str = clause.to_string();
+        let mut dump = format!(
+            "BREAK: state {} matches --break-on \"{}\"\n   Clause: {}\n",
+            state_id,
+            self.config.break_on().unwrap_or_default(),
+            clause_str
+        );
+        {
+            let table = SymbolTable::lock();
+            if let Some(transition) = automaton.get_transition_by_id(transition_id) {
+                let actions: Vec<String> = transition
+                    .action_refs()
+                    .map(|a| a.format_with_symbols(&table))
+                    .collect();
+                dump.push_str(&format!(
+                    "   Incoming: T{} from state {}\n   Concurrent actions: {{{}}}\n",
+                    transition.id,
+                    transition.from,
+                    actions.join(", ")
+                ));
+            }
+            let mut tags: Vec<String> = searcher
+                .clause_tags(clause)
+                .iter()
+                .map(|tag| tag.format_with_symbols(&table))
+                .collect();
+            tags.sort();
+            dump.push_str(&format!("   Tags: {}", tags.join(", ")));
+        }
+        logger.log(LogType::Necessary, &dump);
+
+        if self.config.is_break_halt() {
+            logger.log(
+                LogType::Necessary,
+                "BREAK: construction halted (--break-halt)",
+            );
+            self.halted = true;
+        }
+    }
+
     fn check_conflict_without_clone(&mut self, state_id: usize, indiv: &FxHashSet<i32>) -> bool {
         if let Some(ref mut automaton) = self.automaton {
             // Remove temporariamente, modifica, reinsere
-            let searcher = Some(ConflictSearcher::new(
-                indiv.clone(),
-                self.current_contract.clone().unwrap().get_all_conflicts(),
-            ));
+            let searcher = Some(
+                ConflictSearcher::new(
+                    indiv.clone(),
+                    self.current_contract.clone().unwrap().get_all_conflicts(),
+                )
+                .with_party_matched(self.config.is_party_matched_conflicts()),
+            );
 
             if let Some(mut state) = automaton.get_state_by_id_mut(state_id) {
                 let has_conflict = if let Some(ref searcher) = searcher {
@@ -266,18 +647,246 @@ impl AutomataConstructor {
 
         CompressedConcurrentActions {
             source_map: Arc::new(Vec::new()),
-            valid_masks: Vec::new(),
+            valid_sets: Vec::new(),
         }
     }
 
     fn get_individuals(&self, clause: &Clause) -> FxHashSet<i32> {
-        if self.config.is_use_prunning() {
-            ActionExtractor::calculate_individuals(
-                &clause,
-                self.current_contract.clone().unwrap().individuals,
-            )
-        } else {
-            self.current_contract.clone().unwrap().individuals
+        let contract = self.current_contract.as_ref().unwrap();
+        clause_individuals(clause, contract, self.config.is_use_prunning())
+    }
+}
+
+/// Indivíduos considerados nas expansões de `clause`
+///
+/// Com a poda, só os que aparecem na cláusula e as partes declaradas no cabeçalho
+/// `parties`, que existem justamente para entrar nas expansões; sem ela, todos.
+fn clause_individuals(clause: &Clause, contract: &Contract, pruning: bool) -> FxHashSet<i32> {
+    if pruning {
+        let mut individuals =
+            ActionExtractor::calculate_individuals(clause, contract.individuals.clone());
+        individuals.extend(contract.parties.iter().copied());
+        individuals
+    } else {
+        contract.individuals.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{config, parse, serial, write_contract, RecordingSink};
+
+    /// Builds `src` with `args`, returning the automaton and the number of chunks processed
+    fn construct(src: &str, args: &[&str]) -> (Automaton, usize) {
+        let config = config(&write_contract("batches.rcl", src), args);
+        let mut logger = Logger::new(config.clone()).unwrap();
+        let mut constructor = AutomataConstructor::new(config);
+        let automaton = constructor.process(parse(src), &mut logger).unwrap();
+        (automaton, constructor.chunks_processed())
+    }
+
+    #[test]
+    fn batch_size_sets_the_chunks_per_state() {
+        let _table = serial();
+        let src = "{buyer,seller}O(pay); {seller,buyer}O(deliver); {buyer,seller}F(cancel);";
+
+        let (one_per_chunk, chunks) = construct(src, &["--batch-size", "1"]);
+        assert_eq!(chunks, one_per_chunk.transitions.len());
+
+        let (whole, chunks) = construct(src, &["--batch-size", "100000"]);
+        let expanded = whole
+            .states
+            .iter()
+            .filter(|s| !whole.successors(s.id).is_empty())
+            .count();
+        assert_eq!(chunks, expanded);
+        assert_eq!(whole.transitions.len(), one_per_chunk.transitions.len());
+    }
+
+    #[test]
+    fn estimate_counts_the_actions_of_the_initial_state() {
+        let _table = serial();
+        let src = "{buyer,seller}O(pay)_/{buyer,seller}O(fine)/_; {seller,buyer}F(cancel); {seller}P(ship);";
+        let contract = parse(src);
+        let estimate = AutomataConstructor::new(config(&write_contract("estimate.rcl", src), &[])).estimate(&contract);
+        let (automaton, _) = construct(src, &[]);
+
+        let initial = automaton.initial.as_ref().unwrap().id;
+        let transition = automaton.transitions.iter().find(|t| t.from == initial).unwrap();
+        let actual = transition.source_map.iter().filter(|a| !a.negation).count();
+
+        assert!(actual >= 3);
+        assert_eq!(estimate.relativized_actions, actual);
+        assert_eq!(estimate.concurrent_bound, 1 << actual);
+        assert_eq!(estimate.actions, contract.actions.len());
+        assert_eq!(estimate.individuals, contract.individuals.len());
+    }
+
+    #[test]
+    fn one_thread_builds_the_same_automaton() {
+        let _table = serial();
+        let src = "conflict { global { (ship, hold) }; };
+                   {buyer,seller}O(pay)_/{buyer,seller}O(fine)/_;
+                   {buyer,seller}[pay]({seller,carrier}O(ship));
+                   {seller,buyer}F(cancel);
+                   {seller}P(hold);";
+        let summary = |automaton: &Automaton| {
+            let mut states: Vec<String> = automaton
+                .states
+                .iter()
+                .map(|s| format!("{:?} {}", s.situation, s.clause.as_ref().map(|c| c.to_string()).unwrap_or_default()))
+                .collect();
+            states.sort();
+            (states, automaton.transitions.len(), automaton.conflict_found)
+        };
+
+        let (all_cores, _) = construct(src, &[]);
+        let (one_thread, _) = construct(src, &["--threads", "1"]);
+        assert!(all_cores.states.len() > 3);
+        assert_eq!(summary(&one_thread), summary(&all_cores));
+    }
+
+    #[test]
+    fn conflict_found_event_fires_for_each_conflicting_state() {
+        let _table = serial();
+        let src = "{buyer,seller}[pay]({buyer,seller}O(refund));
+                   {buyer,seller}[pay]({buyer,seller}F(refund));
+                   {seller,buyer}[ship]({seller,buyer}O(invoice));
+                   {seller,buyer}[ship]({seller,buyer}F(invoice));";
+        let config = config(&write_contract("events.rcl", src), &["-c"]);
+        let sink = Arc::new(RecordingSink::default());
+        let mut logger = Logger::new(config.clone()).unwrap().with_event_sink(sink.clone());
+        let automaton = AutomataConstructor::new(config).process(parse(src), &mut logger).unwrap();
+
+        let mut conflicting: Vec<u64> = automaton
+            .states
+            .iter()
+            .filter(|s| s.situation == StateSituation::Conflicting)
+            .map(|s| s.id as u64)
+            .collect();
+        let mut reported: Vec<u64> = sink
+            .payloads("conflict-found")
+            .iter()
+            .map(|event| event["state"].as_u64().unwrap())
+            .collect();
+        conflicting.sort();
+        reported.sort();
+        assert!(conflicting.len() >= 2);
+        assert_eq!(reported, conflicting);
+    }
+
+    #[test]
+    fn star_contract_stops_cleanly_at_the_clause_size_limit() {
+        let _table = serial();
+        let src = "{buyer,seller}[(pay.ship)*]({buyer,seller}O(invoice) ^ {buyer,seller}[pay]({seller,buyer}O(deliver)));";
+        let config = config(&write_contract("star.rcl", src), &["--max-clause-size", "3"]);
+        let mut logger = Logger::new(config.clone()).unwrap();
+        match AutomataConstructor::new(config).process(parse(src), &mut logger) {
+            Err(AnalysisError::ClauseSizeLimit { size, limit, .. }) => {
+                assert_eq!(limit, 3);
+                assert!(size > limit);
+            }
+            other => panic!("expected ClauseSizeLimit, got {:?}", other.map(|a| a.states.len())),
         }
+
+        // Without the limit the same contract is analysed in full
+        let (automaton, _) = construct(src, &[]);
+        assert!(!automaton.conflict_found);
+    }
+
+    #[test]
+    fn no_idle_leaves_out_the_branch_that_breaks_a_single_obligation() {
+        let _table = serial();
+        let src = "{buyer,seller}O(pay);";
+        let situations = |automaton: &Automaton| {
+            let initial = automaton.initial.as_ref().unwrap().id;
+            let mut targets: Vec<String> = automaton
+                .successors(initial)
+                .into_iter()
+                .map(|(_, to)| format!("{:?}", automaton.get_state_by_id(to).unwrap().situation))
+                .collect();
+            targets.sort();
+            targets
+        };
+
+        // Paying satisfies; not paying (the idle branch) violates
+        let (with_idle, _) = construct(src, &[]);
+        assert_eq!(situations(&with_idle), ["Satisfaction", "Violating"]);
+
+        // Only the declared action is left: nothing represents non-compliance
+        let (without_idle, _) = construct(src, &["--no-idle"]);
+        assert_eq!(situations(&without_idle), ["Satisfaction"]);
+        assert_eq!(without_idle.situation_counts().violating, 0);
+        assert!(without_idle.states.len() < with_idle.states.len());
+    }
+
+    #[test]
+    fn decision_table_has_a_row_per_initial_transition() {
+        let _table = serial();
+        let (automaton, _) = construct("{buyer,seller}O(pay)_/{buyer,seller}O(fine)/_;", &[]);
+        let initial = automaton.initial.as_ref().unwrap().id;
+        let rows = AutomataConstructor::decision_table(&automaton);
+
+        let mut expected: Vec<usize> = automaton
+            .transitions
+            .iter()
+            .filter(|t| t.from == initial)
+            .map(|t| t.id)
+            .collect();
+        expected.sort();
+        assert_eq!(rows.iter().map(|r| r.transition).collect::<Vec<_>>(), expected);
+
+        // Paying ends the contract; not paying leaves the fine, which can still go either way
+        let pay = SymbolTable::lock().get_id("pay", crate::utils::SymbolType::Action).unwrap();
+        for row in &rows {
+            if row.actions.iter().any(|a| a.action.value == pay && !a.negation) {
+                assert_eq!((row.first_step, row.outcome), (StateSituation::Satisfaction, StateSituation::Satisfaction));
+            } else {
+                assert_eq!((row.first_step, row.outcome), (StateSituation::ConflictFree, StateSituation::ConflictFree));
+            }
+        }
+
+        let csv = crate::utils::AutomatonExporter::dump_decision_table(&automaton);
+        assert_eq!(csv.lines().count(), rows.len() + 1);
+        assert!(csv.lines().skip(1).all(|l| l.ends_with(";satisfaction") || l.ends_with(";open")), "{}", csv);
+    }
+
+    #[test]
+    fn declared_party_widens_the_global_expansion() {
+        let _table = serial();
+        let clauses = "O(pay);\n{buyer,seller}O(sign);";
+        let with_party = format!("parties carrier;\n{}", clauses);
+        let estimate = |src: &str| {
+            AutomataConstructor::new(config(&write_contract("parties.rcl", src), &[]))
+                .estimate(&parse(src))
+                .relativized_actions
+        };
+        assert!(estimate(&with_party) > estimate(clauses));
+
+        // The carrier isn't named in any clause, but pays and is paid in the initial state
+        let (automaton, _) = construct(&with_party, &[]);
+        let initial = automaton.initial.as_ref().unwrap().id;
+        let (pay, carrier) = {
+            let table = SymbolTable::lock();
+            (
+                table.get_id("pay", crate::utils::SymbolType::Action).unwrap(),
+                table.get_id("carrier", crate::utils::SymbolType::Individual).unwrap(),
+            )
+        };
+        let transition = automaton.transitions.iter().find(|t| t.from == initial).unwrap();
+        let carrier_pays = transition
+            .source_map
+            .iter()
+            .filter(|a| a.action.value == pay && (a.sender == carrier || a.receiver == carrier))
+            .count();
+        assert!(carrier_pays > 0);
+
+        // So a conflict scoped to the declared party is checked against its actions
+        let scoped = format!(
+            "parties carrier;\nconflict {{ relativized {{ (pay, ship){{carrier}} }}; }};\n{}\nO(ship);",
+            clauses
+        );
+        assert!(construct(&scoped, &[]).0.conflict_found);
     }
 }
@@ -2,10 +2,24 @@ use rustc_hash::FxHashSet;
 use std::sync::Arc;
 
 use crate::{
-    Action, ActionOperator, Clause, ClauseComposition, ClauseCompositionType, DeonticClauseType,
-    RelativizationType, RelativizedAction,
+    Action, ActionOperator, Clause, ClauseComposition, ClauseCompositionType, ClauseVisitor,
+    DeonticClauseType, RelativizationType, RelativizedAction,
 };
 
+/// Maior profundidade de ação encontrada nas cláusulas visitadas
+#[derive(Default)]
+struct ActionDepthCollector {
+    max: usize,
+}
+
+impl ClauseVisitor for ActionDepthCollector {
+    fn visit_clause(&mut self, clause: &Clause) {
+        if let Clause::Deontic { action, .. } | Clause::Dynamic { action, .. } = clause {
+            self.max = self.max.max(action.depth());
+        }
+    }
+}
+
 pub struct ClauseDecomposer {
     individuals: FxHashSet<i32>,
     ignore_self_actions: bool,
@@ -19,6 +33,28 @@ impl ClauseDecomposer {
         }
     }
 
+    /// Verifica se as ações da cláusula (inclusive em penalidades e dinâmicas) cabem no
+    /// limite de aninhamento (`--max-action-depth`)
+    ///
+    /// A reescrita de ações compostas e a decomposição percorrem a árvore da ação
+    /// recursivamente; uma composição aninhada demais estouraria a pilha.
+    ///
+    /// # Argumentos
+    /// * `clause` - Cláusula a verificar
+    /// * `limit` - Profundidade máxima aceita
+    ///
+    /// # Retorna
+    /// A maior profundidade encontrada, como erro, se passar de `limit`
+    pub fn check_action_depth(clause: &Clause, limit: usize) -> Result<(), usize> {
+        let mut collector = ActionDepthCollector::default();
+        clause.visit(&mut collector);
+        if collector.max > limit {
+            Err(collector.max)
+        } else {
+            Ok(())
+        }
+    }
+
     pub fn decompose(
         &self,
         clause: &Clause,
@@ -65,6 +101,12 @@ impl ClauseDecomposer {
         }
     }
 
+    /// Decompõe uma cláusula dinâmica `[a]C` com ação básica
+    ///
+    /// As ações da cláusula são sempre geradas sem negação, e as ações negadas do
+    /// conjunto executado (como o `!a` do ramo ocioso) nunca coincidem com elas. Assim,
+    /// `[!a]C` é o complemento exato de `[a]C`: `C` passa a valer justamente nas
+    /// transições em que `a` não ocorre, e a cláusula é cumprida nas demais.
     fn decompose_dynamic(
         &self,
         clause: &Clause,
@@ -109,6 +151,13 @@ impl ClauseDecomposer {
         }
     }
 
+    /// Decompõe uma obrigação ou proibição com ação básica
+    ///
+    /// Se a cláusula não é cumprida, o resultado é a própria penalidade, que vira a
+    /// cláusula do próximo estado e é decomposta no passo seguinte. Como a penalidade
+    /// pode ser qualquer cláusula, inclusive outra obrigação com penalidade própria,
+    /// cadeias de compensação (`O(a)_/O(b)_/O(c)/_/_`) só levam à violação quando
+    /// todos os níveis falham.
     fn decompose_deontic(
         &self,
         clause: &Clause,
@@ -281,6 +330,9 @@ impl ClauseDecomposer {
         }
     }
 
+    /// Ações relativizadas de uma cláusula, com as mesmas regras de
+    /// `ActionExtractor::calculate_relativized_actions` (ações para si mesmo com um só
+    /// indivíduo, ação sem partes quando não há indivíduos)
     fn generate_relativized_actions(&self, clause: &Clause) -> FxHashSet<RelativizedAction> {
         let ignore = if self.individuals.len() > 1 {
             self.ignore_self_actions
@@ -333,6 +385,12 @@ impl ClauseDecomposer {
                                 }
                             }
                         }
+
+                        if self.individuals.is_empty() {
+                            for action in &basic_actions {
+                                set.insert(RelativizedAction::new(-1, action.clone(), -1));
+                            }
+                        }
                     }
                 }
             }
@@ -418,6 +476,14 @@ impl ClauseDecomposer {
         }
     }
 
+    /// Reescreve uma obrigação (ou permissão) sobre ação composta em cláusulas sobre as partes
+    ///
+    /// * `O(a & b)` = `O(a) ∧ O(b)`: as duas ações devem ocorrer
+    /// * `O(a . b)` = `O(a) ∧ [a]O(b)`: `a` agora e, depois dela, `b`
+    /// * `O(a + b)` = `O(a) ∨ O(b)`: basta executar uma das alternativas
+    /// * `O(a*)` = `O(a^N)` = `⊤`: zero repetições já cumprem a obrigação
+    ///
+    /// Os demais operadores não têm reescrita e a cláusula é devolvida inalterada.
     fn process_composed_obligation(clause: &Clause) -> Clause {
         if let Clause::Deontic {
             sender,
@@ -538,6 +604,16 @@ impl ClauseDecomposer {
                         }
                     }
 
+                    // O(a*) = ⊤: a* inclui zero repetições, então a obrigação (ou a
+                    // permissão) é cumprida sem executar nada e não tem penalidade
+                    ActionOperator::Star | ActionOperator::BoundedStar(_) => {
+                        let mut result = Clause::boolean_true();
+                        if let Some(comp) = composition {
+                            result.set_composition(comp.clone());
+                        }
+                        result
+                    }
+
                     _ => clause.clone(),
                 }
             } else {
@@ -548,6 +624,14 @@ impl ClauseDecomposer {
         }
     }
 
+    /// Reescreve uma proibição sobre ação composta em cláusulas sobre as partes
+    ///
+    /// * `F(a + b)` = `F(a) ∧ F(b)`: proibir a escolha proíbe cada alternativa
+    /// * `F(a & b)` = `F(a) ∧ F(b)`: mesma decomposição da escolha
+    /// * `F(a . b)` = `F(a) ∨ [a]F(b)`: basta não executar a sequência até o fim
+    /// * `F(a*)` = `F(a^N)` = `F(a)`: proíbe qualquer repetição não vazia
+    ///
+    /// Os demais operadores não têm reescrita e a cláusula é devolvida inalterada.
     fn process_composed_prohibition(clause: &Clause) -> Clause {
         if let Clause::Deontic {
             sender,
@@ -635,6 +719,25 @@ impl ClauseDecomposer {
                         }
                     }
 
+                    // F(a*) = F(a): toda repetição não vazia começa por `a`, então proibir
+                    // a primeira ocorrência basta. As zero repetições (não agir) ficam
+                    // de fora, senão a proibição seria sempre violada
+                    ActionOperator::Star | ActionOperator::BoundedStar(_) => {
+                        if let Some(left) = &composed.left {
+                            Clause::Deontic {
+                                sender: *sender,
+                                receiver: *receiver,
+                                relativization_type: *relativization_type,
+                                action: (**left).clone(),
+                                deontic_type: *deontic_type,
+                                penalty: penalty.clone(),
+                                composition: composition.clone(),
+                            }
+                        } else {
+                            clause.clone()
+                        }
+                    }
+
                     _ => clause.clone(),
                 }
             } else {
@@ -685,6 +788,44 @@ impl ClauseDecomposer {
                         }
                     }
 
+                    // [a^N]C = C ^ [a][a^(N-1)]C, com [a^0]C = C: a cláusula interna
+                    // vale após 0, 1, ..., N repetições de `a`; depois da N-ésima o
+                    // operador não impõe mais nada (a cadeia termina em C)
+                    ActionOperator::BoundedStar(bound) => {
+                        if let Some(left) = &composed.left {
+                            let remaining = if bound <= 1 {
+                                inner_clause.clone()
+                            } else {
+                                Arc::new(Clause::Dynamic {
+                                    sender: *sender,
+                                    receiver: *receiver,
+                                    relativization_type: *relativization_type,
+                                    action: Action::bounded_star((**left).clone(), bound - 1),
+                                    clause: inner_clause.clone(),
+                                    composition: None,
+                                })
+                            };
+                            let mut clause2 = (**inner_clause).clone();
+
+                            let dc = Clause::Dynamic {
+                                sender: *sender,
+                                receiver: *receiver,
+                                relativization_type: *relativization_type,
+                                action: (**left).clone(),
+                                clause: remaining,
+                                composition: composition.clone(),
+                            };
+
+                            clause2.set_composition(ClauseComposition::new(
+                                ClauseCompositionType::And,
+                                dc,
+                            ));
+                            clause2
+                        } else {
+                            clause.clone()
+                        }
+                    }
+
                     ActionOperator::Sequence => {
                         if let (Some(left), Some(right)) = (&composed.left, &composed.right) {
                             let c1 = Clause::Dynamic {
@@ -709,6 +850,7 @@ impl ClauseDecomposer {
                         }
                     }
 
+                    // [a + b]C = [a]C ∧ [b]C: C deve valer após qualquer uma das alternativas
                     ActionOperator::Choice => {
                         if let (Some(left), Some(right)) = (&composed.left, &composed.right) {
                             let c1 = Clause::Dynamic {
@@ -742,7 +884,10 @@ impl ClauseDecomposer {
 
                     ActionOperator::Negation => Self::process_negation_composed_actions(clause),
 
-                    _ => clause.clone(),
+                    // Sem reescrita para [a & b]C; os demais operadores (inclusive a
+                    // escolha) são tratados acima, e um operador novo precisa ser
+                    // incluído aqui explicitamente
+                    ActionOperator::Concurrency | ActionOperator::None => clause.clone(),
                 }
             } else {
                 clause.clone()
@@ -870,6 +1015,19 @@ impl ClauseDecomposer {
                                 }
                             }
 
+                            // `!!a` equivale a `a`
+                            ActionOperator::Negation => match &inner_composed.left {
+                                Some(inner) => Clause::Dynamic {
+                                    sender: *sender,
+                                    receiver: *receiver,
+                                    relativization_type: *relativization_type,
+                                    action: (**inner).clone(),
+                                    clause: inner_clause.clone(),
+                                    composition: composition.clone(),
+                                },
+                                None => clause.clone(),
+                            },
+
                             _ => clause.clone(),
                         },
                     }
@@ -888,3 +1046,170 @@ impl ClauseDecomposer {
         matches!(action, Action::Composed(_))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{build, parse, serial};
+    use crate::StateSituation;
+    use std::collections::{HashMap, VecDeque};
+
+    /// Penalty of a deontic clause, if it has one
+    fn penalty(clause: &Clause) -> Option<&Clause> {
+        match clause {
+            Clause::Deontic { penalty, .. } => penalty.as_deref(),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn compensation_chain_violates_only_when_every_level_fails() {
+        let _table = serial();
+        let src = "{buyer,seller}O(pay)_/{buyer,seller}O(fine)_/{buyer,seller}O(jail)/_/_;";
+
+        // Both levels of the chain are parsed, the last one falling back to `F`
+        let contract = parse(src);
+        let clause = contract.clauses.iter().next().unwrap();
+        let fine = penalty(clause).expect("first penalty");
+        let jail = penalty(fine).expect("second penalty");
+        assert!(matches!(jail, Clause::Deontic { .. }));
+        assert!(matches!(penalty(jail), Some(Clause::Boolean { value: false, .. })));
+
+        // Shortest path to a violation: not paying, then not fining, then not jailing
+        let automaton = build(src);
+        let initial = automaton.initial.as_ref().unwrap().id;
+        let mut depth = HashMap::from([(initial, 0)]);
+        let mut queue = VecDeque::from([initial]);
+        while let Some(state) = queue.pop_front() {
+            for (_, to) in automaton.successors(state) {
+                if !depth.contains_key(&to) {
+                    depth.insert(to, depth[&state] + 1);
+                    queue.push_back(to);
+                }
+            }
+        }
+        let violating = depth
+            .iter()
+            .filter(|(&id, _)| automaton.get_state_by_id(id).unwrap().situation == StateSituation::Violating)
+            .map(|(_, &d)| d)
+            .min();
+        assert_eq!(violating, Some(3));
+    }
+
+    #[test]
+    fn bounded_deadline_ends_after_the_bound() {
+        let _table = serial();
+        // `late` is forbidden now and after each of the next two ticks, not after that
+        let automaton = build("{carrier,buyer}[tick^2]({carrier,buyer}F(late));");
+        let tick = crate::SymbolTable::lock().get_id("tick", crate::utils::SymbolType::Action).unwrap();
+        let situation = |state: usize| automaton.get_state_by_id(state).unwrap().situation;
+        let can_violate = |state: usize| {
+            automaton
+                .successors(state)
+                .iter()
+                .any(|&(_, to)| situation(to) == StateSituation::Violating)
+        };
+
+        let mut state = automaton.initial.as_ref().unwrap().id;
+        for _ in 0..2 {
+            assert!(can_violate(state));
+            state = automaton
+                .transitions
+                .iter()
+                .find(|t| t.from == state && t.action_refs().map(|a| a.action.value).eq([tick]))
+                .expect("tick transition")
+                .to;
+        }
+        // Last state of the deadline: `late` still violates, anything else ends it
+        assert!(can_violate(state));
+        for (_, to) in automaton.successors(state) {
+            if situation(to) != StateSituation::Violating {
+                assert_eq!(situation(to), StateSituation::Satisfaction);
+                assert!(!can_violate(to));
+            }
+        }
+    }
+
+    /// The clause of `src` with its composed actions rewritten, as text
+    fn decomposed(src: &str) -> String {
+        let contract = parse(src);
+        ClauseDecomposer::process_composed_actions(contract.clauses.iter().next().unwrap()).to_string()
+    }
+
+    #[test]
+    fn obligation_of_a_choice_is_either_obligation() {
+        let _table = serial();
+        assert_eq!(
+            decomposed("{buyer,seller}O(pay+ship);"),
+            "{buyer,seller}OBLIGATION(pay)_/F/_ OR {buyer,seller}OBLIGATION(ship)_/F/_"
+        );
+    }
+
+    #[test]
+    fn prohibition_of_a_choice_forbids_both() {
+        let _table = serial();
+        assert_eq!(
+            decomposed("{buyer,seller}F(pay+ship);"),
+            "{buyer,seller}PROHIBITION(pay)_/F/_ AND {buyer,seller}PROHIBITION(ship)_/F/_"
+        );
+        // Nor is a choice dropped in a dynamic clause: the clause follows either action
+        assert_eq!(
+            decomposed("{buyer,seller}[pay+ship]({seller,buyer}O(deliver));"),
+            "{buyer,seller}[pay]({seller,buyer}OBLIGATION(deliver)_/F/_) AND \
+             {buyer,seller}[ship]({seller,buyer}OBLIGATION(deliver)_/F/_)"
+        );
+    }
+
+    #[test]
+    fn obligation_of_a_star_is_met_by_doing_nothing() {
+        let _table = serial();
+        // Zero repetitions of `pay` already meet `O(pay*)`
+        assert_eq!(decomposed("{buyer,seller}O(pay*);"), "T");
+        // A forbidden repetition is violated by its first `pay`
+        assert_eq!(decomposed("{buyer,seller}F(pay*);"), "{buyer,seller}PROHIBITION(pay)_/F/_");
+
+        // Shipping alone ends the contract: `pay` is never needed
+        let automaton = build("{buyer,seller}O(pay*);\n{seller,buyer}O(ship);");
+        let ship = crate::SymbolTable::lock().get_id("ship", crate::utils::SymbolType::Action).unwrap();
+        let initial = automaton.initial.as_ref().unwrap().id;
+        let shipped = automaton
+            .transitions
+            .iter()
+            .find(|t| t.from == initial && t.action_refs().map(|a| (a.action.value, a.negation)).eq([(ship, false)]))
+            .expect("ship transition")
+            .to;
+        assert_eq!(automaton.get_state_by_id(shipped).unwrap().situation, StateSituation::Satisfaction);
+    }
+
+    #[test]
+    fn negated_dynamic_action_fires_exactly_when_the_action_is_absent() {
+        let _table = serial();
+        let outcomes = |src: &str| {
+            let automaton = build(src);
+            let pay = crate::SymbolTable::lock().get_id("pay", crate::utils::SymbolType::Action).unwrap();
+            let initial = automaton.initial.as_ref().unwrap().id;
+            // For each transition out of the initial state: was `pay` done, and where did it go
+            let outcomes: Vec<(bool, bool)> = automaton
+                .transitions
+                .iter()
+                .filter(|t| t.from == initial)
+                .map(|t| {
+                    let paid = t.action_refs().any(|a| a.action.value == pay && !a.negation);
+                    let target = automaton.get_state_by_id(t.to).unwrap();
+                    let refund_due = target.clause.as_ref().is_some_and(|c| c.to_string().contains("OBLIGATION(refund)"));
+                    (paid, refund_due)
+                })
+                .collect();
+            assert!(outcomes.iter().any(|(paid, _)| *paid) && outcomes.iter().any(|(paid, _)| !*paid));
+            outcomes
+        };
+
+        // `[!pay]`: the refund is due exactly on the transitions without `pay`
+        let negated = outcomes("{buyer,seller}[!pay]({seller,buyer}O(refund));");
+        assert!(negated.iter().all(|(paid, refund_due)| paid != refund_due), "{:?}", negated);
+
+        // `[pay]` is the complement
+        let positive = outcomes("{buyer,seller}[pay]({seller,buyer}O(refund));");
+        assert!(positive.iter().all(|(paid, refund_due)| paid == refund_due), "{:?}", positive);
+    }
+}
@@ -1,12 +1,25 @@
 use crate::{
     BasicAction, Clause, ClauseCompositionType, ClauseDecomposer, Conflict, ConflictInformation,
-    ConflictType, DeonticClauseType, DeonticTag, RelativizationType, State, StateSituation,
+    ConflictType, Contract, DeonticClauseType, DeonticTag, RelativizationType, State,
+    StateSituation,
 };
 use rustc_hash::FxHashSet;
 
+/// Busca de conflitos entre as tags deônticas de um estado
+///
+/// Há dois modos para os conflitos declarados como globais (`global { (a,b) }`):
+/// * padrão: a ação `b` conflita com `a` para quaisquer partes, então uma norma sobre
+///   `a` entre A e B conflita com uma norma sobre `b` entre C e D;
+/// * `party_matched` (`--party-matched-conflicts`): uma norma dirigida ou relativizada
+///   sobre `a` só conflita com normas sobre `b` das mesmas partes (ou globais). Normas
+///   globais sobre `a` continuam valendo para todas as partes.
+///
+/// O conflito direto obrigação/permissão × proibição da mesma ação não muda: ele já
+/// respeita remetente e receptor das tags.
 pub struct ConflictSearcher {
     individuals: FxHashSet<i32>,
     conflicts: Vec<Conflict>,
+    party_matched: bool,
 }
 
 impl ConflictSearcher {
@@ -14,9 +27,16 @@ impl ConflictSearcher {
         ConflictSearcher {
             individuals,
             conflicts,
+            party_matched: false,
         }
     }
 
+    /// Liga ou desliga o modo `party_matched` (ver `ConflictSearcher`)
+    pub fn with_party_matched(mut self, party_matched: bool) -> Self {
+        self.party_matched = party_matched;
+        self
+    }
+
     /// Verifica se um estado possui conflitos
     ///
     /// # Argumentos
@@ -61,6 +81,114 @@ impl ConflictSearcher {
         }
     }
 
+    /// Verificação estrutural rápida: o estado inicial do contrato já é conflitante?
+    ///
+    /// Aplica `has_conflict` à composição de todas as cláusulas (`get_full_contract`),
+    /// sem construir o autômato. Conflitos que só surgem após alguma transição não
+    /// são detectados.
+    ///
+    /// # Argumentos
+    /// * `contract` - O contrato a verificar
+    /// * `party_matched` - Modo dos conflitos declarados (ver `ConflictSearcher`)
+    ///
+    /// # Retorna
+    /// `true` se a cláusula inicial contém tags em conflito
+    pub fn has_static_conflict(contract: &Contract, party_matched: bool) -> bool {
+        let Some(clause) = contract.get_full_contract() else {
+            return false;
+        };
+        let searcher =
+            ConflictSearcher::new(contract.individuals.clone(), contract.get_all_conflicts())
+                .with_party_matched(party_matched);
+        // Estado avulso, sem passar pelo contador global de ids
+        let mut state = State {
+            id: 0,
+            clause: Some(clause),
+            situation: StateSituation::NotChecked,
+            conflict_information: None,
+            trace: None,
+        };
+        searcher.has_conflict(&mut state)
+    }
+
+    /// Menor subconjunto de cláusulas que ainda conflita (delta debugging)
+    ///
+    /// Parte de todas as cláusulas, ordenadas pelo texto para um resultado estável, e
+    /// tenta descartar blocos cada vez menores, mantendo o complemento sempre que ele
+    /// ainda conflita segundo `has_static_conflict`. Termina quando nenhuma cláusula
+    /// pode ser retirada isoladamente, então o resultado é 1-mínimo (não necessariamente
+    /// o menor de todos). Os conflitos e as partes declarados são mantidos em todos os
+    /// subconjuntos.
+    ///
+    /// # Argumentos
+    /// * `contract` - O contrato a reduzir
+    /// * `party_matched` - Modo dos conflitos declarados (ver `ConflictSearcher`)
+    ///
+    /// # Retorna
+    /// As cláusulas responsáveis, ou `None` se o estado inicial do contrato não conflita
+    pub fn minimal_conflict_set(contract: &Contract, party_matched: bool) -> Option<Vec<Clause>> {
+        let conflicts = |clauses: &[Clause]| {
+            let mut subset = Contract::with_clauses(clauses.to_vec());
+            subset.global_conflicts = contract.global_conflicts.clone();
+            subset.relativized_conflicts = contract.relativized_conflicts.clone();
+            for party in &contract.parties {
+                subset.add_party(*party);
+            }
+            Self::has_static_conflict(&subset, party_matched)
+        };
+
+        let mut clauses: Vec<Clause> = contract.clauses.iter().cloned().collect();
+        clauses.sort_by_cached_key(|c| c.to_string());
+        if !conflicts(&clauses) {
+            return None;
+        }
+
+        let mut granularity = 2;
+        while clauses.len() >= 2 {
+            let chunk = clauses.len().div_ceil(granularity);
+            let mut reduced = false;
+
+            for start in (0..clauses.len()).step_by(chunk) {
+                let end = (start + chunk).min(clauses.len());
+                let complement: Vec<Clause> = clauses[..start]
+                    .iter()
+                    .chain(&clauses[end..])
+                    .cloned()
+                    .collect();
+                if conflicts(&complement) {
+                    clauses = complement;
+                    granularity = (granularity - 1).max(2);
+                    reduced = true;
+                    break;
+                }
+            }
+
+            if !reduced {
+                if granularity >= clauses.len() {
+                    break;
+                }
+                granularity = (granularity * 2).min(clauses.len());
+            }
+        }
+
+        Some(clauses)
+    }
+
+    /// Tags deônticas em vigor em uma cláusula, como um único conjunto
+    ///
+    /// # Argumentos
+    /// * `clause` - A cláusula de um estado
+    ///
+    /// # Retorna
+    /// A união das tags de todas as subcláusulas (após processar ações compostas)
+    pub fn clause_tags(&self, clause: &Clause) -> FxHashSet<DeonticTag> {
+        let processed_clause = ClauseDecomposer::process_composed_actions(clause);
+        self.extract_tags(&processed_clause)
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
     /// Função Delta que retorna todas as tags deônticas de uma cláusula
     ///
     /// # Argumentos
@@ -81,7 +209,14 @@ impl ConflictSearcher {
         } = clause
         {
             let mut dt = FxHashSet::default();
-            let basic_actions = action.get_basic_actions();
+            // SKIP e VIOLATION não são ações executáveis: O(VIOLATION) já é uma violação
+            // inevitável e F(VIOLATION) é trivialmente satisfeita pela decomposição, então
+            // não geram tags nem participam de conflitos
+            let basic_actions: Vec<BasicAction> = action
+                .get_basic_actions()
+                .into_iter()
+                .filter(|ba| ba.value > 0)
+                .collect();
 
             match relativization_type {
                 RelativizationType::Global => {
@@ -103,7 +238,9 @@ impl ConflictSearcher {
                 }
             }
 
-            result.push(dt);
+            if !dt.is_empty() {
+                result.push(dt);
+            }
         }
 
         if let Some(composition) = clause.get_composition() {
@@ -179,9 +316,34 @@ impl ConflictSearcher {
                 match conflict.conflict_type {
                     ConflictType::Global => {
                         for &deontic_type in types {
-                            result.extend(self.generate_tags_by_type(
+                            // No modo `party_matched`, `b` herda as partes da tag
+                            let other = if self.party_matched {
+                                DeonticTag::new(
+                                    deontic_type,
+                                    conflict.b.clone(),
+                                    tag.relativization,
+                                    tag.sender,
+                                    tag.receiver,
+                                )
+                            } else {
+                                DeonticTag::global(deontic_type, conflict.b.clone())
+                            };
+                            result.extend(self.generate_tags_by_type(deontic_type, &other));
+                        }
+                    }
+
+                    ConflictType::Scoped(party) => {
+                        // Uma tag global também obriga o indivíduo do escopo
+                        let sender = match tag.relativization {
+                            RelativizationType::Global => party,
+                            _ if conflict.applies_to(tag.sender, tag.receiver) => tag.sender,
+                            _ => continue,
+                        };
+                        for &deontic_type in types {
+                            result.extend(self.generate_relativized_tags(
                                 deontic_type,
-                                &DeonticTag::global(deontic_type, conflict.b.clone()),
+                                &conflict.b,
+                                sender,
                             ));
                         }
                     }
@@ -314,3 +476,82 @@ impl ConflictSearcher {
         tags
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{build, build_with, parse, serial};
+
+    #[test]
+    fn minimal_conflict_set_keeps_only_the_clashing_clauses() {
+        let _table = serial();
+        let contract = parse(
+            "{buyer,seller}O(pay);
+             {seller,buyer}O(deliver);
+             {buyer,seller}P(cancel);
+             {seller,buyer}F(deliver);",
+        );
+        assert!(ConflictSearcher::has_static_conflict(&contract, false));
+
+        let minimal: Vec<String> = ConflictSearcher::minimal_conflict_set(&contract, false)
+            .expect("the contract conflicts")
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        let expected: Vec<String> = parse("{seller,buyer}F(deliver);\n{seller,buyer}O(deliver);")
+            .clauses
+            .iter()
+            .map(|c| c.to_string())
+            .collect::<std::collections::BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        assert_eq!(minimal, expected);
+
+        let conflict_free = parse("{buyer,seller}O(pay);\n{seller,buyer}O(deliver);");
+        assert!(ConflictSearcher::minimal_conflict_set(&conflict_free, false).is_none());
+    }
+
+    #[test]
+    fn party_scoped_conflict_fires_only_for_its_party() {
+        let _table = serial();
+        let contract = |scope: &str, parties: &str| {
+            format!(
+                "conflict {{ relativized {{ (ship, hold){} }}; }};\n{{{p}}}O(ship);\n{{{p}}}O(hold);",
+                scope,
+                p = parties
+            )
+        };
+
+        let scoped = parse(&contract("{seller}", "seller,buyer"));
+        assert_eq!(scoped.relativized_conflicts.len(), 1);
+        assert!(matches!(scoped.relativized_conflicts[0].conflict_type, ConflictType::Scoped(_)));
+
+        // The seller is bound by the scoped conflict; buyer and carrier are not
+        assert!(build(&contract("{seller}", "seller,buyer")).conflict_found);
+        assert!(!build(&contract("{seller}", "buyer,carrier")).conflict_found);
+        // Without the scope the same pair conflicts for anyone
+        assert!(build(&contract("", "buyer,carrier")).conflict_found);
+    }
+
+    #[test]
+    fn party_matched_mode_drops_the_cross_party_conflict() {
+        let _table = serial();
+        let contract = |shipper: &str| {
+            format!(
+                "conflict {{ global {{ (pay, ship) }}; }};\n{{buyer,seller}}O(pay);\n{{{}}}O(ship);",
+                shipper
+            )
+        };
+        let cross_party = contract("carrier,agent");
+        let same_parties = contract("buyer,seller");
+
+        // Default: the declared pair clashes whoever the parties are
+        assert!(build(&cross_party).conflict_found);
+        assert!(ConflictSearcher::has_static_conflict(&parse(&cross_party), false));
+
+        // Party-matched: only norms on the same parties clash
+        assert!(!build_with(&cross_party, &["--party-matched-conflicts"]).conflict_found);
+        assert!(!ConflictSearcher::has_static_conflict(&parse(&cross_party), true));
+        assert!(build_with(&same_parties, &["--party-matched-conflicts"]).conflict_found);
+    }
+}
@@ -0,0 +1,865 @@
+//! Helpers of the batch and sidecar commands that don't need a Tauri handle.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use rayon::prelude::*;
+
+use crate::algorithms::automata_constructor::AutomataConstructor;
+use crate::utils::{
+    apply_rc_file, escape_csv_field, fnv1a_hex, get_automaton_data, parse_command_line,
+    print_result, FileUtil, Logger, SymbolTable, CSV_SCHEMA_VERSION, DEFAULT_CSV_DELIMITER,
+};
+
+/// Number of most recent files used for the batch ETA average.
+const BATCH_ETA_WINDOW: usize = 5;
+
+/// Estimated time left for `remaining` files from the per-file durations so far,
+/// averaged over the last `BATCH_ETA_WINDOW` files. The first file is left out
+/// since it also pays the sidecar's cold start, so no ETA is given until a
+/// second file has finished.
+pub fn estimate_eta_ms(durations: &[u64], remaining: usize) -> Option<u64> {
+    let warm = durations.get(1..).filter(|d| !d.is_empty())?;
+    let window = &warm[warm.len().saturating_sub(BATCH_ETA_WINDOW)..];
+    let average = window.iter().sum::<u64>() / window.len() as u64;
+    Some(average * remaining as u64)
+}
+
+/// Contract files (see `FileUtil::is_contract_file`) directly inside `folder`,
+/// sorted by path so batch runs always go through them in the same order.
+pub fn list_contract_files(folder: &Path) -> Result<Vec<String>, String> {
+    if !folder.is_dir() {
+        return Err("Path is not a directory".to_string());
+    }
+
+    let entries = fs::read_dir(folder).map_err(|e| e.to_string())?;
+    let mut files = Vec::new();
+    for entry in entries {
+        let entry = entry.map_err(|e| e.to_string())?;
+        let path = entry.path();
+        if path.is_file() && FileUtil::is_contract_file(&path.to_string_lossy()) {
+            files.push(path.to_string_lossy().to_string());
+        }
+    }
+
+    if files.is_empty() {
+        return Err("No .rcl files found in the directory".to_string());
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// `fail_fast` of the batch commands: remembers the first failing file and
+/// tells the loop whether to stop there.
+pub struct FailFast {
+    enabled: bool,
+    first_failure: Option<String>,
+}
+
+impl FailFast {
+    pub fn new(enabled: bool) -> Self {
+        FailFast { enabled, first_failure: None }
+    }
+
+    /// Records that `file_name` failed; `true` if the batch has to stop at it.
+    pub fn failed(&mut self, file_name: &str) -> bool {
+        if self.enabled && self.first_failure.is_none() {
+            self.first_failure = Some(file_name.to_string());
+        }
+        self.enabled
+    }
+
+    /// Error ending a batch stopped at its first failure, `None` if it wasn't.
+    pub fn error(&self, results_path: &Path) -> Option<String> {
+        self.first_failure.as_ref().map(|file_name| {
+            format!(
+                "Batch analysis stopped: {} failed. Partial results saved to {}",
+                file_name,
+                results_path.display()
+            )
+        })
+    }
+}
+
+const BATCH_CONFIG_FILE: &str = "batch_config.json";
+
+/// Per-file analyzer options for batch mode, read from `batch_config.json` in the
+/// analyzed folder. Keys are file name patterns (`*` matches any sequence of
+/// characters) and values are the extra options for matching files, e.g.
+/// `{ "big_*.rcl": ["-n"], "loop.rcl": ["-c"] }`.
+pub fn load_batch_config(folder: &Path) -> Result<BTreeMap<String, Vec<String>>, String> {
+    let config_path = folder.join(BATCH_CONFIG_FILE);
+    if !config_path.exists() {
+        return Ok(BTreeMap::new());
+    }
+
+    let content = fs::read_to_string(&config_path)
+        .map_err(|e| format!("Failed to read {}: {}", BATCH_CONFIG_FILE, e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| format!("Invalid {}: {}", BATCH_CONFIG_FILE, e))
+}
+
+/// Options configured for `file_name`, in pattern order, without duplicates.
+fn batch_file_options(config: &BTreeMap<String, Vec<String>>, file_name: &str) -> Vec<String> {
+    let mut options: Vec<String> = Vec::new();
+    for (pattern, pattern_options) in config {
+        let re_pattern = format!("^{}$", regex::escape(pattern).replace(r"\*", ".*"));
+        let matches = regex::Regex::new(&re_pattern)
+            .map(|re| re.is_match(file_name))
+            .unwrap_or(false);
+        if matches {
+            for option in pattern_options {
+                if !options.contains(option) {
+                    options.push(option.clone());
+                }
+            }
+        }
+    }
+    options
+}
+
+/// Analyzer arguments for one batch file: `args`, then `extra_args` and the
+/// options `batch_config.json` sets for `file_name`, skipping repeated options.
+pub fn batch_file_args(
+    mut args: Vec<String>,
+    extra_args: &[String],
+    config: &BTreeMap<String, Vec<String>>,
+    file_name: &str,
+) -> Vec<String> {
+    for option in extra_args.iter().cloned().chain(batch_file_options(config, file_name)) {
+        if !args.contains(&option) {
+            args.push(option);
+        }
+    }
+    args
+}
+
+/// `-n` or `--prunning`: explicit either way, so the interface's choice overrides
+/// a `no_pruning` key in the `.recallrc`.
+pub fn pruning_arg(use_pruning: bool) -> String {
+    if use_pruning { "--prunning" } else { "-n" }.to_string()
+}
+
+/// Directory for the analyzer's `.result`/`.log` files during an in-memory batch,
+/// so nothing is written next to the contracts.
+pub fn in_memory_log_dir() -> PathBuf {
+    std::env::temp_dir().join("recall_batch_logs")
+}
+
+/// Analyzer arguments for `file_path` in an in-memory batch: its logs go to
+/// `log_dir` and no exports are requested, then `batch_file_args` adds the rest.
+pub fn in_memory_args(
+    file_path: &str,
+    log_dir: &Path,
+    use_pruning: bool,
+    extra_args: &[String],
+    config: &BTreeMap<String, Vec<String>>,
+) -> Vec<String> {
+    let file_name = Path::new(file_path)
+        .file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or(file_path);
+    let mut args = vec![
+        file_path.to_string(),
+        "-t".to_string(),
+        "--log-dir".to_string(),
+        log_dir.to_string_lossy().to_string(),
+    ];
+    args.push(pruning_arg(use_pruning));
+    batch_file_args(args, extra_args, config, file_name)
+}
+
+/// Contract fingerprint printed by the analyzer (`FINGERPRINT:` line), empty if
+/// it stopped before loading the contract.
+pub fn output_fingerprint(stdout: &str) -> String {
+    stdout
+        .lines()
+        .find_map(|l| l.trim().strip_prefix("FINGERPRINT:"))
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// `RESULT_CSV` data and final summary of a batch file, or its error message
+/// (stderr, else the `CRITICAL:` line) if the analyzer produced no result.
+/// A result announced with a `CSV_SCHEMA:` other than `CSV_SCHEMA_VERSION` is an
+/// error, since its columns would be read wrong.
+pub fn batch_output(stdout_full: &str, stderr: String) -> Result<(String, String), String> {
+    let mut summary = String::new();
+    let mut in_summary = false;
+    for line in stdout_full.lines() {
+        if line.trim() == "FINAL_SUMMARY_START" {
+            in_summary = true;
+            continue;
+        }
+        if line.trim() == "FINAL_SUMMARY_END" {
+            in_summary = false;
+            continue;
+        }
+        if in_summary {
+            summary.push_str(line);
+            summary.push('\n');
+        }
+    }
+    let summary = summary.trim().to_string();
+
+    let stdout = stdout_full.lines()
+        .find(|l| l.trim().starts_with("RESULT_CSV:"))
+        .map(|l| l.trim().replace("RESULT_CSV:", ""))
+        .unwrap_or_default();
+
+    if !stdout.is_empty() {
+        let schema = stdout_full.lines()
+            .find_map(|l| l.trim().strip_prefix("CSV_SCHEMA:"))
+            .and_then(|version| version.trim().parse::<u32>().ok());
+        return match schema {
+            Some(CSV_SCHEMA_VERSION) => Ok((stdout, summary)),
+            Some(version) => Err(format!(
+                "RESULT_CSV schema version {} does not match the expected {}",
+                version, CSV_SCHEMA_VERSION
+            )),
+            None => Err(format!(
+                "RESULT_CSV without a schema version (expected {})",
+                CSV_SCHEMA_VERSION
+            )),
+        };
+    }
+
+    if !stderr.is_empty() {
+        return Err(stderr);
+    }
+    Err(stdout_full.lines()
+        .find(|l| l.contains("CRITICAL:"))
+        .map(|l| l.trim().to_string())
+        .unwrap_or_else(|| "Unknown error or interrupted".to_string()))
+}
+
+/// Largest contract file analysed in-process; bigger ones go to the sidecar.
+pub const IN_PROCESS_MAX_BYTES: u64 = 16 * 1024;
+
+/// Most relativized actions (see `AutomataConstructor::estimate`) of a contract
+/// analysed in-process. Each state may have up to 2^n successors, so anything
+/// bigger goes to the sidecar, where the memory guard can kill a runaway analysis.
+pub const IN_PROCESS_MAX_ACTIONS: usize = 10;
+
+/// Analyzer options supported in-process, and whether they take a value. Anything
+/// else (exports, `--threads`, `--memory-limit`, ...) needs the sidecar.
+const IN_PROCESS_OPTIONS: [(&str, bool); 14] = [
+    ("-t", false),
+    ("-v", false),
+    ("--no-verbose", false),
+    ("-n", false),
+    ("--prunning", false),
+    ("-c", false),
+    ("--no-continue", false),
+    ("--no-idle", false),
+    ("--party-matched-conflicts", false),
+    ("--log-dir", true),
+    ("--batch-size", true),
+    ("--max-clause-size", true),
+    ("--max-action-depth", true),
+    ("--timeout", true),
+];
+
+/// Whether `args` (contract first, `.recallrc` already applied) only use options
+/// supported in-process, with values `parse_command_line` accepts. Anything it would
+/// reject has to reach the sidecar, since it exits the process on a usage error.
+fn in_process_supported(args: &[String]) -> bool {
+    let mut rest = args.iter().skip(1);
+    while let Some(arg) = rest.next() {
+        let Some(&(option, takes_value)) = IN_PROCESS_OPTIONS.iter().find(|(o, _)| o == arg) else {
+            return false;
+        };
+        if !takes_value {
+            continue;
+        }
+        let valid = match (option, rest.next()) {
+            (_, None) => false,
+            ("--log-dir", Some(dir)) => !dir.starts_with('-'),
+            ("--batch-size", Some(value)) => value.parse::<usize>().is_ok(),
+            (_, Some(value)) => value.parse::<u64>().is_ok_and(|n| n > 0),
+        };
+        if !valid {
+            return false;
+        }
+    }
+    true
+}
+
+/// Result of a batch file analysed in-process, as the sidecar path reports it.
+pub struct InProcessResult {
+    /// `RESULT_CSV` data and final summary, or the error message (see `batch_output`)
+    pub outcome: Result<(String, String), String>,
+    /// Contract fingerprint, empty if the contract could not be loaded
+    pub fingerprint: String,
+    pub time_ms: u64,
+}
+
+/// Analyses one batch file (`args` as built by `in_memory_args`) inside this
+/// process, with its own symbol table (`SymbolTable::isolated`).
+///
+/// Returns `None` when the file has to go to the sidecar instead: it is bigger than
+/// `IN_PROCESS_MAX_BYTES` or `IN_PROCESS_MAX_ACTIONS`, or `args` (or the
+/// `.recallrc`) use an option not in `IN_PROCESS_OPTIONS`. The CPU time and
+/// memory columns are 0, since the process is shared with other analyses.
+pub fn analyze_in_process(args: &[String]) -> Option<InProcessResult> {
+    let start = Instant::now();
+    let size = fs::metadata(args.first()?).ok()?.len();
+    if size > IN_PROCESS_MAX_BYTES {
+        return None;
+    }
+    let args = apply_rc_file(args).ok()?;
+    if !in_process_supported(&args) {
+        return None;
+    }
+    let mut config = parse_command_line(&args);
+    // Keeps the logger off this process's stdout; the logs are still written
+    config.set_csv_only(true);
+
+    SymbolTable::isolated(move || {
+        let analysis = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let contract = match crate::load_contract(&config) {
+                Ok(contract) => contract,
+                Err(err) => return Some((Err(err.to_string()), String::new())),
+            };
+            let fingerprint = contract.fingerprint();
+            let estimate = AutomataConstructor::new(config.clone()).estimate(&contract);
+            if estimate.relativized_actions > IN_PROCESS_MAX_ACTIONS {
+                return None;
+            }
+
+            let outcome = Logger::new(config.clone())
+                .map_err(|err| err.to_string())
+                .and_then(|mut logger| {
+                    let automaton = crate::analyze_contract(contract.clone(), &config, &mut logger)
+                        .map_err(|err| err.to_string())?;
+                    let elapsed = start.elapsed().as_millis() as u64;
+                    let data = get_automaton_data(elapsed, 0, 0, &automaton, &contract, config.csv_delimiter());
+                    let summary = print_result(&automaton, elapsed, 0, 0, 0, None, None);
+                    let summary = match contract.title {
+                        Some(ref title) => format!("\nContract: {}{}", title, summary),
+                        None => summary,
+                    };
+                    Ok((data, Logger::strip_ansi_codes(&summary).trim().to_string()))
+                });
+            Some((outcome, fingerprint))
+        }));
+        match analysis {
+            Ok(analysis) => analysis,
+            Err(panic) => {
+                let message = panic
+                    .downcast_ref::<&str>()
+                    .map(|s| s.to_string())
+                    .or_else(|| panic.downcast_ref::<String>().cloned())
+                    .unwrap_or_else(|| "Unknown panic".to_string());
+                Some((Err(message), String::new()))
+            }
+        }
+    })
+    .flatten()
+    .map(|(outcome, fingerprint)| InProcessResult {
+        outcome,
+        fingerprint,
+        time_ms: start.elapsed().as_millis() as u64,
+    })
+}
+
+/// `analyze_in_process` for every batch file, in parallel (one contract per rayon
+/// thread), in the order of `files`. `None` entries go to the sidecar.
+pub fn analyze_batch_in_process(files: &[Vec<String>]) -> Vec<Option<InProcessResult>> {
+    files.par_iter().map(|args| analyze_in_process(args)).collect()
+}
+
+/// Hash of the options the analyzer will run with for `args` (contract first):
+/// the remaining arguments plus the `.recallrc` options `apply_rc_file` adds.
+/// A reused batch result must have been produced with the same hash.
+pub fn options_hash(args: &[String]) -> String {
+    let effective = apply_rc_file(args).unwrap_or_else(|_| args.to_vec());
+    fnv1a_hex(&effective.get(1..).unwrap_or_default().join("\n"))
+}
+
+/// Columns of the batch report, `;`-separated as the analyzer's `RESULT_CSV` line.
+const REPORT_COLUMNS: &str = "file;wall_ms;states;transitions;individuals;actions;conflicting;conflict_count;automaton_size_mb;max_memory_mb;satisfaction_states;violating_states;conflicting_states;conflict_free_states;not_checked_states;cpu_ms;obs;fingerprint";
+
+/// Header line of the batch report, separated by `delimiter`.
+pub fn report_header(delimiter: char) -> String {
+    format!("{}\n", REPORT_COLUMNS.replace(DEFAULT_CSV_DELIMITER, &delimiter.to_string()))
+}
+
+/// Report line of an analysed file. The analyzer always reports with `;`, so `result`
+/// is re-separated with `delimiter`.
+pub fn report_row(file_name: &str, result: &str, fingerprint: &str, delimiter: char) -> String {
+    format!(
+        "{}{d}{}{d}{}\n",
+        escape_csv_field(file_name, delimiter),
+        result.replace(DEFAULT_CSV_DELIMITER, &delimiter.to_string()),
+        fingerprint,
+        d = delimiter
+    )
+}
+
+/// Report line of a file the analyzer failed on: no metrics, the error in `obs`.
+pub fn report_error_row(
+    file_name: &str,
+    elapsed_ms: u64,
+    error: &str,
+    fingerprint: &str,
+    delimiter: char,
+) -> String {
+    format!(
+        "{}{d}{}{d}{}{d}{} {d}{}\n",
+        escape_csv_field(file_name, delimiter),
+        elapsed_ms,
+        vec!["-"; 14].join(&delimiter.to_string()),
+        escape_csv_field(error, delimiter),
+        fingerprint,
+        d = delimiter
+    )
+}
+
+/// Name of the per-folder batch cache, see `BatchCache`.
+const BATCH_CACHE_FILE: &str = ".recall_cache";
+
+/// Modification time (seconds since the epoch) and size of `path`, the batch cache key.
+fn file_stamp(path: &Path) -> Option<(u64, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    let mtime = metadata
+        .modified()
+        .ok()?
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()?
+        .as_secs();
+    Some((mtime, metadata.len()))
+}
+
+/// Rows of the previous batch run in a folder (`.recall_cache`) and the rows of
+/// the current one, written back by `save`.
+///
+/// A row is keyed by file name and holds the file's mtime and size, the
+/// `options_hash` it ran with, its fingerprint and its `RESULT_CSV` data. A hit
+/// needs the same mtime, size and options, and no parsing at all. A cache written
+/// with another `RESULT_CSV` schema is ignored as a whole.
+#[derive(Default)]
+pub struct BatchCache {
+    previous: HashMap<String, ((u64, u64), String, String, String)>,
+    current: String,
+}
+
+impl BatchCache {
+    /// First line of the cache file, recording the `RESULT_CSV` schema of its rows.
+    fn header() -> String {
+        format!("#schema;{}\n", CSV_SCHEMA_VERSION)
+    }
+
+    /// Reads the cache of `folder`; empty if it is missing or from another schema.
+    pub fn load(folder: &Path) -> Self {
+        let mut cache = BatchCache::default();
+        let Ok(content) = fs::read_to_string(folder.join(BATCH_CACHE_FILE)) else {
+            return cache;
+        };
+        if !content.starts_with(&Self::header()) {
+            return cache;
+        }
+
+        for line in content.lines() {
+            // file;mtime;size;options;fingerprint;<16 RESULT_CSV fields>
+            let fields: Vec<&str> = line.split(';').collect();
+            if fields.len() != 21 {
+                continue;
+            }
+            if let (Ok(mtime), Ok(size)) = (fields[1].parse(), fields[2].parse()) {
+                cache.previous.insert(
+                    fields[0].to_string(),
+                    ((mtime, size), fields[3].to_string(), fields[4].to_string(), fields[5..].join(";")),
+                );
+            }
+        }
+        cache
+    }
+
+    /// `(fingerprint, result)` of `file_path` from the previous run, if the file
+    /// hasn't changed since and `options` are the same.
+    pub fn reuse(&self, file_path: &Path, options: &str) -> Option<(String, String)> {
+        let file_name = file_path.file_name()?.to_str()?;
+        let stamp = file_stamp(file_path)?;
+        self.previous
+            .get(file_name)
+            .filter(|(cached_stamp, cached_options, _, _)| *cached_stamp == stamp && cached_options == options)
+            .map(|(_, _, fingerprint, result)| (fingerprint.clone(), result.clone()))
+    }
+
+    /// Keeps the row of a successful (or reused) file for the next run.
+    pub fn record(&mut self, file_path: &Path, options: &str, fingerprint: &str, result: &str) {
+        let (Some(file_name), Some((mtime, size))) =
+            (file_path.file_name().and_then(|n| n.to_str()), file_stamp(file_path))
+        else {
+            return;
+        };
+        self.current
+            .push_str(&format!("{};{};{};{};{};{}\n", file_name, mtime, size, options, fingerprint, result));
+    }
+
+    /// Replaces the cache of `folder` with the rows recorded in this run.
+    pub fn save(&self, folder: &Path) -> std::io::Result<()> {
+        fs::write(folder.join(BATCH_CACHE_FILE), Self::header() + &self.current)
+    }
+}
+
+/// Environment variable naming an analyzer executable to use instead of the sidecar.
+const ANALYZER_PATH_ENV: &str = "RECALL_ANALYZER_PATH";
+
+/// Analyzer executable to spawn instead of the bundled sidecar: the path set through
+/// `set_analyzer_path` (`configured`), else `RECALL_ANALYZER_PATH` as read by `env`,
+/// else `None` (use the sidecar). Blank paths count as unset.
+pub fn resolve_analyzer_path(
+    configured: Option<String>,
+    env: impl Fn(&str) -> Option<String>,
+) -> Option<String> {
+    configured
+        .or_else(|| env(ANALYZER_PATH_ENV))
+        .filter(|path| !path.trim().is_empty())
+}
+
+/// Runs `op` up to `attempts` times, waiting a little longer after each failure.
+/// `on_retry` is called with the failed attempt number and its error, then
+/// `wait` with the delay before the next attempt (`base_delay` times the attempt).
+pub async fn retry_with_backoff<T, F, L, W, Fut>(
+    attempts: u32,
+    base_delay: Duration,
+    mut op: F,
+    mut on_retry: L,
+    mut wait: W,
+) -> Result<T, String>
+where
+    F: FnMut() -> Result<T, String>,
+    L: FnMut(u32, &str),
+    W: FnMut(Duration) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    let mut last_error = String::new();
+    for attempt in 1..=attempts {
+        match op() {
+            Ok(value) => return Ok(value),
+            Err(e) => {
+                last_error = e;
+                if attempt < attempts {
+                    on_retry(attempt, &last_error);
+                    wait(base_delay * attempt).await;
+                }
+            }
+        }
+    }
+    Err(last_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::pin::pin;
+    use std::task::{Context, Poll, Waker};
+
+    /// Polls a future that never waits on anything.
+    fn ready<T>(future: impl Future<Output = T>) -> T {
+        let mut cx = Context::from_waker(Waker::noop());
+        match pin!(future).poll(&mut cx) {
+            Poll::Ready(value) => value,
+            Poll::Pending => panic!("future should be ready"),
+        }
+    }
+
+    #[test]
+    fn fingerprint_is_read_from_the_analyzer_output() {
+        assert_eq!(output_fingerprint("Loaded\nFINGERPRINT:00ff\nRESULT_CSV:1;2\n"), "00ff");
+        assert_eq!(output_fingerprint("CRITICAL: syntax error\n"), "");
+    }
+
+    #[test]
+    fn options_hash_covers_arguments_and_recallrc() {
+        let folder = std::env::temp_dir().join(format!("recall_options_hash_{}", std::process::id()));
+        fs::create_dir_all(&folder).unwrap();
+        let contract = folder.join("a.rcl").to_string_lossy().into_owned();
+        let args = |extra: &[&str]| {
+            let mut args = vec![contract.clone(), "-t".to_string(), "--no-rc".to_string()];
+            args.extend(extra.iter().map(|a| a.to_string()));
+            args
+        };
+
+        let plain = options_hash(&args(&[]));
+        assert_eq!(plain, options_hash(&args(&[])));
+        assert_ne!(plain, options_hash(&args(&["-n"])));
+
+        // Without `--no-rc`, the folder's `.recallrc` is part of the options
+        let with_rc = |extra: &[&str]| {
+            let mut args = vec![contract.clone(), "-t".to_string()];
+            args.extend(extra.iter().map(|a| a.to_string()));
+            options_hash(&args)
+        };
+        let before = with_rc(&[]);
+        fs::write(folder.join(".recallrc"), "max_action_depth = 12345\n").unwrap();
+        let after = with_rc(&[]);
+        let _ = fs::remove_dir_all(&folder);
+        assert_ne!(before, after);
+    }
+
+    #[test]
+    fn unchanged_file_is_reused_and_touched_file_is_not() {
+        let folder = crate::test_support::temp_dir("batch_cache");
+        let unchanged = folder.join("unchanged.rcl");
+        let touched = folder.join("touched.rcl");
+        fs::write(&unchanged, "{a,b}O(pay);").unwrap();
+        fs::write(&touched, "{a,b}O(pay);").unwrap();
+        let result = vec!["1"; 16].join(";");
+
+        let mut first_run = BatchCache::load(&folder);
+        assert_eq!(first_run.reuse(&unchanged, "opts"), None);
+        first_run.record(&unchanged, "opts", "f1", &result);
+        first_run.record(&touched, "opts", "f2", &result);
+        first_run.save(&folder).unwrap();
+
+        let later = std::time::SystemTime::now() + Duration::from_secs(60);
+        fs::File::options().write(true).open(&touched).unwrap().set_modified(later).unwrap();
+
+        let second_run = BatchCache::load(&folder);
+        assert_eq!(second_run.reuse(&unchanged, "opts"), Some(("f1".to_string(), result.clone())));
+        assert_eq!(second_run.reuse(&touched, "opts"), None);
+        // Other options (or `.recallrc`) than the cached run: analyzed again
+        assert_eq!(second_run.reuse(&unchanged, "other"), None);
+    }
+
+    /// Analyses the contracts of `folder` in batch order, as the batch commands do,
+    /// returning the files analysed and the error of a fail-fast stop.
+    fn run_folder(folder: &Path, fail_fast: bool) -> (Vec<String>, Option<String>) {
+        let mut stop = FailFast::new(fail_fast);
+        let mut analysed = Vec::new();
+        for file in list_contract_files(folder).unwrap() {
+            let file_name = Path::new(&file).file_name().unwrap().to_string_lossy().into_owned();
+            analysed.push(file_name.clone());
+            let failed = crate::analyze(&crate::test_support::config(&file, &[])).is_err();
+            if failed && stop.failed(&file_name) {
+                break;
+            }
+        }
+        (analysed, stop.error(&folder.join("results.csv")))
+    }
+
+    #[test]
+    fn fail_fast_stops_at_the_first_failing_file() {
+        let _table = crate::test_support::serial();
+        let folder = crate::test_support::temp_dir("fail_fast");
+        fs::write(folder.join("1_ok.rcl"), "{a,b}O(pay);").unwrap();
+        fs::write(folder.join("2_broken.rcl"), "{a,b}O(pay").unwrap();
+        fs::write(folder.join("3_ok.rcl"), "{a,b}F(cancel);").unwrap();
+
+        let (analysed, error) = run_folder(&folder, true);
+        assert_eq!(analysed, ["1_ok.rcl", "2_broken.rcl"]);
+        assert!(error.unwrap().starts_with("Batch analysis stopped: 2_broken.rcl failed."));
+
+        // Default: every file is analysed and the batch itself doesn't fail
+        let (analysed, error) = run_folder(&folder, false);
+        assert_eq!(analysed.len(), 3);
+        assert_eq!(error, None);
+    }
+
+    /// `in_memory_args` of `src` written as `name`, ignoring any `.recallrc`.
+    fn in_process_args(name: &str, src: &str, extra: &[&str]) -> Vec<String> {
+        let path = crate::test_support::write_contract(name, src);
+        let log_dir = Path::new(&path).parent().unwrap().to_path_buf();
+        let mut extra: Vec<String> = extra.iter().map(|a| a.to_string()).collect();
+        extra.push("--no-rc".to_string());
+        in_memory_args(&path, &log_dir, true, &extra, &BTreeMap::new())
+    }
+
+    #[test]
+    fn in_process_batch_matches_one_analysis_at_a_time() {
+        let _table = crate::test_support::serial();
+        let contracts = [
+            ("buyer.rcl", "{buyer,seller}O(pay);\n{buyer,seller}[pay]({seller,buyer}O(ship));"),
+            ("clash.rcl", "{a,b}O(x);\n{a,b}F(x);"),
+            ("penalty.rcl", "{buyer,seller}O(pay)_/{buyer,seller}O(fine)/_;\n{seller,buyer}F(cancel);"),
+        ];
+        let files: Vec<Vec<String>> =
+            contracts.iter().map(|(name, src)| in_process_args(name, src, &[])).collect();
+
+        crate::SymbolTable::lock().add_symbol("outsider".to_string(), crate::SymbolType::Individual);
+        let global = crate::SymbolTable::lock().get_dictionary().to_vec();
+        let results = analyze_batch_in_process(&files);
+        // The isolated tables left the global one alone
+        assert_eq!(crate::SymbolTable::lock().get_dictionary(), global.as_slice());
+
+        for (args, result) in files.iter().zip(results) {
+            let result = result.expect("small contract analysed in-process");
+            let (data, summary) = result.outcome.unwrap();
+            let fields: Vec<&str> = data.split(';').collect();
+            let automaton = crate::analyze(&crate::test_support::config(&args[0], &[])).unwrap();
+            assert_eq!(fields[1], automaton.states.len().to_string(), "{}", args[0]);
+            assert_eq!(fields[2], automaton.transitions.len().to_string(), "{}", args[0]);
+            assert_eq!(fields[5], (automaton.conflict_found as u8).to_string());
+            assert_eq!((fields[8], fields[14], fields[15]), ("0.00", "0", "success"));
+            assert!(!result.fingerprint.is_empty());
+            assert!(summary.contains("Completed in"), "{}", summary);
+            assert!(!summary.contains('\x1B'));
+        }
+    }
+
+    #[test]
+    fn in_process_errors_are_reported_like_the_sidecar() {
+        let broken = analyze_in_process(&in_process_args("broken.rcl", "{a,b}O(pay", &[])).unwrap();
+        assert!(broken.outcome.is_err());
+        assert_eq!(broken.fingerprint, "");
+
+        let src = "{a,b}O(pay);\n{a,b}[pay]({b,a}O(deliver));";
+        let limited = analyze_in_process(&in_process_args("limited.rcl", src, &["--max-clause-size", "1"])).unwrap();
+        let error = limited.outcome.unwrap_err();
+        assert!(error.starts_with("CRITICAL: Clause size limit exceeded"), "{}", error);
+        assert!(!limited.fingerprint.is_empty());
+    }
+
+    #[test]
+    fn in_process_leaves_big_or_unsupported_files_to_the_sidecar() {
+        let small = "{a,b}O(pay);";
+        assert!(analyze_in_process(&in_process_args("small.rcl", small, &[])).is_some());
+
+        let big: String = (0..1000).map(|i| format!("{{a,b}}O(action_{});\n", i)).collect();
+        assert!(big.len() as u64 > IN_PROCESS_MAX_BYTES);
+        assert!(analyze_in_process(&in_process_args("big.rcl", &big, &[])).is_none());
+
+        let complex: String = (0..=IN_PROCESS_MAX_ACTIONS).map(|i| format!("{{a,b}}O(a{});\n", i)).collect();
+        assert!(analyze_in_process(&in_process_args("complex.rcl", &complex, &[])).is_none());
+
+        for unsupported in [
+            &["--format=json"][..],
+            &["--threads", "2"],
+            &["--memory-limit", "512"],
+            &["--timeout", "0"],
+            &["--max-clause-size"],
+        ] {
+            let args = in_process_args("options.rcl", small, unsupported);
+            assert!(analyze_in_process(&args).is_none(), "{:?}", unsupported);
+        }
+        assert!(analyze_in_process(&in_process_args("timeout.rcl", small, &["--timeout", "60", "-c"])).is_some());
+
+        let missing = vec!["/nonexistent/missing.rcl".to_string(), "-t".to_string()];
+        assert!(analyze_in_process(&missing).is_none());
+    }
+
+    #[test]
+    fn analyzer_override_comes_from_the_setting_or_the_environment() {
+        let env = |name: &str| (name == "RECALL_ANALYZER_PATH").then(|| "/opt/debug/analyzer".to_string());
+        let no_env = |_: &str| None;
+
+        assert_eq!(resolve_analyzer_path(None, env), Some("/opt/debug/analyzer".to_string()));
+        assert_eq!(resolve_analyzer_path(Some("/custom".to_string()), env), Some("/custom".to_string()));
+        assert_eq!(resolve_analyzer_path(None, no_env), None);
+        assert_eq!(resolve_analyzer_path(Some(" ".to_string()), no_env), None);
+    }
+
+    #[test]
+    fn eta_skips_the_first_file_and_averages_the_last_ones() {
+        assert_eq!(estimate_eta_ms(&[], 10), None);
+        assert_eq!(estimate_eta_ms(&[5000], 9), None);
+        assert_eq!(estimate_eta_ms(&[5000, 100, 300], 4), Some(800));
+        assert_eq!(estimate_eta_ms(&[5000, 100, 300], 0), Some(0));
+        // Only the last BATCH_ETA_WINDOW durations count
+        assert_eq!(estimate_eta_ms(&[5000, 900, 900, 100, 100, 100, 100, 100], 2), Some(200));
+    }
+
+    #[test]
+    fn batch_config_adds_options_to_matching_files() {
+        let folder = std::env::temp_dir().join(format!("recall_batch_config_{}", std::process::id()));
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(folder.join(BATCH_CONFIG_FILE), r#"{ "big_*.rcl": ["-n"], "loop.rcl": ["-c"] }"#).unwrap();
+        let config = load_batch_config(&folder).unwrap();
+        let _ = fs::remove_dir_all(&folder);
+
+        let base = |file: &str| vec![file.to_string(), "-t".to_string()];
+        assert_eq!(batch_file_args(base("big_1.rcl"), &[], &config, "big_1.rcl"), ["big_1.rcl", "-t", "-n"]);
+        assert_eq!(
+            batch_file_args(base("big_1.rcl"), &["-n".to_string(), "-v".to_string()], &config, "big_1.rcl"),
+            ["big_1.rcl", "-t", "-n", "-v"]
+        );
+        assert_eq!(batch_file_args(base("small.rcl"), &[], &config, "small.rcl"), ["small.rcl", "-t"]);
+    }
+
+    #[test]
+    fn missing_batch_config_is_empty() {
+        assert!(load_batch_config(Path::new("/nonexistent/recall")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn spawn_succeeds_after_two_failures() {
+        let mut calls = 0;
+        let mut retries = Vec::new();
+        let mut delays = Vec::new();
+        let result = ready(retry_with_backoff(
+            3,
+            Duration::from_millis(250),
+            || {
+                calls += 1;
+                if calls < 3 { Err(format!("locked {}", calls)) } else { Ok("child") }
+            },
+            |attempt, error| retries.push((attempt, error.to_string())),
+            |delay| {
+                delays.push(delay);
+                std::future::ready(())
+            },
+        ));
+
+        assert_eq!(result, Ok("child"));
+        assert_eq!(calls, 3);
+        assert_eq!(retries, vec![(1, "locked 1".to_string()), (2, "locked 2".to_string())]);
+        assert_eq!(delays, vec![Duration::from_millis(250), Duration::from_millis(500)]);
+    }
+
+    #[test]
+    fn last_error_is_kept_when_attempts_run_out() {
+        let mut retries = 0;
+        let result: Result<(), String> = ready(retry_with_backoff(
+            3,
+            Duration::ZERO,
+            || Err("locked".to_string()),
+            |_, _| retries += 1,
+            |_| std::future::ready(()),
+        ));
+
+        assert_eq!(result, Err("locked".to_string()));
+        assert_eq!(retries, 2);
+    }
+
+    #[test]
+    fn comma_delimited_report_keeps_every_row_aligned() {
+        let header = report_header(',');
+        let columns = header.trim_end().split(',').count();
+        assert_eq!(columns, REPORT_COLUMNS.split(';').count());
+        assert!(!header.contains(';'));
+
+        let result = (0..15).map(|i| i.to_string()).chain(["success".to_string()]);
+        let result = result.collect::<Vec<_>>().join(";");
+        let row = report_row("a,b.rcl", &result, "f00d", ',');
+        assert_eq!(row.trim_end().split(',').count(), columns);
+        assert!(row.starts_with("a;b.rcl,0,1,"));
+
+        let error = report_error_row("x.rcl", 12, "expected ',' or ';'\nat line 3", "", ',');
+        assert_eq!(error.trim_end().split(',').count(), columns);
+        assert_eq!(error.lines().count(), 1);
+    }
+
+    #[test]
+    fn result_with_another_schema_is_an_error() {
+        let output = |schema: &str| {
+            format!("{}RESULT_CSV:1;2;3\nFINAL_SUMMARY_START\ndone\nFINAL_SUMMARY_END\n", schema)
+        };
+        let current = format!("CSV_SCHEMA:{}\n", CSV_SCHEMA_VERSION);
+        assert_eq!(
+            batch_output(&output(&current), String::new()),
+            Ok(("1;2;3".to_string(), "done".to_string()))
+        );
+
+        let newer = format!("CSV_SCHEMA:{}\n", CSV_SCHEMA_VERSION + 1);
+        let error = batch_output(&output(&newer), String::new()).unwrap_err();
+        assert!(error.contains("does not match"), "{}", error);
+        let error = batch_output(&output(""), String::new()).unwrap_err();
+        assert!(error.contains("without a schema version"), "{}", error);
+    }
+}
@@ -1,17 +1,135 @@
-use recall_lib::parser::{build_ast, RCLParser, Rule};
-use recall_lib::utils::{parse_command_line, Logger, MemoryGuard, LogType, AutomatonExporter};
+use recall_lib::error::AnalysisError;
+use recall_lib::parser::extract_commented_clauses;
+use recall_lib::utils::{parse_command_line, Baseline, CSV_SCHEMA_VERSION, Logger, MemoryGuard, LogType, AutomatonExporter, DotSource, ExportFormat, RunConfiguration};
 use recall_lib::algorithms::automata_constructor::AutomataConstructor;
+use recall_lib::algorithms::conflict_searcher::ConflictSearcher;
 use recall_lib::model::contracts::Contract;
-use pest::Parser;
-use std::time::Instant;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, AtomicU64};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Exit code when `--deny-warnings` is set and the contract has warnings
+const EXIT_DENIED_WARNINGS: i32 = 3;
+
+/// Exit code when `--baseline` is set and the metrics differ from the baseline
+const EXIT_BASELINE_MISMATCH: i32 = 8;
+
+/// Quiet period after the last save before `--watch` re-runs the analysis
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Exit code after Ctrl+C (128 + SIGINT)
+const EXIT_INTERRUPTED: i32 = 130;
+
+/// What the Ctrl+C handler needs from the analysis in progress
+struct Interrupt {
+    logger: Logger,
+    start: Instant,
+    guard_stop: Option<Arc<AtomicBool>>,
+    max_rss: Option<Arc<AtomicU64>>,
+}
+
+/// Analysis in progress, if any; set by `run` and cleared when it returns
+static INTERRUPT: Mutex<Option<Interrupt>> = Mutex::new(None);
+
+/// Temp contract written for `--clause`, removed with its outputs before exiting
+static CLAUSE_FILE: Mutex<Option<String>> = Mutex::new(None);
+
+/// Removes the `--clause` temp contract and the files written next to it, if any
+fn remove_clause_files() {
+    if let Some(path) = CLAUSE_FILE.lock().ok().and_then(|mut file| file.take()) {
+        recall_lib::utils::FileUtil::remove_clause_files(&path);
+    }
+}
+
+/// Exits with `code` once the `--clause` temp files are gone
+fn exit(code: i32) -> ! {
+    remove_clause_files();
+    std::process::exit(code);
+}
+
+/// Clears `INTERRUPT` however `run` returns
+struct InterruptScope;
+
+impl Drop for InterruptScope {
+    fn drop(&mut self) {
+        if let Ok(mut interrupt) = INTERRUPT.lock() {
+            *interrupt = None;
+        }
+    }
+}
+
+/// Ctrl+C: stops the memory guard, prints a partial summary, flushes the logs and exits
+fn on_interrupt() {
+    use std::sync::atomic::Ordering;
+
+    let interrupt = INTERRUPT.lock().ok().and_then(|mut i| i.take());
+    if let Some(interrupt) = interrupt {
+        if let Some(ref stop) = interrupt.guard_stop {
+            stop.store(true, Ordering::Relaxed);
+        }
+        let max_rss = interrupt.max_rss.map_or(0, |rss| rss.load(Ordering::Relaxed));
+        let summary = format!(
+            "\n[INTERRUPTED] Analysis stopped by the user after {}ms; the result is incomplete.\nMax RAM: {}MB\n",
+            interrupt.start.elapsed().as_millis(),
+            max_rss
+        );
+        println!("FINAL_SUMMARY_START");
+        interrupt.logger.log(LogType::Minimal, &summary);
+        println!("FINAL_SUMMARY_END");
+        interrupt.logger.flush();
+    }
+    let _ = std::io::stdout().flush();
+    exit(EXIT_INTERRUPTED);
+}
+
+/// Reports an analysis error on stderr and exits with its exit code
+fn fail(err: AnalysisError) -> ! {
+    eprintln!("{}", err);
+    exit(err.exit_code());
+}
+
+/// Prints a machine-readable protocol line (`PREFIX:payload`), unless `--csv-only`
+/// restricts stdout to the bare CSV line
+fn protocol_line(config: &RunConfiguration, line: &str) {
+    if !config.is_csv_only() {
+        println!("{}", line);
+    }
+}
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let args: Vec<String> = std::env::args().collect();
+    let mut args: Vec<String> = std::env::args().collect();
     if args.len() < 2 {
         eprintln!("Usage: analyzer <contract_file> [options]");
         std::process::exit(1);
     }
 
+    if args[1] == "--example" {
+        let Some(dir) = args.get(2) else {
+            eprintln!("Usage: analyzer --example <directory>");
+            std::process::exit(1);
+        };
+        for path in recall_lib::utils::FileUtil::write_examples(dir)? {
+            println!("Example written to {}", path);
+        }
+        return Ok(());
+    }
+
+    // A single clause is analysed as a one-clause contract written to a temp file,
+    // which then takes the place of the contract argument. The file is only written
+    // once the options are valid, and its `.recallrc` is looked up in the working
+    // directory rather than the temp one
+    let clause = if args[1] == "--clause" {
+        let Some(clause) = args.get(2).cloned() else {
+            eprintln!("Usage: analyzer --clause \"<rcl clause>\" [options]");
+            std::process::exit(1);
+        };
+        args.splice(1..3, [recall_lib::utils::FileUtil::clause_contract_path()]);
+        Some(clause)
+    } else {
+        None
+    };
+
     std::panic::set_hook(Box::new(|info| {
         let payload = info.payload();
         let msg = if let Some(s) = payload.downcast_ref::<&str>() {
@@ -23,18 +141,91 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         };
         
         eprintln!("{}", msg);
-        std::process::exit(101);
+        exit(101);
     }));
-    let config_args = args[1..].to_vec();
+    if let Err(err) = ctrlc::set_handler(on_interrupt) {
+        eprintln!("Could not install the Ctrl+C handler: {}", err);
+    }
+
+    let rc_args = if clause.is_some() {
+        let cwd = std::env::current_dir().unwrap_or_else(|_| ".".into());
+        recall_lib::utils::apply_rc_file_in(&args[1..], &cwd)
+    } else {
+        recall_lib::utils::apply_rc_file(&args[1..])
+    };
+    let config_args = match rc_args {
+        Ok(config_args) => config_args,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
     let config = parse_command_line(&config_args);
+
+    if let Some(ref clause) = clause {
+        if let Ok(mut file) = CLAUSE_FILE.lock() {
+            *file = Some(config.contract_file_name().to_string());
+        }
+        recall_lib::utils::FileUtil::write_clause_contract(clause)?;
+    }
+
+    if config.is_watch() {
+        // Errors are reported and the next save tries again
+        if let Err(err) = run(&config) {
+            eprintln!("{}", err);
+        }
+        println!("Watching {} for changes (Ctrl+C to stop)", config.contract_file_name());
+        std::io::stdout().flush()?;
+        let watched = recall_lib::utils::FileUtil::watch(config.contract_file_name(), WATCH_DEBOUNCE, || {
+            // Each run starts from a fresh table, as a new process would
+            recall_lib::utils::SymbolTable::lock().clear();
+            println!("Contract changed, analysing again...");
+            if let Err(err) = run(&config) {
+                eprintln!("{}", err);
+            }
+            let _ = std::io::stdout().flush();
+            true
+        });
+        remove_clause_files();
+        watched?;
+        return Ok(());
+    }
+
+    let result = run(&config);
+    remove_clause_files();
+    if let Err(err) = result {
+        match err.downcast::<AnalysisError>() {
+            Ok(err) => fail(*err),
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(())
+}
+
+/// Runs one full analysis of the configured contract and prints its results
+fn run(config: &RunConfiguration) -> Result<(), Box<dyn std::error::Error>> {
     let logger = Logger::new(config.clone())?;
 
-    let mut constructor = AutomataConstructor::new(config.clone());
+    let constructor = AutomataConstructor::new(config.clone());
     let start = Instant::now();
+    let cpu_start = recall_lib::utils::process_cpu_time_ms();
+
+    let _interrupt_scope = InterruptScope;
+    if let Ok(mut interrupt) = INTERRUPT.lock() {
+        *interrupt = Some(Interrupt {
+            logger: logger.clone(),
+            start,
+            guard_stop: None,
+            max_rss: None,
+        });
+    }
     let mut analyzer_logger = logger.clone();
 
     let (total_ram_mb, total_swap_mb) = recall_lib::utils::get_system_memory_info();
-    let max_process_mb = recall_lib::utils::calculate_safe_memory_limit(total_ram_mb, total_swap_mb);
+    let max_process_mb = config
+        .memory_limit()
+        .unwrap_or_else(|| recall_lib::utils::calculate_safe_memory_limit(total_ram_mb, total_swap_mb));
     analyzer_logger.log(LogType::Necessary, "Memory guard active:");
     analyzer_logger.log(LogType::Necessary, &format!("   - System RAM: {}MB", total_ram_mb));
     analyzer_logger.log(LogType::Necessary, &format!("   - System Swap: {}MB", total_swap_mb));
@@ -44,72 +235,376 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         (max_process_mb as f64 / (total_ram_mb + total_swap_mb) as f64) * 100.0
     ));
 
-    analyzer_logger.log(LogType::Necessary, &format!("Using {:?}", config));
+    analyzer_logger.log(LogType::Necessary, &format!("Using {}", config));
     analyzer_logger.log(LogType::Necessary, &format!("Analysing contract in {}", config.contract_file_name()));
 
-    let mut file = recall_lib::utils::FileUtil::open_protected(config.contract_file_name(), false, false, false)?;
-    let mut input_string = String::new();
-    use std::io::Read;
-    file.read_to_string(&mut input_string)?;
-    let mut pairs = RCLParser::parse(Rule::main, &input_string)?;
-    let main_pair = pairs.next().unwrap();
-    let contract: Contract = build_ast(main_pair)?;
+    let mut contract: Contract = recall_lib::load_contract(config)?;
+
+    if let Some(other_file) = config.merge_file_name() {
+        // Loaded against the same symbol table, so shared names get the same ids
+        let mut other_config = config.clone();
+        other_config.set_contract_file_name(other_file.to_string());
+        let other = recall_lib::load_contract(&other_config)?;
+        contract = contract.merge(&other);
+        contract.validate();
+        analyzer_logger.log(LogType::Necessary, &format!("Merged with {}", other_file));
+    }
+
+    // Taken from here by batch mode, so the GUI never has to parse contracts itself
+    protocol_line(config, &format!("FINGERPRINT:{}", contract.fingerprint()));
+
+    if config.is_echo_command() {
+        // Normalized flags plus the input fingerprint are enough to reproduce this run
+        let command: Vec<String> = config
+            .command_line()
+            .into_iter()
+            .map(|arg| if arg.contains(char::is_whitespace) { format!("'{}'", arg) } else { arg })
+            .collect();
+        analyzer_logger.log(LogType::Necessary, &format!("Command: recall {}", command.join(" ")));
+        analyzer_logger.log(LogType::Necessary, &format!("Fingerprint: {}", contract.fingerprint()));
+    }
+
+    if config.is_sort_symbols() {
+        // Renumber once, before anything is formatted or analysed
+        let ids = recall_lib::utils::SymbolTable::lock().normalize();
+        contract.remap_symbols(&ids);
+    }
+
+    if config.is_anonymize() {
+        // Only names change, so every later format and export picks them up
+        recall_lib::utils::SymbolTable::lock().anonymize();
+        // The warnings were formatted while loading, still with the original names
+        contract.validate();
+    }
+
+    if config.is_print_ast() {
+        println!("{}", recall_lib::utils::print_ast(&contract));
+        return Ok(());
+    }
+
+    if config.is_stats() {
+        let input_string = recall_lib::utils::FileUtil::read_contract(config.contract_file_name())?;
+        contract
+            .disabled_clauses
+            .extend(extract_commented_clauses(&input_string));
+        analyzer_logger.log(LogType::Necessary, &recall_lib::utils::print_stats(&contract));
+    }
 
     analyzer_logger.log(LogType::Necessary, &format!("Loaded Contract: \n{}", contract));
 
-    let symbol_table_wrapped = recall_lib::utils::SymbolTable::instance();
-    let table = symbol_table_wrapped.lock().unwrap();
+    if config.is_strict() {
+        let mut strict_warnings = contract.lint_relativization();
+        strict_warnings.extend(contract.lint_unpenalized());
+        contract.warnings.extend(strict_warnings);
+    }
+
+    for warning in &contract.warnings {
+        analyzer_logger.log(LogType::Minimal, &format!("[WARNING] {}", warning));
+    }
+
+    let table = recall_lib::utils::SymbolTable::lock();
     analyzer_logger.log(LogType::Necessary, &format!("{}", *table));
     drop(table);
 
+    if config.is_estimate() {
+        let estimate = constructor.estimate(&contract);
+        analyzer_logger.log(LogType::Minimal, &estimate.to_string());
+        protocol_line(config, &format!("ESTIMATE_JSON:{}", serde_json::to_string(&estimate)?));
+        std::io::stdout().flush()?;
+        return Ok(());
+    }
+
+    if config.is_minimal_conflict() {
+        let clauses = ConflictSearcher::minimal_conflict_set(&contract, config.is_party_matched_conflicts());
+        let texts: Option<Vec<String>> = clauses.map(|c| c.iter().map(|c| c.to_string()).collect());
+        match texts {
+            Some(ref texts) => {
+                analyzer_logger.log(LogType::Minimal, &format!("Minimal conflicting clauses ({}):", texts.len()));
+                for text in texts {
+                    analyzer_logger.log(LogType::Minimal, &format!("   - {}", text));
+                }
+            }
+            None => analyzer_logger.log(
+                LogType::Minimal,
+                "No conflict in the initial state; a minimal set needs a conflict visible without any transition",
+            ),
+        }
+        protocol_line(config, &format!("MINIMAL_CONFLICT_JSON:{}", serde_json::to_string(&texts)?));
+        std::io::stdout().flush()?;
+        return Ok(());
+    }
+
     analyzer_logger.log(LogType::Necessary, "Processing contract...");
 
     let memory_guard = MemoryGuard::new(max_process_mb, analyzer_logger.clone());
-    let _guard_handle = memory_guard.start_monitoring();
+    let guard_handle = memory_guard.start_monitoring();
+    if let Ok(mut interrupt) = INTERRUPT.lock() {
+        if let Some(ref mut interrupt) = *interrupt {
+            interrupt.guard_stop = Some(guard_handle.clone());
+            interrupt.max_rss = Some(memory_guard.max_rss_used.clone());
+        }
+    }
     
-    let automaton = constructor.process(contract.clone(), &mut analyzer_logger);
+    let analysis = recall_lib::analyze_contract(contract.clone(), config, &mut analyzer_logger);
+    // Stop the monitor thread so repeated runs under --watch don't pile them up
+    guard_handle.store(true, std::sync::atomic::Ordering::Relaxed);
+    let automaton = analysis?;
     let elapsed = start.elapsed();
+    let cpu_ms = recall_lib::utils::process_cpu_time_ms().saturating_sub(cpu_start);
+
+    // Only logged: a permission-only contract is valid, so it must not fail `--deny-warnings`
+    if !automaton.conflict_found && automaton.is_vacuous() {
+        analyzer_logger.log(
+            LogType::Minimal,
+            "WARNING: contract may be vacuously satisfied (nothing it requires can be violated)",
+        );
+    }
+
+    if config.is_verify() {
+        let failures = automaton.find_dedup_failures();
+        for ids in &failures {
+            let ids: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+            analyzer_logger.log(
+                LogType::Minimal,
+                &format!("VERIFY: states {} have equivalent clauses (dedup failure)", ids.join(", ")),
+            );
+        }
+        if failures.is_empty() {
+            analyzer_logger.log(LogType::Necessary, "VERIFY: no duplicate states");
+        }
+    }
+
+    for state in automaton.get_unavoidable_violations() {
+        analyzer_logger.log(
+            LogType::Necessary,
+            &format!("State {} unavoidably leads to a violation: {}", state.id, state),
+        );
+    }
 
     use recall_lib::utils::get_automaton_data;
     use std::sync::atomic::Ordering;
     let max_rss = memory_guard.max_rss_used.load(Ordering::Relaxed);
-    let data = get_automaton_data(elapsed.as_millis() as u64, max_rss, &automaton, &contract);
+    let data = get_automaton_data(elapsed.as_millis() as u64, cpu_ms, max_rss, &automaton, &contract, config.csv_delimiter());
 
     use recall_lib::utils::print_result;
     let max_total = memory_guard.max_total_used.load(Ordering::Relaxed);
+    let certificate = config
+        .is_certificate()
+        .then(|| recall_lib::utils::print_certificate(&automaton, &contract));
+    let cycles = config.is_cycles().then(|| automaton.cyclic_components());
     let result_summary = print_result(
         &automaton,
         elapsed.as_millis() as u64,
+        cpu_ms,
         max_rss,
         max_total,
+        certificate.as_deref(),
+        cycles.as_deref(),
     );
+    let result_summary = match contract.title {
+        Some(ref title) => format!("\nContract: {}{}", title, result_summary),
+        None => result_summary,
+    };
     
-    // Use markers to help the main process extract the final summary
-    println!("FINAL_SUMMARY_START");
-    analyzer_logger.log(LogType::Minimal, &result_summary);
-    println!("FINAL_SUMMARY_END");
+    // Use markers to help the main process extract the final summary.
+    // Holding the stdout lock keeps other threads (e.g. the memory guard) from
+    // interleaving lines inside the block; the logger's own println! re-enters it.
+    if !config.is_csv_only() {
+        let stdout = std::io::stdout();
+        let mut out = stdout.lock();
+        writeln!(out, "FINAL_SUMMARY_START")?;
+        out.flush()?;
+        analyzer_logger.log(LogType::Minimal, &result_summary);
+        writeln!(out, "FINAL_SUMMARY_END")?;
+        out.flush()?;
+    } else {
+        // Quiet logger: the summary still reaches the log files
+        analyzer_logger.log(LogType::Minimal, &result_summary);
+    }
     
+    if config.is_print_states() {
+        analyzer_logger.log(LogType::Minimal, &AutomatonExporter::dump_states_table(&automaton));
+    }
+
     analyzer_logger.log(LogType::Minimal, "Analysis completed successfully");
+
+    protocol_line(config, &format!("CONFLICTS_JSON:{}", recall_lib::utils::get_conflicts_json(&automaton)));
+    std::io::stdout().flush()?;
+
+    if config.is_deontic_tags() {
+        let searcher = ConflictSearcher::new(contract.individuals.clone(), contract.get_all_conflicts());
+        let tags = automaton.all_deontic_tags(&searcher);
+        analyzer_logger.log(LogType::Necessary, &format!("{} distinct deontic tag(s) in force", tags.len()));
+        protocol_line(config, &format!("DEONTIC_TAGS_JSON:{}", recall_lib::utils::get_deontic_tags_json(&tags)));
+        std::io::stdout().flush()?;
+    }
+
+    if let Some(limit) = config.satisfaction_paths() {
+        let paths = automaton.satisfaction_paths(limit);
+        analyzer_logger.log(LogType::Necessary, &format!("Found {} path(s) to satisfaction states", paths.len()));
+        protocol_line(config, &format!("SATISFACTION_PATHS_JSON:{}", recall_lib::utils::get_satisfaction_paths_json(&automaton, &paths)));
+        std::io::stdout().flush()?;
+    }
+
+    if let Some(id) = config.explain_transition() {
+        match automaton.explain_transition(id) {
+            Some(explanation) => {
+                analyzer_logger.log(LogType::Minimal, &explanation.to_string());
+                protocol_line(config, &format!("EXPLAIN_JSON:{}", serde_json::to_string(&explanation)?));
+            }
+            None => {
+                analyzer_logger.log(LogType::Minimal, &format!("No transition with id {}", id));
+                protocol_line(config, "EXPLAIN_JSON:null");
+            }
+        }
+        std::io::stdout().flush()?;
+    }
+
+    if let Some(trace_file) = config.trace_file_name() {
+        let trace = recall_lib::utils::load_trace(trace_file)?;
+        let outcome = automaton.run_trace(&trace);
+        analyzer_logger.log(LogType::Necessary, &format!("Trace in {}: {}", trace_file, outcome));
+        protocol_line(config, &format!("TRACE_JSON:{}", recall_lib::utils::get_trace_json(&outcome)));
+        std::io::stdout().flush()?;
+    }
     
+    let dot_source = DotSource {
+        contract_file: config.contract_file_name(),
+        fingerprint: contract.fingerprint(),
+        title: contract.title.as_deref(),
+        description: contract.description.as_deref(),
+    };
+
     if config.is_export_automaton() {
-        let dot = AutomatonExporter::dump_to_dot(&automaton);
+        let dot = AutomatonExporter::dump_to_dot_with(
+            &automaton,
+            config.is_collapse_epsilon(),
+            Some(&dot_source),
+            config.tooltip_length(),
+            config.is_group_by_party(),
+        );
         let path = config.automaton_file_name();
         let mut file = recall_lib::utils::FileUtil::open_protected(path, true, false, true)?;
-        use std::io::Write;
         file.write_all(dot.as_bytes())?;
         analyzer_logger.log(LogType::Necessary, &format!("Automaton exported to {}", path));
+
+        // Tooltips may be truncated, so the full clauses go alongside the graph
+        let clauses = AutomatonExporter::dump_state_clauses(&automaton);
+        let path = config.state_clauses_file_name();
+        let mut file = recall_lib::utils::FileUtil::open_protected(&path, true, false, true)?;
+        file.write_all(clauses.as_bytes())?;
+        analyzer_logger.log(LogType::Necessary, &format!("State clauses exported to {}", path));
     }
 
     if config.is_export_min_automaton() {
-        let dot = AutomatonExporter::dump_to_min_dot(&automaton);
+        let dot = AutomatonExporter::dump_to_min_dot(
+            &automaton,
+            Some(&dot_source),
+            config.tooltip_length(),
+            config.is_group_by_party(),
+        );
         let path = config.min_automaton_file_name();
         let mut file = recall_lib::utils::FileUtil::open_protected(path, true, false, true)?;
-        use std::io::Write;
         file.write_all(dot.as_bytes())?;
         analyzer_logger.log(LogType::Necessary, &format!("Minimized automaton exported to {}", path));
     }
 
-    println!("RESULT_CSV:{}", data);
+    if config.is_conflicts_csv() {
+        let content = AutomatonExporter::dump_conflicts_csv(&automaton);
+        let path = config.conflicts_csv_file_name();
+        let mut file = recall_lib::utils::FileUtil::open_protected(&path, true, false, true)?;
+        file.write_all(content.as_bytes())?;
+        analyzer_logger.log(LogType::Necessary, &format!("Conflicts exported to {}", path));
+    }
+
+    if config.is_decision_table() {
+        let content = AutomatonExporter::dump_decision_table(&automaton);
+        let path = config.decision_table_file_name();
+        let mut file = recall_lib::utils::FileUtil::open_protected(&path, true, false, true)?;
+        file.write_all(content.as_bytes())?;
+        analyzer_logger.log(LogType::Necessary, &format!("Decision table exported to {}", path));
+    }
+
+    if config.is_conflict_subgraph() {
+        let dot = AutomatonExporter::dump_conflict_subgraph_dot_with(
+            &automaton,
+            Some(&dot_source),
+            config.tooltip_length(),
+            config.is_group_by_party(),
+        );
+        let path = config.conflict_subgraph_file_name();
+        let mut file = recall_lib::utils::FileUtil::open_protected(&path, true, false, true)?;
+        file.write_all(dot.as_bytes())?;
+        analyzer_logger.log(LogType::Necessary, &format!("Conflict subgraph exported to {}", path));
+    }
+
+    if config.is_export_contract() {
+        let content = AutomatonExporter::dump_contract(&automaton, &contract);
+        let path = config.contract_export_file_name();
+        let mut file = recall_lib::utils::FileUtil::open_protected(&path, true, false, true)?;
+        file.write_all(content.as_bytes())?;
+        analyzer_logger.log(LogType::Necessary, &format!("Analysed contract written to {}", path));
+    }
+
+    for format in config.export_formats() {
+        // DOT is already covered by the -g export above
+        if *format == ExportFormat::Dot {
+            continue;
+        }
+        let content = AutomatonExporter::export_with(
+            &automaton,
+            *format,
+            Some(&dot_source),
+            config.is_group_by_party(),
+        );
+        let path = config.export_file_name(*format);
+        let mut file = recall_lib::utils::FileUtil::open_protected(&path, true, false, true)?;
+        file.write_all(content.as_bytes())?;
+        analyzer_logger.log(LogType::Necessary, &format!("Automaton ({}) exported to {}", format, path));
+        protocol_line(
+            config,
+            &format!("EXPORT_JSON:{}", serde_json::json!({ "format": format.name(), "path": path })),
+        );
+    }
+
+    if config.is_csv_only() {
+        println!("{}", data);
+    } else {
+        // Lets consumers detect a RESULT_CSV column layout they don't know
+        println!("CSV_SCHEMA:{}", CSV_SCHEMA_VERSION);
+        println!("RESULT_CSV:{}", data);
+    }
+    std::io::stdout().flush()?;
+
+    if let Some(baseline_path) = config.baseline_file_name() {
+        let baseline = Baseline::parse(&std::fs::read_to_string(baseline_path)?)?;
+        let file_name = std::path::Path::new(config.contract_file_name())
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        match baseline.compare(&file_name, &data, config.csv_delimiter()) {
+            None => eprintln!("{} is not in the baseline {}", file_name, baseline_path),
+            Some(mismatches) if !mismatches.is_empty() => {
+                for mismatch in &mismatches {
+                    eprintln!("Baseline mismatch in {}: {}", file_name, mismatch);
+                }
+                if !config.is_watch() {
+                    exit(EXIT_BASELINE_MISMATCH);
+                }
+            }
+            Some(_) => analyzer_logger.log(LogType::Necessary, &format!("Metrics match the baseline {}", baseline_path)),
+        }
+    }
+
+    if config.is_deny_warnings() && !contract.warnings.is_empty() {
+        eprintln!(
+            "{} warning(s) treated as errors (--deny-warnings)",
+            contract.warnings.len()
+        );
+        if !config.is_watch() {
+            exit(EXIT_DENIED_WARNINGS);
+        }
+    }
 
     Ok(())
 }
@@ -0,0 +1,1433 @@
+use crate::batch::{
+    analyze_batch_in_process, batch_file_args, batch_output, estimate_eta_ms, in_memory_args,
+    in_memory_log_dir, list_contract_files, load_batch_config, options_hash, output_fingerprint, pruning_arg,
+    report_error_row, report_header, report_row, resolve_analyzer_path, retry_with_backoff,
+    BatchCache, FailFast,
+};
+use crate::utils::{
+    parse_csv_delimiter, Baseline, FileUtil, LogPayload, LogType, SystemMemory,
+    CSV_SCHEMA_VERSION, DEFAULT_CSV_DELIMITER,
+};
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use serde::Serialize;
+use tauri_plugin_dialog::DialogExt;
+use tauri_plugin_shell::ShellExt;
+use tauri_plugin_shell::process::CommandEvent;
+use tauri::{Emitter, Manager};
+use std::sync::Mutex;
+use tauri_plugin_shell::process::CommandChild;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Default)]
+pub struct AnalysisState {
+    pub child_processes: Mutex<HashMap<String, CommandChild>>,
+    pub stopped_processes: Mutex<HashSet<String>>,
+    pub locked_files: Mutex<HashMap<String, Vec<std::fs::File>>>,
+    /// Analyzer executable to run instead of the bundled sidecar (see `analyzer_override`)
+    pub analyzer_path: Mutex<Option<String>>,
+}
+
+#[derive(Clone, Serialize)]
+struct SymbolEntry {
+    id: String,
+    symbol_type: String,
+    value: String,
+}
+
+#[derive(Clone, Serialize)]
+struct BatchProgress {
+    file: String,
+    status: String,
+    result: Option<String>,
+    time_ms: Option<u64>,
+    eta_ms: Option<u64>,
+    progress: f32,
+}
+
+const SIDECAR_SPAWN_ATTEMPTS: u32 = 3;
+const SIDECAR_RETRY_DELAY_MS: u64 = 250;
+
+/// Analyzer executable overriding the bundled sidecar, see `resolve_analyzer_path`.
+fn analyzer_override(app: &tauri::AppHandle) -> Option<String> {
+    let state = app.state::<AnalysisState>();
+    let configured = state.analyzer_path.lock().unwrap().clone();
+    resolve_analyzer_path(configured, |name| std::env::var(name).ok())
+}
+
+/// Spawns the analyzer sidecar (or its override, see `analyzer_override`),
+/// retrying transient failures (e.g. the binary briefly locked by an antivirus
+/// scan on Windows).
+async fn spawn_analyzer(
+    app: &tauri::AppHandle,
+    args: Vec<String>,
+) -> Result<(tauri::async_runtime::Receiver<CommandEvent>, CommandChild), String> {
+    let analyzer_path = analyzer_override(app);
+    retry_with_backoff(
+        SIDECAR_SPAWN_ATTEMPTS,
+        Duration::from_millis(SIDECAR_RETRY_DELAY_MS),
+        || {
+            let command = match &analyzer_path {
+                Some(path) => app.shell().command(path),
+                None => app.shell().sidecar("analyzer").map_err(|e| e.to_string())?,
+            };
+            command
+                .args(args.clone())
+                .spawn()
+                .map_err(|e| e.to_string())
+        },
+        |attempt, error| {
+            let _ = app.emit("log-message", LogPayload {
+                log_type: LogType::Necessary,
+                message: format!(
+                    "Failed to spawn analyzer (attempt {}/{}): {}. Retrying...",
+                    attempt, SIDECAR_SPAWN_ATTEMPTS, error
+                ),
+                date: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            });
+        },
+        |delay| async move {
+            let _ = tauri::async_runtime::spawn_blocking(move || std::thread::sleep(delay)).await;
+        },
+    )
+    .await
+}
+
+/// Sets (or clears, with `None`) the analyzer executable used instead of the
+/// bundled sidecar, e.g. a debug build. Takes precedence over `RECALL_ANALYZER_PATH`.
+#[tauri::command]
+async fn set_analyzer_path(state: tauri::State<'_, AnalysisState>, path: Option<String>) -> Result<(), String> {
+    if let Some(ref path) = path {
+        if !Path::new(path).is_file() {
+            return Err(format!("Analyzer not found: {}", path));
+        }
+    }
+    *state.analyzer_path.lock().unwrap() = path;
+    Ok(())
+}
+
+#[tauri::command]
+async fn select_directory(app: tauri::AppHandle) -> Result<Option<String>, String> {
+    let folder = app.dialog().file().blocking_pick_folder();
+    if let Some(folder_path) = folder {
+        return Ok(Some(folder_path.to_string()));
+    }
+    Ok(None)
+}
+
+/// Runs the analyzer on one batch file, registered as `batch_analysis` so that
+/// `stop_analysis` can kill it, and returns its whole stdout and stderr.
+///
+/// Big or complex files always get their own process, so the memory guard can kill
+/// a runaway analysis; small ones are analysed in-process by the in-memory batch
+/// (see `analyze_batch_in_process`).
+async fn run_batch_sidecar(app: &tauri::AppHandle, args: Vec<String>) -> Result<(String, String), String> {
+    let state = app.state::<AnalysisState>();
+
+    // Use spawn to allow killing the process later
+    let (mut rx, child) = spawn_analyzer(app, args)
+        .await
+        .map_err(|e| format!("Failed to spawn batch sidecar: {}", e))?;
+
+    // Register batch process
+    {
+        let mut processes = state.child_processes.lock().unwrap();
+        processes.insert("batch_analysis".to_string(), child);
+    }
+
+    let mut stdout_full = String::new();
+    let mut stderr = String::new();
+
+    while let Some(event) = rx.recv().await {
+        match event {
+            CommandEvent::Stdout(line_bytes) => {
+                stdout_full.push_str(&String::from_utf8_lossy(&line_bytes));
+            }
+            CommandEvent::Stderr(line_bytes) => {
+                stderr.push_str(&String::from_utf8_lossy(&line_bytes));
+            }
+            _ => {}
+        }
+    }
+
+    // Unregister after file is done
+    {
+        let mut processes = state.child_processes.lock().unwrap();
+        processes.remove("batch_analysis");
+    }
+
+    Ok((stdout_full, stderr))
+}
+
+#[derive(Clone, Serialize)]
+struct BatchFileResult {
+    file: String,
+    /// "Success" or "Error", as in `batch-progress`
+    status: String,
+    /// `RESULT_CSV` data, for successful files
+    result: Option<String>,
+    /// `CSV_SCHEMA_VERSION` of `result`, for successful files
+    schema_version: Option<u32>,
+    summary: String,
+    /// Error message, for failed files
+    error: Option<String>,
+    fingerprint: String,
+    time_ms: u64,
+}
+
+/// Batch analysis of `folder_path` that returns every file's result instead of
+/// writing the `<folder>_<timestamp>.csv` report and the `.recall_cache` file.
+/// Nothing is written into the folder: the analyzer logs go to a temporary
+/// directory and no exports are requested. The UI may save the results itself
+/// with `save_file`. Progress is reported through `batch-progress` as usual.
+///
+/// Small files are first analysed in parallel inside the app, each with its own
+/// symbol table (`analyze_batch_in_process`); only the rest spawn the sidecar.
+#[tauri::command]
+async fn run_batch_analysis_in_memory(
+    app: tauri::AppHandle,
+    folder_path: String,
+    use_pruning: bool,
+    fail_fast: bool,
+    extra_args: Option<Vec<String>>,
+) -> Result<Vec<BatchFileResult>, String> {
+    let state = app.state::<AnalysisState>();
+
+    // Clear stop flag before starting
+    {
+        let mut stopped = state.stopped_processes.lock().unwrap();
+        stopped.remove("batch_analysis");
+    }
+
+    let path = Path::new(&folder_path);
+    let files = list_contract_files(path)?;
+    let batch_config = load_batch_config(path)?;
+    let total = files.len() as f32;
+
+    let log_dir = in_memory_log_dir();
+    fs::create_dir_all(&log_dir).map_err(|e| format!("Failed to create {}: {}", log_dir.display(), e))?;
+
+    let file_args: Vec<Vec<String>> = files
+        .iter()
+        .map(|file_path| {
+            in_memory_args(
+                file_path,
+                &log_dir,
+                use_pruning,
+                extra_args.as_deref().unwrap_or(&[]),
+                &batch_config,
+            )
+        })
+        .collect();
+    let in_process_args = file_args.clone();
+    let mut in_process =
+        tauri::async_runtime::spawn_blocking(move || analyze_batch_in_process(&in_process_args))
+            .await
+            .map_err(|e| format!("In-process batch analysis failed: {}", e))?;
+
+    let mut results = Vec::with_capacity(files.len());
+    let mut durations: Vec<u64> = Vec::with_capacity(files.len());
+    let mut stop = FailFast::new(fail_fast);
+
+    for (i, (file_path, args)) in files.iter().zip(file_args).enumerate() {
+        if state.stopped_processes.lock().unwrap().contains("batch_analysis") {
+            break;
+        }
+
+        let file_name = Path::new(file_path).file_name().and_then(|s| s.to_str()).unwrap_or(file_path);
+
+        let _ = app.emit("batch-progress", BatchProgress {
+            file: file_path.to_string(),
+            status: "Processing".to_string(),
+            result: None,
+            time_ms: None,
+            eta_ms: estimate_eta_ms(&durations, files.len() - i),
+            progress: (i as f32) / total,
+        });
+
+        let (fingerprint, outcome, elapsed) = match in_process[i].take() {
+            Some(analysed) => (analysed.fingerprint, analysed.outcome, analysed.time_ms),
+            None => {
+                let start = Instant::now();
+                let (stdout_full, stderr) = run_batch_sidecar(&app, args).await?;
+                let elapsed = start.elapsed().as_millis() as u64;
+                if state.stopped_processes.lock().unwrap().contains("batch_analysis") {
+                    break;
+                }
+                (output_fingerprint(&stdout_full), batch_output(&stdout_full, stderr), elapsed)
+            }
+        };
+        durations.push(elapsed);
+
+        let failed = outcome.is_err();
+        let result = match outcome {
+            Ok((data, summary)) => BatchFileResult {
+                file: file_name.to_string(),
+                status: "Success".to_string(),
+                result: Some(data),
+                schema_version: Some(CSV_SCHEMA_VERSION),
+                summary,
+                error: None,
+                fingerprint,
+                time_ms: elapsed,
+            },
+            Err(error) => BatchFileResult {
+                file: file_name.to_string(),
+                status: "Error".to_string(),
+                result: None,
+                schema_version: None,
+                summary: String::new(),
+                error: Some(error),
+                fingerprint,
+                time_ms: elapsed,
+            },
+        };
+
+        let _ = app.emit("batch-progress", BatchProgress {
+            file: file_path.to_string(),
+            status: result.status.clone(),
+            result: match (&result.result, &result.error) {
+                (Some(data), _) => Some(format!("{};SUMMARY_DATA:{}", data, result.summary)),
+                (None, error) => error.clone(),
+            },
+            time_ms: Some(elapsed),
+            eta_ms: estimate_eta_ms(&durations, files.len() - (i + 1)),
+            progress: (i + 1) as f32 / total,
+        });
+        results.push(result);
+
+        if failed && stop.failed(file_name) {
+            break;
+        }
+    }
+
+    Ok(results)
+}
+
+#[tauri::command]
+async fn run_batch_analysis(
+    app: tauri::AppHandle, 
+    folder_path: String,
+    export_automaton: bool,
+    export_min_automaton: bool,
+    use_pruning: bool,
+    fail_fast: bool,
+    extra_args: Option<Vec<String>>
+) -> Result<String, String> {
+    let state = app.state::<AnalysisState>();
+    
+    // Clear stop flag before starting
+    {
+        let mut stopped = state.stopped_processes.lock().unwrap();
+        stopped.remove("batch_analysis");
+    }
+
+    let path = Path::new(&folder_path);
+    let files = list_contract_files(path)?;
+
+    let batch_config = load_batch_config(path)?;
+
+    use tauri::Emitter;
+    let total = files.len() as f32;
+
+    let folder_name = Path::new(&folder_path)
+        .file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or("batch_results");
+
+    // `--no-cache` is handled here, the analyzer doesn't know it
+    let no_cache = extra_args.iter().flatten().any(|arg| arg == "--no-cache");
+    let mut extra_args: Vec<String> = extra_args
+        .into_iter()
+        .flatten()
+        .filter(|arg| arg != "--no-cache")
+        .collect();
+
+    // `--baseline` is checked here too, so reused rows are compared as well
+    let baseline = match extra_args.iter().position(|arg| arg == "--baseline") {
+        Some(index) => {
+            let file = extra_args
+                .get(index + 1)
+                .cloned()
+                .ok_or("--baseline requires a CSV file")?;
+            extra_args.drain(index..=index + 1);
+            let content = fs::read_to_string(&file)
+                .map_err(|e| format!("Failed to read baseline {}: {}", file, e))?;
+            Some(Baseline::parse(&content)?)
+        }
+        None => None,
+    };
+    let mut baseline_mismatches: Vec<String> = Vec::new();
+    let mut compare_baseline = |file_name: &str, result: &str| -> Option<String> {
+        let mismatches = baseline.as_ref()?.compare(file_name, result, DEFAULT_CSV_DELIMITER)?;
+        if mismatches.is_empty() {
+            return None;
+        }
+        baseline_mismatches.push(file_name.to_string());
+        Some(format!("Baseline mismatch: {}", mismatches.join(", ")))
+    };
+
+    // `--delimiter` only changes the report, the analyzer doesn't get it
+    let delimiter = match extra_args.iter().position(|arg| arg == "--delimiter") {
+        Some(index) => {
+            let delimiter = extra_args
+                .get(index + 1)
+                .and_then(|value| parse_csv_delimiter(value))
+                .ok_or("--delimiter requires a single character (or \"tab\")")?;
+            extra_args.drain(index..=index + 1);
+            delimiter
+        }
+        None => DEFAULT_CSV_DELIMITER,
+    };
+    let mut csv_results = report_header(delimiter);
+
+    // Unchanged contracts reuse the previous row, unless files have to be exported
+    let reuse_results = !no_cache && !export_automaton && !export_min_automaton;
+    let batch_cache = if reuse_results { BatchCache::load(path) } else { BatchCache::default() };
+    let mut new_cache = BatchCache::default();
+
+    let add_log = |message: &str, status: &str| {
+        let _ = app.emit("batch-progress", BatchProgress {
+            file: "".to_string(),
+            status: status.to_string(),
+            result: Some(message.to_string()),
+            time_ms: None,
+            eta_ms: None,
+            progress: 0.0,
+        });
+    };
+
+    let mut durations: Vec<u64> = Vec::with_capacity(files.len());
+    let mut stop = FailFast::new(fail_fast);
+
+    for (i, file_path) in files.iter().enumerate() {
+        // Check if stopped before starting a new file
+        {
+            let stopped = state.stopped_processes.lock().unwrap();
+            if stopped.contains("batch_analysis") {
+                add_log("Batch analysis stopped by user.", "info");
+                break;
+            }
+        }
+
+        let file_name = Path::new(file_path).file_name().and_then(|s| s.to_str()).unwrap_or(file_path);
+
+        let mut args = vec![file_path.clone(), "-t".to_string()];
+        if export_automaton {
+            args.push("-g".to_string());
+        }
+        if export_min_automaton {
+            args.push("-m".to_string());
+        }
+        args.push(pruning_arg(use_pruning));
+        let args = batch_file_args(args, &extra_args, &batch_config, file_name);
+        let options = options_hash(&args);
+
+        // Same mtime, size and options as in the cache: reuse without running the analyzer
+        if let Some((fingerprint, result)) = batch_cache.reuse(Path::new(file_path), &options) {
+            csv_results.push_str(&report_row(file_name, &result, &fingerprint, delimiter));
+            new_cache.record(Path::new(file_path), &options, &fingerprint, &result);
+            if let Some(message) = compare_baseline(file_name, &result) {
+                let _ = app.emit("batch-progress", BatchProgress {
+                    file: file_path.to_string(),
+                    status: "Error".to_string(),
+                    result: Some(message),
+                    time_ms: None,
+                    eta_ms: estimate_eta_ms(&durations, files.len() - (i + 1)),
+                    progress: (i + 1) as f32 / total,
+                });
+                if stop.failed(file_name) {
+                    add_log(&format!("Stopping at {} (fail fast).", file_name), "info");
+                    break;
+                }
+                continue;
+            }
+            let _ = app.emit("batch-progress", BatchProgress {
+                file: file_path.to_string(),
+                status: "Success".to_string(),
+                result: Some(format!("{};SUMMARY_DATA:Unchanged since the previous batch run, result reused.", result)),
+                time_ms: result.split(';').next().and_then(|t| t.parse().ok()),
+                eta_ms: estimate_eta_ms(&durations, files.len() - (i + 1)),
+                progress: (i + 1) as f32 / total,
+            });
+            continue;
+        }
+        
+        let _ = app.emit("batch-progress", BatchProgress {
+            file: file_path.to_string(),
+            status: "Processing".to_string(),
+            result: None,
+            time_ms: None,
+            eta_ms: estimate_eta_ms(&durations, files.len() - i),
+            progress: (i as f32) / total,
+        });
+
+        let start = Instant::now();
+        
+        // Lock the file during its processing time with Windows-safe sharing mode
+        let _rcl_file = FileUtil::open_protected(file_path, false, false, false)
+            .map_err(|e| format!("Failed to open {} for protection: {}", file_name, e))?;
+
+        let (stdout_full, stderr) = run_batch_sidecar(&app, args).await?;
+        let fingerprint = output_fingerprint(&stdout_full);
+
+        let elapsed = start.elapsed().as_millis() as u64;
+        durations.push(elapsed);
+        let eta_ms = estimate_eta_ms(&durations, files.len() - (i + 1));
+
+        // Final check: was it stopped during execution?
+        {
+            let stopped = state.stopped_processes.lock().unwrap();
+            if stopped.contains("batch_analysis") {
+                add_log(&format!("File {} analysis interrupted (stopped).", file_name), "info");
+                break;
+            }
+        }
+
+        match batch_output(&stdout_full, stderr) {
+            Ok((stdout, summary)) => {
+                csv_results.push_str(&report_row(file_name, &stdout, &fingerprint, delimiter));
+                new_cache.record(Path::new(file_path), &options, &fingerprint, &stdout);
+                if let Some(message) = compare_baseline(file_name, &stdout) {
+                    let _ = app.emit("batch-progress", BatchProgress {
+                        file: file_path.to_string(),
+                        status: "Error".to_string(),
+                        result: Some(message),
+                        time_ms: Some(elapsed),
+                        eta_ms,
+                        progress: (i + 1) as f32 / total,
+                    });
+                    if stop.failed(file_name) {
+                        add_log(&format!("Stopping at {} (fail fast).", file_name), "info");
+                        break;
+                    }
+                    continue;
+                }
+                let _ = app.emit("batch-progress", BatchProgress {
+                    file: file_path.to_string(),
+                    status: "Success".to_string(),
+                    result: Some(format!("{};SUMMARY_DATA:{}", stdout, summary)),
+                    time_ms: Some(elapsed),
+                    eta_ms,
+                    progress: (i + 1) as f32 / total,
+                });
+            }
+            Err(error_msg) => {
+                csv_results.push_str(&report_error_row(
+                    file_name, elapsed, &error_msg, &fingerprint, delimiter,
+                ));
+                let _ = app.emit("batch-progress", BatchProgress {
+                    file: file_path.to_string(),
+                    status: "Error".to_string(),
+                    result: Some(error_msg),
+                    time_ms: Some(elapsed),
+                    eta_ms,
+                    progress: (i + 1) as f32 / total,
+                });
+
+                if stop.failed(file_name) {
+                    add_log(&format!("Stopping at {} (fail fast).", file_name), "info");
+                    break;
+                }
+            }
+        }
+    }
+
+    let timestamp = chrono::Local::now().format("%Y-%m-%d_%H-%M-%S").to_string();
+    let results_filename = format!("{}_{}.csv", folder_name, timestamp);
+    let results_path = Path::new(&folder_path).join(results_filename);
+    
+    fs::write(&results_path, &csv_results).map_err(|e| format!("Failed to save results: {}", e))?;
+    if !no_cache {
+        // Not worth failing the batch over; the next run just analyses everything again
+        let _ = new_cache.save(path);
+    }
+
+    if let Some(error) = stop.error(&results_path) {
+        return Err(error);
+    }
+
+    if !baseline_mismatches.is_empty() {
+        return Err(format!(
+            "{} file(s) differ from the baseline: {}. Results saved to {}",
+            baseline_mismatches.len(),
+            baseline_mismatches.join(", "),
+            results_path.display()
+        ));
+    }
+
+    Ok(format!("Batch analysis completed. Results saved to {}", results_path.display()))
+}
+
+async fn run_analysis_internal(app_handle: tauri::AppHandle, path: String, mode: String, export_automaton: bool, export_min_automaton: bool, use_pruning: bool) -> Result<String, String> {
+    use std::sync::{Arc, Mutex};
+
+    // `--events`: live events come back as `EVENT_JSON:` lines, re-emitted below
+    let mut args = vec![path.clone(), "--events".to_string()];
+    // The interface's choices are explicit, so they override a `.recallrc` either way
+    if mode == "Verbose" {
+        args.push("-v".to_string());
+    } else {
+        args.push("--no-verbose".to_string());
+    }
+    if mode == "Test" {
+        args.push("-t".to_string());
+    }
+
+    if export_automaton {
+        args.push("-g".to_string());
+    }
+
+    if export_min_automaton {
+        args.push("-m".to_string());
+    }
+
+    args.push(pruning_arg(use_pruning));
+
+    let (mut rx, child) = spawn_analyzer(&app_handle, args)
+        .await
+        .map_err(|e| format!("Failed to spawn sidecar: {}", e))?;
+
+    // Lock the input file to prevent deletion during analysis (Windows persistent protection)
+    let file = FileUtil::open_protected(&path, false, false, false)
+        .map_err(|e| format!("Failed to open file for protection: {}", e))?;
+
+    // Store the child process and the locked handle for the single analysis
+    {
+        let state = app_handle.state::<AnalysisState>();
+        let mut processes = state.child_processes.lock().unwrap();
+        processes.insert("single_analysis".to_string(), child);
+        
+        let mut locks = state.locked_files.lock().unwrap();
+        locks.insert("single_analysis".to_string(), vec![file]);
+    }
+
+    let stdout_acc = Arc::new(Mutex::new(String::new()));
+    let stderr_acc = Arc::new(Mutex::new(String::new()));
+    
+    let stdout_acc_clone = stdout_acc.clone();
+    let stderr_acc_clone = stderr_acc.clone();
+    let app_clone = app_handle.clone();
+
+    let get_date = || chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let start_instant = std::time::Instant::now();
+
+    while let Some(event) = rx.recv().await {
+        match event {
+            CommandEvent::Stdout(line_bytes) => {
+                let line = String::from_utf8_lossy(&line_bytes).to_string();
+                stdout_acc_clone.lock().unwrap().push_str(&line);
+                
+                for l in line.lines() {
+                    let trimmed = l.trim();
+                    if trimmed == "FINAL_SUMMARY_START" || trimmed == "FINAL_SUMMARY_END" || trimmed.starts_with("RESULT_CSV:") || trimmed.starts_with("CSV_SCHEMA:") || trimmed.starts_with("CONFLICTS_JSON:") || trimmed.starts_with("EXPORT_JSON:") {
+                        continue;
+                    }
+                    // Live events from the analyzer (see `Logger::event`)
+                    if let Some(json) = trimmed.strip_prefix("EVENT_JSON:") {
+                        if let Ok(event) = serde_json::from_str::<serde_json::Value>(json) {
+                            if let Some(name) = event["event"].as_str() {
+                                let _ = app_clone.emit(name, event["payload"].clone());
+                            }
+                        }
+                        continue;
+                    }
+                    let _ = app_clone.emit("log-message", LogPayload {
+                        log_type: LogType::Minimal,
+                        message: l.to_string(),
+                        date: get_date(),
+                    });
+                }
+            }
+            CommandEvent::Stderr(line_bytes) => {
+                let line = String::from_utf8_lossy(&line_bytes).to_string();
+                stderr_acc_clone.lock().unwrap().push_str(&line);
+                
+                for l in line.lines() {
+                    let _ = app_clone.emit("log-message", LogPayload {
+                        log_type: LogType::Necessary,
+                        message: l.to_string(),
+                        date: get_date(),
+                    });
+                }
+            }
+            CommandEvent::Terminated(status) => {
+                let stdout = stdout_acc.lock().unwrap().clone();
+                let stderr = stderr_acc.lock().unwrap().clone();
+                let elapsed = start_instant.elapsed().as_millis();
+
+                // Cleanup here since we return early
+                {
+                    let state = app_handle.state::<AnalysisState>();
+                    let mut processes = state.child_processes.lock().unwrap();
+                    processes.remove("single_analysis");
+                    
+                    let mut locks = state.locked_files.lock().unwrap();
+                    locks.remove("single_analysis");
+                }
+                if status.code == Some(0) {
+                    let mut in_summary = false;
+                    let mut summary_lines = Vec::new();
+                    
+                    for line in stdout.lines() {
+                        let trimmed = line.trim();
+                        if trimmed == "FINAL_SUMMARY_START" {
+                            in_summary = true;
+                            continue;
+                        }
+                        if trimmed == "FINAL_SUMMARY_END" {
+                            in_summary = false;
+                            continue;
+                        }
+                        if in_summary {
+                            summary_lines.push(line);
+                        }
+                    }
+
+                    let csv_line = stdout.lines()
+                        .find(|l| l.trim().starts_with("RESULT_CSV:"))
+                        .map(|l| l.trim().replace("RESULT_CSV:", ""))
+                        .unwrap_or_default();
+
+                    // Structured conflict list, so the UI doesn't need to scrape the trace
+                    if let Some(conflicts) = stdout.lines()
+                        .find_map(|l| l.trim().strip_prefix("CONFLICTS_JSON:"))
+                        .and_then(|json| serde_json::from_str::<serde_json::Value>(json).ok())
+                    {
+                        let _ = app_handle.emit("analysis-conflicts", conflicts);
+                    }
+
+                    let summary = if summary_lines.is_empty() {
+                        // Fallback: everything except CSV and completion markers
+                        stdout.lines()
+                            .filter(|l| {
+                                let t = l.trim();
+                                !t.starts_with("RESULT_CSV:") && 
+                                !t.starts_with("CONFLICTS_JSON:") && 
+                                t != "Analysis completed successfully" && // Match analyzer's success message
+                                t != "FINAL_SUMMARY_START" && 
+                                t != "FINAL_SUMMARY_END"
+                            })
+                            .collect::<Vec<_>>()
+                            .join("\n")
+                    } else {
+                        summary_lines.join("\n")
+                    };
+
+                    let final_result = if !csv_line.is_empty() {
+                        format!("{};SUMMARY_DATA:{}", csv_line, summary.trim())
+                    } else {
+                        // If for some reason CSV wasn't found, we use our manual timer as first column
+                        format!("{};0;0;0;0;0;0;0;0;SUMMARY_DATA:{}", elapsed, summary.trim())
+                    };
+
+                    return Ok(final_result);
+                } else {
+                    let mut is_stopped = false;
+                    {
+                        let state = app_handle.state::<AnalysisState>();
+                        let mut stopped = state.stopped_processes.lock().unwrap();
+                        if stopped.remove("single_analysis") {
+                            is_stopped = true;
+                        }
+                    }
+
+                    if is_stopped {
+                        return Err("Analysis stopped by the user.".to_string());
+                    }
+
+                    let mut error_msg = stderr.trim().to_string();
+                    if error_msg.is_empty() {
+                        error_msg = stdout.lines()
+                            .find(|l| l.contains("CRITICAL:"))
+                            .map(|l| l.trim().to_string())
+                            .unwrap_or_else(|| format!("Analysis failed with exit code {:?}", status.code));
+                    }
+                    
+                    // Return structured error with time
+                    return Ok(format!("{};0;0;0;0;0;0;0;0;ERROR_DATA:{}", elapsed, error_msg));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Remove the process and lock from tracking after finished
+    {
+        let state = app_handle.state::<AnalysisState>();
+        let mut processes = state.child_processes.lock().unwrap();
+        processes.remove("single_analysis");
+
+        let mut locks = state.locked_files.lock().unwrap();
+        locks.remove("single_analysis");
+    }
+
+    Err("Sidecar process closed unexpectedly".to_string())
+}
+
+#[tauri::command]
+async fn process_file(app_handle: tauri::AppHandle, path: String, mode: String, export_automaton: bool, export_min_automaton: bool, use_pruning: bool) -> Result<String, String> {
+    if !std::path::Path::new(&path).exists() {
+        return Err(format!("File not found: {}", path));
+    }
+    run_analysis_internal(app_handle, path, mode, export_automaton, export_min_automaton, use_pruning).await
+}
+
+fn get_next_versioned_stem(parent: &Path, stem: &str) -> String {
+    let mut n = 1;
+    loop {
+        let name = format!("{} ({})", stem, n);
+        let path = parent.join(format!("{}.rcl", name));
+        if !path.exists() {
+            return name;
+        }
+        n += 1;
+    }
+}
+
+#[tauri::command]
+async fn analyze_text(
+    app_handle: tauri::AppHandle,
+    text: String,
+    mode: String,
+    export_automaton: bool,
+    export_min_automaton: bool,
+    use_pruning: bool,
+    origin_path: Option<String>,
+) -> Result<String, String> {
+    use std::path::PathBuf;
+
+    // 1. Determine if the content has changed or is new
+    let mut has_changed = true;
+    let mut original_stem = String::from("contract");
+    let mut base_output_dir: Option<PathBuf> = None;
+
+    if let Some(ref orig) = origin_path {
+        let orig_path = std::path::Path::new(orig);
+        if orig_path.exists() {
+            if let Ok(orig_content) = fs::read_to_string(orig_path) {
+                if orig_content.trim() == text.trim() {
+                    has_changed = false;
+                }
+            }
+            // Strip any existing version numbers like " (1)" OR timestamps like "_2024..." from the stem for base matching
+            let stem_full = orig_path.file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("contract");
+            
+            let re_v = regex::Regex::new(r"\s\(\d+\)$").unwrap();
+            let re_ts = regex::Regex::new(r"_\d{4}-\d{2}-\d{2}_\d{2}-\d{2}-\d{2}$").unwrap();
+            
+            let stem_no_v = re_v.replace(stem_full, "").to_string();
+            original_stem = re_ts.replace(&stem_no_v, "").to_string();
+            
+            base_output_dir = orig_path.parent().map(|p| p.to_path_buf());
+        }
+    }
+
+    // 2. Generate unique and base names for this analysis run
+    let ts = chrono::Local::now().format("%Y-%m-%d_%H-%M-%S").to_string();
+    let temp_stem = format!("recall_analysis_{}", ts);
+    let base_recall_stem = String::from("recall_analysis"); // Used when no original file
+    
+    // Resolve final output directory and stems
+    let re_ts = regex::Regex::new(r"_\d{4}-\d{2}-\d{2}_\d{2}-\d{2}-\d{2}$").unwrap();
+    let re_v = regex::Regex::new(r"\s\(\d+\)$").unwrap();
+
+    let (output_dir, _output_stem_no_ts, output_stem_for_rcl, output_stem_ts): (PathBuf, String, String, String) = if let Some(parent) = base_output_dir {
+        // Case 1 & 2: Selected file
+        let current_stem = Path::new(origin_path.as_ref().unwrap()).file_stem().and_then(|s| s.to_str()).unwrap_or(&original_stem).to_string();
+        
+        if has_changed {
+            // If the current file is already versioned, we keep the SAME versioned name (overwrite)
+            // Otherwise, we create a NEW version (increment)
+            let stem_for_rcl = if re_v.is_match(&current_stem) {
+                current_stem.clone()
+            } else {
+                get_next_versioned_stem(&parent, &original_stem)
+            };
+            
+            let stem_ts = format!("{}_{}", re_ts.replace(&stem_for_rcl, ""), ts);
+            (parent, original_stem, stem_for_rcl, stem_ts)
+        } else {
+            // Unchanged: use the CURRENT stem
+            let current_stem_no_ts = re_ts.replace(&current_stem, "").to_string();
+            let stem_ts = format!("{}_{}", current_stem_no_ts, ts);
+            (parent, original_stem, current_stem, stem_ts)
+        }
+    } else {
+        // Case 3: Pasted contract (no original file) -> use TIMESTAMP for rcl to avoid overwriting
+        let docs_dir: PathBuf = dirs::document_dir()
+            .or_else(|| dirs::home_dir().map(|h| h.join("Documents")))
+            .unwrap_or_else(|| std::env::temp_dir());
+        let recall_dir = docs_dir.join("Recall");
+        let stem_ts = format!("{}_{}", base_recall_stem, ts);
+        (recall_dir, base_recall_stem.clone(), stem_ts.clone(), stem_ts)
+    };
+
+    // 3. Always write analysis content to a TEMP file first
+    let temp_dir = std::env::temp_dir();
+    let temp_rcl = temp_dir.join(format!("{}.rcl", temp_stem));
+    let temp_rcl_str = temp_rcl.to_string_lossy().to_string();
+
+    fs::write(&temp_rcl, &text)
+        .map_err(|e| format!("Failed to create temp analysis file: {}", e))?;
+
+    // 4. Run the analysis using the temp file path
+    let result = run_analysis_internal(
+        app_handle,
+        temp_rcl_str.clone(),
+        mode,
+        export_automaton,
+        export_min_automaton,
+        use_pruning,
+    )
+    .await;
+
+    // 5. Ensure output directory exists
+    if let Err(e) = fs::create_dir_all(&output_dir) {
+        eprintln!("Warning: could not create output dir '{}': {}", output_dir.display(), e);
+    } else {
+        // 6. Copy temp outputs (.result, .log) to final destination
+        
+        // Log is special: APPEND to the log corresponding to the current rcl version
+        let src_log = temp_dir.join(format!("{}.log", temp_stem));
+        let dst_log = output_dir.join(format!("{}.log", output_stem_for_rcl));
+        if src_log.exists() {
+            if let Ok(log_content) = fs::read(&src_log) {
+                use std::io::Write;
+                let mut options = fs::OpenOptions::new();
+                options.create(true).append(true);
+                if let Ok(mut file) = options.open(&dst_log) {
+                    let _ = writeln!(file, "\n--- Analysis Execution: {} ---", ts);
+                    let _ = file.write_all(&log_content);
+                }
+            }
+            let _ = fs::remove_file(&src_log);
+        }
+
+        // Result and others: always WITH timestamp, based on the versioned stem
+        let src_res = temp_dir.join(format!("{}.result", temp_stem));
+        let dst_res = output_dir.join(format!("{}.result", output_stem_ts));
+        if src_res.exists() {
+            let _ = fs::copy(&src_res, &dst_res);
+            let _ = fs::remove_file(&src_res);
+        }
+
+        // Conditionally copy .dot / _min.dot
+        if export_automaton {
+            let src = temp_dir.join(format!("{}.dot", temp_stem));
+            let dst = output_dir.join(format!("{}.dot", output_stem_ts));
+            if src.exists() {
+                let _ = fs::copy(&src, &dst);
+                let _ = fs::remove_file(&src);
+            }
+        }
+        if export_min_automaton {
+            let src = temp_dir.join(format!("{}_min.dot", temp_stem));
+            let dst = output_dir.join(format!("{}_min.dot", output_stem_ts));
+            if src.exists() {
+                let _ = fs::copy(&src, &dst);
+                let _ = fs::remove_file(&src);
+            }
+        }
+
+        // 7. Save the .rcl contract itself WITH versioning (only if modified or new)
+        if has_changed || origin_path.is_none() {
+            let dst_rcl = output_dir.join(format!("{}.rcl", output_stem_for_rcl));
+            let _ = fs::write(&dst_rcl, &text);
+        }
+    }
+
+    // Always clean up the temp .rcl
+    let _ = fs::remove_file(&temp_rcl);
+
+    match result {
+        Ok(res) => {
+            let final_rcl_path = output_dir.join(format!("{}.rcl", output_stem_for_rcl));
+            Ok(format!("{};FILES_PATH:{}", res, final_rcl_path.to_string_lossy()))
+        },
+        Err(e) => Err(e)
+    }
+}
+
+
+#[tauri::command]
+async fn stop_analysis(state: tauri::State<'_, AnalysisState>) -> Result<(), String> {
+    let mut processes = state.child_processes.lock().map_err(|e| e.to_string())?;
+    
+    let mut stopped_any = false;
+
+    // Check single analysis
+    if let Some(child) = processes.remove("single_analysis") {
+        {
+            let mut stopped = state.stopped_processes.lock().unwrap();
+            stopped.insert("single_analysis".to_string());
+        }
+        let _ = child.kill();
+        stopped_any = true;
+    }
+
+    // Check batch analysis
+    if let Some(child) = processes.remove("batch_analysis") {
+        {
+            let mut stopped = state.stopped_processes.lock().unwrap();
+            stopped.insert("batch_analysis".to_string());
+        }
+        let _ = child.kill();
+        stopped_any = true;
+    }
+
+    if stopped_any {
+        Ok(())
+    } else {
+        Err("No active analysis to stop".to_string())
+    }
+}
+
+
+#[tauri::command]
+async fn get_related_files(path: String) -> HashMap<String, String> {
+    let mut related = HashMap::new();
+    let rcl_path = Path::new(&path);
+    let parent = rcl_path.parent().unwrap_or(Path::new(""));
+    let stem = rcl_path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+
+    if stem.is_empty() {
+        return related;
+    }
+
+    let extensions = vec!["log", "result", "dot", "csv", "min_dot"];
+    
+    // Regex for timestamp: _YYYY-MM-DD_HH-MM-SS
+    let re_ts = regex::Regex::new(r"_\d{4}-\d{2}-\d{2}_\d{2}-\d{2}-\d{2}$").unwrap();
+    // Regex for version: (N)
+    let re_v = regex::Regex::new(r"\s\(\d+\)$").unwrap();
+    
+    let stem_no_ts = re_ts.replace(stem, "").to_string();
+    let base_stem = re_v.replace(&stem_no_ts, "").to_string();
+
+    for ext_key in extensions {
+        let ext = if ext_key == "min_dot" { "dot" } else { ext_key };
+        
+        let file_path = if ext_key == "log" {
+            // Log logic: try direct match, versioned log, then base log
+            let logs_to_try = vec![
+                parent.join(format!("{}.log", stem)),
+                parent.join(format!("{}.log", stem_no_ts)),
+                parent.join(format!("{}.log", base_stem)),
+            ];
+            
+            let mut found_log = logs_to_try[0].clone();
+            for log in logs_to_try {
+                if log.exists() {
+                    found_log = log;
+                    break;
+                }
+            }
+            found_log
+        } else {
+            // Other files use the current stem
+            let current_stem = stem;
+            if ext_key == "min_dot" {
+                parent.join(format!("{}_min.dot", current_stem))
+            } else {
+                parent.join(format!("{}.{}", current_stem, ext))
+            }
+        };
+
+        if file_path.exists() {
+            related.insert(ext_key.to_string(), file_path.to_string_lossy().to_string());
+        } else if ext_key != "log" {
+            // If not found and not a log, look for the MOST RECENT timestamped version
+            // Note: we strip any existing TS from 'stem' to keep results tied to the base version (e.g. "contract (1)_TS.result")
+            let stem_prefix = re_ts.replace(stem, "").to_string();
+            if let Ok(entries) = fs::read_dir(parent) {
+                let mut matches = Vec::new();
+                let pattern = if ext_key == "min_dot" {
+                    format!(r"{}(_\d{{4}}-\d{{2}}-\d{{2}}_\d{{2}}-\d{{2}}-\d{{2}})_min\.dot$", regex::escape(&stem_prefix))
+                } else {
+                    format!(r"{}(_\d{{4}}-\d{{2}}-\d{{2}}_\d{{2}}-\d{{2}}-\d{{2}})\.{}$", regex::escape(&stem_prefix), regex::escape(ext))
+                };
+                if let Ok(re_file) = regex::Regex::new(&pattern) {
+                    for entry in entries.flatten() {
+                        let name = entry.file_name().to_string_lossy().to_string();
+                        if re_file.is_match(&name) {
+                            if let Ok(meta) = entry.metadata() {
+                                if let Ok(modified) = meta.modified() {
+                                    matches.push((modified, entry.path().to_string_lossy().to_string()));
+                                }
+                            }
+                        }
+                    }
+                }
+                matches.sort_by(|a, b| b.0.cmp(&a.0)); // Newest first
+                if let Some((_, path)) = matches.into_iter().next() {
+                    related.insert(ext_key.to_string(), path);
+                }
+            }
+        }
+    }
+    
+    related
+}
+
+#[tauri::command]
+async fn get_symbol_table(file_path: String) -> Result<Vec<SymbolEntry>, String> {
+    // We try to read from the .result file first as it's cleaner (overwritten per session)
+    // If not found or empty, fallback to .log (which might be appended)
+    let path = Path::new(&file_path);
+    let parent = path.parent().unwrap_or(Path::new(""));
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+    
+    if stem.is_empty() {
+        return Err("Invalid file path".to_string());
+    }
+
+    let mut result_path = parent.join(format!("{}.result", stem));
+    
+    // Regexes
+    let re_ts = regex::Regex::new(r"_\d{4}-\d{2}-\d{2}_\d{2}-\d{2}-\d{2}$").unwrap();
+    let re_v = regex::Regex::new(r"\s\(\d+\)$").unwrap();
+    
+    let stem_no_ts = re_ts.replace(stem, "").to_string();
+    let base_stem = re_v.replace(&stem_no_ts, "").to_string();
+    
+    // Try direct stem first, then versioned, then base
+    let logs_to_try = vec![
+        parent.join(format!("{}.log", stem)),
+        parent.join(format!("{}.log", stem_no_ts)),
+        parent.join(format!("{}.log", base_stem)),
+    ];
+    let mut log_path = logs_to_try[0].clone();
+    for log in logs_to_try {
+        if log.exists() {
+            log_path = log;
+            break;
+        }
+    }
+
+    // Fallback for result_path if it doesn't exist (e.g. searching from original rcl)
+    if !result_path.exists() {
+        if let Ok(entries) = fs::read_dir(parent) {
+            let mut matches = Vec::new();
+            let stem_prefix = re_ts.replace(stem, "").to_string();
+            let pattern = format!(r"{}(_\d{{4}}-\d{{2}}-\d{{2}}_\d{{2}}-\d{{2}}-\d{{2}})\.result$", regex::escape(&stem_prefix));
+            if let Ok(re_file) = regex::Regex::new(&pattern) {
+                for entry in entries.flatten() {
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    if re_file.is_match(&name) {
+                        if let Ok(meta) = entry.metadata() {
+                            if let Ok(modified) = meta.modified() {
+                                matches.push((modified, entry.path()));
+                            }
+                        }
+                    }
+                }
+            }
+            matches.sort_by(|a, b| b.0.cmp(&a.0));
+            if let Some((_, path)) = matches.into_iter().next() {
+                result_path = path;
+            }
+        }
+    }
+
+    let content = if result_path.exists() {
+        fs::read_to_string(&result_path).map_err(|e| e.to_string())?
+    } else if log_path.exists() {
+        fs::read_to_string(&log_path).map_err(|e| e.to_string())?
+    } else {
+        return Err("No result or log file found for this analysis".to_string());
+    };
+
+    let mut symbols = Vec::new();
+    let mut in_table = false;
+    
+    // Regex matches something like "(1)      action  neo.saveWorld"
+    let re = regex::Regex::new(r"\((\d+)\)\s+(\w+)\s+(.+)").unwrap();
+
+    for line in content.lines() {
+        if line.contains("Table of Symbols") {
+            in_table = true;
+            continue;
+        }
+
+        if in_table {
+            if let Some(caps) = re.captures(line) {
+                symbols.push(SymbolEntry {
+                    id: caps[1].to_string(),
+                    symbol_type: caps[2].to_string(),
+                    value: caps[3].trim().to_string(),
+                });
+            } else if !line.trim().is_empty() && symbols.len() > 0 {
+                // If we hit a non-empty line that doesn't match the regex after we've already found symbols,
+                // we've probably reached the end of the table.
+                // However, the sidecar might log other things. Common end markers?
+                // For now, if it's not a symbol line and we have symbols, let's keep looking or stop if it's a known delimiter.
+                if line.contains("----------------") || line.contains("[") {
+                    // break; // Optional: stop if we hit a log header
+                }
+            }
+        }
+    }
+
+    if symbols.is_empty() {
+        return Err("No symbols found in log".to_string());
+    }
+
+    Ok(symbols)
+}
+
+/// Runs a trace (list of steps, each a list of `[sender, action, receiver]` names)
+/// over the automaton of the contract in `path` and returns the analyzer's
+/// `TRACE_JSON` outcome, e.g. `{"outcome": "violating", "state": 3, "steps": 2}`.
+#[tauri::command]
+async fn run_trace(
+    app: tauri::AppHandle,
+    path: String,
+    trace: Vec<Vec<[String; 3]>>,
+    use_pruning: bool,
+) -> Result<serde_json::Value, String> {
+    let trace_path = std::env::temp_dir().join(format!(
+        "recall_trace_{}_{}.json",
+        std::process::id(),
+        chrono::Local::now().format("%Y%m%d%H%M%S%f")
+    ));
+    let trace_json = serde_json::to_string(&trace).map_err(|e| e.to_string())?;
+    fs::write(&trace_path, trace_json).map_err(|e| format!("Failed to write trace: {}", e))?;
+
+    let mut args = vec![path, format!("--trace={}", trace_path.to_string_lossy())];
+    args.push(pruning_arg(use_pruning));
+
+    let result = analyzer_json_line(&app, args, "TRACE_JSON:").await;
+    let _ = fs::remove_file(&trace_path);
+    result
+}
+
+/// Lists up to `limit` simple paths from the initial state to satisfaction states,
+/// each a list of `{transition, from, to, actions}` steps with labelled actions.
+#[tauri::command]
+async fn get_satisfaction_paths(
+    app: tauri::AppHandle,
+    path: String,
+    limit: usize,
+    use_pruning: bool,
+) -> Result<serde_json::Value, String> {
+    let mut args = vec![path, format!("--satisfaction-paths={}", limit)];
+    args.push(pruning_arg(use_pruning));
+
+    analyzer_json_line(&app, args, "SATISFACTION_PATHS_JSON:").await
+}
+
+/// Distinct deontic tags in force in any state of the automaton of `path`,
+/// as `{tag, type, party, action}` entries sorted by party and action.
+#[tauri::command]
+async fn get_deontic_tags(
+    app: tauri::AppHandle,
+    path: String,
+    use_pruning: bool,
+) -> Result<serde_json::Value, String> {
+    let mut args = vec![path, "--tags".to_string()];
+    args.push(pruning_arg(use_pruning));
+
+    analyzer_json_line(&app, args, "DEONTIC_TAGS_JSON:").await
+}
+
+/// Explains transition `id` of the automaton of `path`: the action set that
+/// enabled it and the clauses before and after (`null` if there is no such transition).
+#[tauri::command]
+async fn explain_transition(
+    app: tauri::AppHandle,
+    path: String,
+    id: usize,
+    use_pruning: bool,
+) -> Result<serde_json::Value, String> {
+    let mut args = vec![path, format!("--explain={}", id)];
+    args.push(pruning_arg(use_pruning));
+
+    analyzer_json_line(&app, args, "EXPLAIN_JSON:").await
+}
+
+/// Minimal set of clauses of `path` whose initial state still conflicts, as their
+/// text (`null` if the initial state has no conflict), see `ConflictSearcher::minimal_conflict_set`.
+#[tauri::command]
+async fn get_minimal_conflict(app: tauri::AppHandle, path: String) -> Result<serde_json::Value, String> {
+    analyzer_json_line(&app, vec![path, "--minimal-conflict".to_string()], "MINIMAL_CONFLICT_JSON:").await
+}
+
+/// Cheap size estimate of the analysis of `path` (see `AutomataConstructor::estimate`),
+/// so the UI can warn before launching a potentially huge run.
+#[tauri::command]
+async fn estimate_analysis(
+    app: tauri::AppHandle,
+    path: String,
+    use_pruning: bool,
+) -> Result<serde_json::Value, String> {
+    let mut args = vec![path, "--estimate".to_string()];
+    args.push(pruning_arg(use_pruning));
+
+    analyzer_json_line(&app, args, "ESTIMATE_JSON:").await
+}
+
+/// Analyses a single clause as a one-clause contract (see `FileUtil::clause_contract`).
+/// Nothing is exported; the temp contract and the files written next to it are removed
+/// afterwards.
+#[tauri::command]
+async fn analyze_clause(
+    app_handle: tauri::AppHandle,
+    clause: String,
+    mode: String,
+    use_pruning: bool,
+) -> Result<String, String> {
+    let path = FileUtil::write_clause_contract(&clause)
+        .map_err(|e| format!("Failed to create temp analysis file: {}", e))?;
+
+    let result = run_analysis_internal(app_handle, path.clone(), mode, false, false, use_pruning).await;
+
+    FileUtil::remove_clause_files(&path);
+
+    result
+}
+
+/// Analyses `path` and writes a self-contained HTML report next to it (see
+/// `AutomatonExporter::dump_to_html`), returning the path of the HTML file.
+#[tauri::command]
+async fn export_html(app: tauri::AppHandle, path: String, use_pruning: bool) -> Result<String, String> {
+    let mut args = vec![path, "--format=html".to_string()];
+    args.push(pruning_arg(use_pruning));
+
+    let export = analyzer_json_line(&app, args, "EXPORT_JSON:").await?;
+    export["path"]
+        .as_str()
+        .map(|p| p.to_string())
+        .ok_or_else(|| "The analyzer did not report the HTML file".to_string())
+}
+
+/// Runs the analyzer to completion and parses the JSON of its first stdout line
+/// starting with `prefix`.
+async fn analyzer_json_line(
+    app: &tauri::AppHandle,
+    args: Vec<String>,
+    prefix: &str,
+) -> Result<serde_json::Value, String> {
+    let (mut rx, _child) = spawn_analyzer(app, args)
+        .await
+        .map_err(|e| format!("Failed to spawn sidecar: {}", e))?;
+
+    let mut stdout = String::new();
+    let mut stderr = String::new();
+    let mut exit_code = None;
+    while let Some(event) = rx.recv().await {
+        match event {
+            CommandEvent::Stdout(line_bytes) => stdout.push_str(&String::from_utf8_lossy(&line_bytes)),
+            CommandEvent::Stderr(line_bytes) => stderr.push_str(&String::from_utf8_lossy(&line_bytes)),
+            CommandEvent::Terminated(status) => {
+                exit_code = status.code;
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    let json_line = stdout
+        .lines()
+        .find_map(|l| l.trim().strip_prefix(prefix));
+    match json_line {
+        Some(json) => serde_json::from_str(json).map_err(|e| e.to_string()),
+        None if !stderr.trim().is_empty() => Err(stderr.trim().to_string()),
+        None => Err(format!("Analysis failed (exit code {:?})", exit_code)),
+    }
+}
+
+/// Total RAM and swap, plus the limit the analyzer's memory guard will enforce,
+/// so the UI can warn before a large analysis.
+#[tauri::command]
+fn system_memory() -> SystemMemory {
+    SystemMemory::current()
+}
+
+/// Writes the bundled example contracts into `dir` and returns their paths,
+/// so new users have something to run a batch analysis on.
+#[tauri::command]
+async fn write_example_contracts(dir: String) -> Result<Vec<String>, String> {
+    FileUtil::write_examples(&dir).map_err(|e| format!("Failed to write examples: {}", e))
+}
+
+#[tauri::command]
+async fn read_file(path: String) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|e| e.to_string())
+}
+
+/// Writes `content` to `path`, e.g. results the UI chose to save.
+#[tauri::command]
+async fn save_file(path: String, content: String) -> Result<(), String> {
+    fs::write(&path, content).map_err(|e| format!("Failed to save {}: {}", path, e))
+}
+
+/// Registers the commands the frontend can call: builds both `handler()` and
+/// `COMMAND_NAMES` from one list, so the two can't drift apart.
+macro_rules! commands {
+    ($($command:ident),* $(,)?) => {
+        /// Names of the registered commands, in registration order.
+        pub const COMMAND_NAMES: &[&str] = &[$(stringify!($command)),*];
+
+        /// Invoke handler with every command the frontend can call.
+        /// A new `#[tauri::command]` only needs to be listed in `commands!`.
+        pub fn handler() -> impl Fn(tauri::ipc::Invoke) -> bool + Send + Sync + 'static {
+            tauri::generate_handler![$($command),*]
+        }
+    };
+}
+
+commands![
+    process_file,
+    analyze_text,
+    analyze_clause,
+    read_file,
+    save_file,
+    select_directory,
+    run_batch_analysis,
+    run_batch_analysis_in_memory,
+    stop_analysis,
+    get_related_files,
+    get_symbol_table,
+    run_trace,
+    get_satisfaction_paths,
+    get_deontic_tags,
+    write_example_contracts,
+    explain_transition,
+    set_analyzer_path,
+    system_memory,
+    estimate_analysis,
+    get_minimal_conflict,
+    export_html,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_command_the_frontend_invokes_is_registered() {
+        let pages = [
+            include_str!("../../src/pages/AnalysisPage.tsx"),
+            include_str!("../../src/pages/BatchAnalysisPage.tsx"),
+        ];
+        let invoke = regex::Regex::new(r#"invoke(?:<[^(]*>)?\(\s*"(\w+)""#).unwrap();
+
+        let mut invoked = 0;
+        for page in pages {
+            for name in invoke.captures_iter(page).map(|c| c[1].to_string()) {
+                assert!(COMMAND_NAMES.contains(&name.as_str()), "{} is not registered", name);
+                invoked += 1;
+            }
+        }
+        assert!(invoked > 0, "no invoke() calls found in the pages");
+
+        let mut names = COMMAND_NAMES.to_vec();
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(names.len(), COMMAND_NAMES.len(), "a command is registered twice");
+    }
+}
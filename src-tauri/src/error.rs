@@ -0,0 +1,197 @@
+use crate::parser::ast_builder::AstError;
+use thiserror::Error;
+
+/// Errors that end an analysis before a result can be reported.
+///
+/// Each variant has a distinct process exit code so callers of the sidecar
+/// can tell failures apart without scraping stderr.
+#[derive(Debug, Error)]
+pub enum AnalysisError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("{0}")]
+    Parse(#[from] AstError),
+    #[error("Contract has no clauses")]
+    ContractEmpty,
+    #[error("Contract is too complex to analyse ({actions} relativized actions)")]
+    TooComplex { actions: usize },
+    #[error("CRITICAL: Memory limit exceeded")]
+    MemoryExceeded,
+    #[error("CRITICAL: Analysis timed out after {seconds}s")]
+    Timeout { seconds: u64 },
+    #[error("CRITICAL: Clause size limit exceeded. A successor of state {state} has {size} clause nodes (limit {limit}).")]
+    ClauseSizeLimit { state: usize, size: usize, limit: usize },
+    #[error("CRITICAL: Action composition too deeply nested (depth {depth}, limit {limit})")]
+    ActionTooDeep { depth: usize, limit: usize },
+}
+
+impl AnalysisError {
+    /// Process exit code reported by the analyzer for this error.
+    /// 1 is reserved for usage errors, 3 for `--deny-warnings`, 8 for `--baseline`
+    /// mismatches and 101 for panics.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            AnalysisError::Io(_) => 2,
+            AnalysisError::Parse(_) => 4,
+            AnalysisError::ContractEmpty => 5,
+            AnalysisError::TooComplex { .. } => 6,
+            AnalysisError::ClauseSizeLimit { .. } => 7,
+            AnalysisError::ActionTooDeep { .. } => 9,
+            AnalysisError::Timeout { .. } => 10,
+            AnalysisError::MemoryExceeded => 137,
+        }
+    }
+}
+
+impl From<pest::error::Error<crate::parser::Rule>> for AnalysisError {
+    fn from(err: pest::error::Error<crate::parser::Rule>) -> Self {
+        AnalysisError::Parse(AstError::ParseError(err.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AnalysisError;
+    use crate::test_support::{config, serial, write_contract};
+
+    fn analyze(src: &str, args: &[&str]) -> Result<crate::Automaton, AnalysisError> {
+        crate::analyze(&config(&write_contract("contract.rcl", src), args))
+    }
+
+    #[test]
+    fn missing_file_is_an_io_error() {
+        let _guard = serial();
+        let path = write_contract("contract.rcl", "");
+        let missing = format!("{}.missing", path);
+        let err = crate::analyze(&config(&missing, &[])).unwrap_err();
+        assert!(matches!(err, AnalysisError::Io(_)), "{:?}", err);
+        assert_eq!(err.exit_code(), 2);
+    }
+
+    #[test]
+    fn bad_syntax_is_a_parse_error() {
+        let _guard = serial();
+        let err = analyze("{a,b}O(pay", &[]).unwrap_err();
+        assert!(matches!(err, AnalysisError::Parse(_)), "{:?}", err);
+        assert_eq!(err.exit_code(), 4);
+    }
+
+    #[test]
+    fn contract_of_disabled_clauses_is_empty() {
+        let _guard = serial();
+        let err = analyze("@disabled {a,b}O(pay);", &[]).unwrap_err();
+        assert!(matches!(err, AnalysisError::ContractEmpty), "{:?}", err);
+        assert_eq!(err.exit_code(), 5);
+    }
+
+    #[test]
+    fn too_many_relativized_actions_is_too_complex() {
+        let _guard = serial();
+        // 260 parties, each obliged towards everyone else: 260 * 259 relativized actions
+        let src: String = (0..260).map(|i| format!("{{p{i}}}O(a{i});\n")).collect();
+        let err = analyze(&src, &[]).unwrap_err();
+        match err {
+            AnalysisError::TooComplex { actions } => {
+                assert!(actions > crate::model::automata::SPARSE_ACTION_LIMIT)
+            }
+            other => panic!("expected TooComplex, got {:?}", other),
+        }
+        assert_eq!(AnalysisError::TooComplex { actions: 0 }.exit_code(), 6);
+    }
+
+    #[test]
+    fn oversized_successor_hits_the_clause_size_limit() {
+        let _guard = serial();
+        let err = analyze("{a,b}O(pay);\n{a,b}[pay]({b,a}O(deliver));", &["--max-clause-size", "1"])
+            .unwrap_err();
+        match &err {
+            AnalysisError::ClauseSizeLimit { size, limit, .. } => {
+                assert_eq!(*limit, 1);
+                assert!(*size > 1);
+            }
+            other => panic!("expected ClauseSizeLimit, got {:?}", other),
+        }
+        assert!(err.to_string().starts_with("CRITICAL: Clause size limit exceeded"));
+        assert_eq!(err.exit_code(), 7);
+    }
+
+    #[test]
+    fn nested_action_beyond_the_limit_is_too_deep() {
+        let _guard = serial();
+        let err = analyze("{a,b}O(pay&(ship&deliver));", &["--max-action-depth", "1"]).unwrap_err();
+        assert!(
+            matches!(err, AnalysisError::ActionTooDeep { limit: 1, .. }),
+            "{:?}",
+            err
+        );
+        assert_eq!(err.exit_code(), 9);
+    }
+
+    #[test]
+    fn very_deep_action_is_a_clean_error_not_a_stack_overflow() {
+        let _guard = serial();
+        // Far deeper than the parser could recurse into
+        let depth = 100_000;
+        let src = format!("{{a,b}}O({}ship{});", "pay&(".repeat(depth), ")".repeat(depth));
+        let err = analyze(&src, &[]).unwrap_err();
+        assert!(
+            matches!(err, AnalysisError::ActionTooDeep { limit: 1000, .. }),
+            "{:?}",
+            err
+        );
+        assert!(err.to_string().starts_with("CRITICAL: Action composition too deeply nested"));
+
+        // Just past the limit: parsed, but stopped before the actions are built
+        let src = format!("{{a,b}}O({}ship{});", "pay&(".repeat(20), ")".repeat(20));
+        let err = analyze(&src, &["--max-action-depth", "15"]).unwrap_err();
+        assert!(matches!(err, AnalysisError::ActionTooDeep { depth: 21, limit: 15 }), "{:?}", err);
+    }
+
+    #[test]
+    fn expired_timeout_stops_the_construction() {
+        let _guard = serial();
+        let src = "{a,b}O(pay);\n{a,b}[pay]({b,a}O(deliver));";
+        let mut config = config(&write_contract("contract.rcl", src), &[]);
+        // Already expired when the construction starts
+        config.set_timeout(Some(0));
+        let err = crate::analyze(&config).unwrap_err();
+        assert!(matches!(err, AnalysisError::Timeout { seconds: 0 }), "{:?}", err);
+        assert_eq!(err.exit_code(), 10);
+
+        // A generous timeout does not get in the way
+        config.set_timeout(Some(3600));
+        assert!(crate::analyze(&config).is_ok());
+    }
+
+    #[test]
+    fn memory_guard_signal_ends_the_analysis_with_memory_exceeded() {
+        let _guard = serial();
+        crate::MemoryGuard::signal_exceeded(true);
+        let result = analyze("{a,b}O(pay);", &[]);
+        crate::MemoryGuard::signal_exceeded(false);
+        let err = result.unwrap_err();
+        assert!(matches!(err, AnalysisError::MemoryExceeded), "{:?}", err);
+        assert_eq!(err.exit_code(), 137);
+
+        assert!(analyze("{a,b}O(pay);", &[]).is_ok());
+    }
+
+    #[test]
+    fn exit_codes_are_distinct_and_avoid_the_reserved_ones() {
+        let codes = [
+            AnalysisError::Io(std::io::Error::other("io")).exit_code(),
+            AnalysisError::Parse(crate::parser::ast_builder::AstError::ParseError(String::new())).exit_code(),
+            AnalysisError::ContractEmpty.exit_code(),
+            AnalysisError::TooComplex { actions: 0 }.exit_code(),
+            AnalysisError::ClauseSizeLimit { state: 0, size: 0, limit: 0 }.exit_code(),
+            AnalysisError::ActionTooDeep { depth: 0, limit: 0 }.exit_code(),
+            AnalysisError::Timeout { seconds: 0 }.exit_code(),
+            AnalysisError::MemoryExceeded.exit_code(),
+        ];
+        let unique: std::collections::HashSet<_> = codes.iter().collect();
+        assert_eq!(unique.len(), codes.len());
+        for reserved in [0, 1, 3, 8, 101, 130] {
+            assert!(!codes.contains(&reserved), "{} is reserved", reserved);
+        }
+    }
+}
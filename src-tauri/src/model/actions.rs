@@ -1,4 +1,5 @@
-use crate::SymbolTable;
+use crate::{SymbolTable, SymbolType};
+use rustc_hash::FxHashMap;
 use std::fmt;
 
 // ==================== ActionOperator ====================
@@ -10,6 +11,8 @@ pub enum ActionOperator {
     Sequence,
     Negation,
     Star,
+    /// Iteração limitada `a^N`: de 0 a N repetições
+    BoundedStar(u32),
     None,
 }
 
@@ -21,6 +24,7 @@ impl fmt::Display for ActionOperator {
             ActionOperator::Negation => write!(f, "!"),
             ActionOperator::Sequence => write!(f, "."),
             ActionOperator::Star => write!(f, "*"),
+            ActionOperator::BoundedStar(bound) => write!(f, "^{}", bound),
             ActionOperator::None => write!(f, "NONE"),
         }
     }
@@ -98,6 +102,29 @@ impl BasicAction {
         }
     }
 
+    /// Renumera a ação (e suas ações concorrentes) segundo `ids` (id antigo -> id novo)
+    ///
+    /// SKIP e VIOLATION não possuem símbolo e são mantidas.
+    pub fn remap(&self, ids: &FxHashMap<i32, i32>) -> Self {
+        let value = if self.value > 0 {
+            ids.get(&self.value).copied().unwrap_or(self.value)
+        } else {
+            self.value
+        };
+
+        BasicAction {
+            value,
+            violation: self.violation,
+            skip: self.skip,
+            negation: self.negation,
+            concurrent_actions: self
+                .concurrent_actions
+                .iter()
+                .map(|a| a.remap(ids))
+                .collect(),
+        }
+    }
+
     pub fn negate(&self) -> Self {
         BasicAction {
             value: self.value,
@@ -119,8 +146,7 @@ impl fmt::Display for BasicAction {
             return write!(f, "0");
         }
 
-        let symbol_table = SymbolTable::instance();
-        let table = symbol_table.lock().unwrap();
+        let table = SymbolTable::lock();
 
         let prefix = if self.negation { "!" } else { "" };
 
@@ -183,6 +209,10 @@ impl fmt::Display for ComposedAction {
                 write!(f, "{}*", left)
             }
 
+            (Some(left), None, ActionOperator::BoundedStar(bound)) => {
+                write!(f, "{}^{}", left, bound)
+            }
+
             (Some(left), None, ActionOperator::Negation) => {
                 write!(f, "{}{}", self.operator, left)
             }
@@ -230,6 +260,36 @@ impl Action {
         }
     }
 
+    /// Profundidade da árvore de composição (1 para uma ação básica)
+    ///
+    /// Calculada com pilha explícita, para não estourar a pilha justamente nas ações
+    /// profundas que se quer detectar.
+    pub fn depth(&self) -> usize {
+        let mut max = 0;
+        let mut stack = vec![(self, 1)];
+        while let Some((action, level)) = stack.pop() {
+            max = max.max(level);
+            if let Action::Composed(ca) = action {
+                for child in [&ca.left, &ca.right].into_iter().flatten() {
+                    stack.push((child, level + 1));
+                }
+            }
+        }
+        max
+    }
+
+    /// Renumera todas as ações básicas segundo `ids` (id antigo -> id novo)
+    pub fn remap(&self, ids: &FxHashMap<i32, i32>) -> Self {
+        match self {
+            Action::Basic(ba) => Action::Basic(ba.remap(ids)),
+            Action::Composed(ca) => Action::Composed(ComposedAction {
+                left: ca.left.as_ref().map(|l| Box::new(l.remap(ids))),
+                right: ca.right.as_ref().map(|r| Box::new(r.remap(ids))),
+                operator: ca.operator,
+            }),
+        }
+    }
+
     pub fn choice(left: Action, right: Action) -> Self {
         Action::Composed(ComposedAction::binary(left, right, ActionOperator::Choice))
     }
@@ -257,6 +317,13 @@ impl Action {
     pub fn star(action: Action) -> Self {
         Action::Composed(ComposedAction::unary(action, ActionOperator::Star))
     }
+
+    pub fn bounded_star(action: Action, bound: u32) -> Self {
+        Action::Composed(ComposedAction::unary(
+            action,
+            ActionOperator::BoundedStar(bound),
+        ))
+    }
 }
 
 impl fmt::Display for Action {
@@ -322,6 +389,21 @@ impl RelativizedAction {
         }
     }
 
+    /// Cria a ação a partir dos nomes, registrando-os na tabela de símbolos
+    ///
+    /// # Argumentos
+    /// * `sender` - Remetente; vazio para ações sem remetente (id `-1`)
+    /// * `action` - Nome da ação
+    /// * `receiver` - Destinatário; vazio para ações relativizadas (id `-1`)
+    /// * `table` - Tabela de símbolos, normalmente `SymbolTable::lock()`
+    pub fn from_names(sender: &str, action: &str, receiver: &str, table: &mut SymbolTable) -> Self {
+        RelativizedAction::new(
+            table.intern(sender, SymbolType::Individual),
+            BasicAction::with_value(table.intern(action, SymbolType::Action)),
+            table.intern(receiver, SymbolType::Individual),
+        )
+    }
+
     pub fn negation(action: &RelativizedAction) -> Self {
         RelativizedAction {
             negation: true,
@@ -338,15 +420,8 @@ impl RelativizedAction {
             self.action.format_with_symbols(symbol_table)
         };
 
-        let sender_str = symbol_table
-            .get_symbol_by_id(self.sender)
-            .map(|s| s.value.as_str())
-            .unwrap_or("?");
-
-        let receiver_str = symbol_table
-            .get_symbol_by_id(self.receiver)
-            .map(|s| s.value.as_str())
-            .unwrap_or("?");
+        let sender_str = symbol_table.individual_name(self.sender);
+        let receiver_str = symbol_table.individual_name(self.receiver);
 
         format!("({}, {}, {})", sender_str, action_str, receiver_str)
     }
@@ -354,8 +429,7 @@ impl RelativizedAction {
 
 impl fmt::Display for RelativizedAction {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let symbol_table = SymbolTable::instance();
-        let table = symbol_table.lock().unwrap();
+        let table = SymbolTable::lock();
         write!(f, "{}", self.format_with_symbols(&table))
     }
 }
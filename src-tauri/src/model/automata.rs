@@ -4,10 +4,11 @@ use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
 use crate::{
-    BasicAction, Clause, Contract, DeonticClauseType, RelativizationType, RelativizedAction,
-    SymbolTable,
+    BasicAction, Clause, ConflictSearcher, Contract, DeonticClauseType, RelativizationType,
+    RelativizedAction, SymbolTable, SymbolType,
 };
 use rustc_hash::FxHashMap;
+use serde::Serialize;
 
 // ==================== StateSituation ====================
 
@@ -77,19 +78,45 @@ impl DeonticTag {
         Self::new(deontic_type, action, RelativizationType::Global, -1, -1)
     }
 
+    /// Cria a tag a partir dos nomes, registrando-os na tabela de símbolos
+    ///
+    /// A relativização segue as partes informadas, como no parser: remetente e
+    /// destinatário dão `Directed`, só o remetente dá `Relativized` e nenhum dá `Global`.
+    ///
+    /// # Argumentos
+    /// * `deontic_type` - Obrigação, permissão ou proibição
+    /// * `sender` - Remetente, ou vazio
+    /// * `action` - Nome da ação
+    /// * `receiver` - Destinatário, ou vazio
+    /// * `table` - Tabela de símbolos, normalmente `SymbolTable::lock()`
+    pub fn from_names(
+        deontic_type: DeonticClauseType,
+        sender: &str,
+        action: &str,
+        receiver: &str,
+        table: &mut SymbolTable,
+    ) -> Self {
+        let action = BasicAction::with_value(table.intern(action, SymbolType::Action));
+        if sender.is_empty() {
+            return Self::global(deontic_type, action);
+        }
+
+        let sender = table.intern(sender, SymbolType::Individual);
+        if receiver.is_empty() {
+            Self::relativized(deontic_type, action, sender)
+        } else {
+            let receiver = table.intern(receiver, SymbolType::Individual);
+            Self::directed(deontic_type, action, sender, receiver)
+        }
+    }
+
     pub fn format_with_symbols(&self, symbol_table: &SymbolTable) -> String {
         let symbol = self.deontic_type.short_symbol();
 
         match self.relativization {
             RelativizationType::Directed => {
-                let sender_name = symbol_table
-                    .get_symbol_by_id(self.sender)
-                    .map(|s| s.value.as_str())
-                    .unwrap_or("?");
-                let receiver_name = symbol_table
-                    .get_symbol_by_id(self.receiver)
-                    .map(|s| s.value.as_str())
-                    .unwrap_or("?");
+                let sender_name = symbol_table.individual_name(self.sender);
+                let receiver_name = symbol_table.individual_name(self.receiver);
                 format!(
                     "{}({},{},{})",
                     symbol,
@@ -99,10 +126,7 @@ impl DeonticTag {
                 )
             }
             RelativizationType::Relativized => {
-                let sender_name = symbol_table
-                    .get_symbol_by_id(self.sender)
-                    .map(|s| s.value.as_str())
-                    .unwrap_or("?");
+                let sender_name = symbol_table.individual_name(self.sender);
                 format!(
                     "{}({},{})",
                     symbol,
@@ -124,8 +148,7 @@ impl DeonticTag {
 impl fmt::Display for DeonticTag {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         // Usa uma symbol table padrão para display
-        let symbol_table = SymbolTable::instance();
-        let table = symbol_table.lock().unwrap();
+        let table = SymbolTable::lock();
         write!(f, "{}", self.format_with_symbols(&table))
     }
 }
@@ -169,16 +192,141 @@ impl fmt::Display for ConflictInformation {
     }
 }
 
+// ==================== ActionSet ====================
+
+/// Maior `source_map` representado por máscara de bits em `ActionSet`
+pub const MASK_ACTION_LIMIT: usize = 30;
+
+/// Maior `source_map` representável em `ActionSet::Sparse` (índices `u16`)
+pub const SPARSE_ACTION_LIMIT: usize = u16::MAX as usize + 1;
+
+/// Máximo de conjuntos gerados para um estado pela busca de `ActionSet::Sparse`
+pub const SPARSE_SET_LIMIT: usize = 1 << 24;
+
+/// Conjunto de ações concorrentes, como índices no `source_map` de onde veio
+///
+/// Até `MASK_ACTION_LIMIT` ações o conjunto é uma máscara de bits (`Mask`), compacta
+/// e rápida. Acima disso vira a lista ordenada dos índices (`Sparse`), sem limite de
+/// ações, ao custo de uma alocação por conjunto. Todos os conjuntos de um mesmo
+/// `source_map` usam a mesma representação (ver `ActionSet::from_indices`).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(untagged)]
+pub enum ActionSet {
+    Mask(u32),
+    Sparse(Vec<u16>),
+}
+
+impl ActionSet {
+    /// Monta o conjunto na representação adequada ao tamanho do `source_map`
+    ///
+    /// # Argumentos
+    /// * `indices` - Índices das ações, em qualquer ordem
+    /// * `source_len` - Tamanho do `source_map` ao qual os índices se referem
+    pub fn from_indices(indices: &[usize], source_len: usize) -> Self {
+        if source_len <= MASK_ACTION_LIMIT {
+            ActionSet::Mask(indices.iter().fold(0, |mask, &idx| mask | (1 << idx)))
+        } else {
+            let mut sparse: Vec<u16> = indices.iter().map(|&idx| idx as u16).collect();
+            sparse.sort_unstable();
+            sparse.dedup();
+            ActionSet::Sparse(sparse)
+        }
+    }
+
+    /// Quantidade de ações no conjunto
+    pub fn len(&self) -> usize {
+        match self {
+            ActionSet::Mask(mask) => mask.count_ones() as usize,
+            ActionSet::Sparse(indices) => indices.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Índices das ações em ordem crescente
+    pub fn indices(&self) -> ActionSetIter<'_> {
+        match self {
+            ActionSet::Mask(mask) => ActionSetIter::Mask(*mask),
+            ActionSet::Sparse(indices) => ActionSetIter::Sparse(indices.iter()),
+        }
+    }
+}
+
+impl fmt::Display for ActionSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ActionSet::Mask(mask) => write!(f, "{}", mask),
+            ActionSet::Sparse(indices) => write!(f, "{:?}", indices),
+        }
+    }
+}
+
+/// Iterador de `ActionSet::indices`
+pub enum ActionSetIter<'a> {
+    Mask(u32),
+    Sparse(std::slice::Iter<'a, u16>),
+}
+
+impl Iterator for ActionSetIter<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        match self {
+            ActionSetIter::Mask(mask) => {
+                if *mask == 0 {
+                    return None;
+                }
+                let idx = mask.trailing_zeros();
+                *mask &= *mask - 1;
+                Some(idx as usize)
+            }
+            ActionSetIter::Sparse(iter) => iter.next().map(|&idx| idx as usize),
+        }
+    }
+}
+
 // ==================== Transition ====================
 
 static TRANSITION_COUNTER: AtomicUsize = AtomicUsize::new(1);
 
+thread_local! {
+    /// Próximos ids de estado e de transição desta thread, se ela tiver contadores
+    /// próprios (ver `use_thread_ids`)
+    static THREAD_IDS: std::cell::Cell<Option<(usize, usize)>> = const { std::cell::Cell::new(None) };
+}
+
+/// Faz a thread atual numerar estados e transições com contadores próprios, desde o
+/// início (estados a partir de 0, transições a partir de 1), como um processo novo
+///
+/// Usado pelas análises isoladas (`SymbolTable::isolated`), para que os ids não
+/// dependam das outras análises do processo.
+pub fn use_thread_ids() {
+    THREAD_IDS.with(|ids| ids.set(Some((0, 1))));
+}
+
+/// Próximo id de estado (`state = true`) ou de transição, da thread ou global
+fn next_id(state: bool, global: &AtomicUsize) -> usize {
+    THREAD_IDS.with(|ids| match ids.get() {
+        Some((states, transitions)) if state => {
+            ids.set(Some((states + 1, transitions)));
+            states
+        }
+        Some((states, transitions)) => {
+            ids.set(Some((states, transitions + 1)));
+            transitions
+        }
+        None => global.fetch_add(1, Ordering::SeqCst),
+    })
+}
+
 #[derive(Debug, Clone)]
 pub struct Transition {
     pub id: usize,
     pub from: usize,
     pub to: usize,
-    pub mask: u32,
+    pub action_set: ActionSet,
     pub source_map: Arc<Vec<Arc<RelativizedAction>>>,
 }
 
@@ -186,29 +334,81 @@ impl Transition {
     pub fn new(
         from: usize,
         to: usize,
-        mask: u32,
+        action_set: ActionSet,
         source_map: Arc<Vec<Arc<RelativizedAction>>>,
     ) -> Self {
         Transition {
-            id: TRANSITION_COUNTER.fetch_add(1, Ordering::SeqCst),
+            id: next_id(false, &TRANSITION_COUNTER),
             from,
             to,
-            mask,
+            action_set,
             source_map,
         }
     }
 
     pub fn actions(&self) -> Vec<Arc<RelativizedAction>> {
-        let mut actions = Vec::with_capacity(self.mask.count_ones() as usize);
-        let mut temp_mask = self.mask;
-        while temp_mask > 0 {
-            let idx = temp_mask.trailing_zeros();
-            if let Some(act) = self.source_map.get(idx as usize) {
-                actions.push(act.clone());
-            }
-            temp_mask &= temp_mask - 1;
-        }
-        actions
+        self.action_refs().cloned().collect()
+    }
+
+    /// Índices em `source_map` das ações da transição, em ordem crescente
+    pub fn action_indices(&self) -> impl Iterator<Item = usize> + '_ {
+        self.action_set.indices()
+    }
+
+    /// Ações da transição por referência, sem clonar os `Arc` (mesma ordem de `actions`)
+    pub fn action_refs(&self) -> impl Iterator<Item = &Arc<RelativizedAction>> + '_ {
+        self.action_indices()
+            .filter_map(move |idx| self.source_map.get(idx))
+    }
+}
+
+// ==================== SituationCounts ====================
+
+/// Quantidade de estados do autômato em cada situação
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct SituationCounts {
+    pub satisfaction: usize,
+    pub violating: usize,
+    pub conflicting: usize,
+    pub conflict_free: usize,
+    pub not_checked: usize,
+}
+
+impl SituationCounts {
+    pub fn total(&self) -> usize {
+        self.satisfaction
+            + self.violating
+            + self.conflicting
+            + self.conflict_free
+            + self.not_checked
+    }
+}
+
+// ==================== TransitionExplanation ====================
+
+/// Por que uma transição existe: o conjunto de ações que a habilitou e as
+/// cláusulas antes (estado de origem) e depois (estado de destino) da decomposição
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TransitionExplanation {
+    pub id: usize,
+    pub from: usize,
+    pub to: usize,
+    pub mask: ActionSet,
+    pub actions: Vec<String>,
+    pub before: String,
+    pub after: String,
+}
+
+impl fmt::Display for TransitionExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "Transition T{} (S{} -> S{})",
+            self.id, self.from, self.to
+        )?;
+        writeln!(f, "   - Actions: [{}]", self.actions.join(", "))?;
+        writeln!(f, "   - Before: {}", self.before)?;
+        write!(f, "   - After: {}", self.after)
     }
 }
 
@@ -231,7 +431,7 @@ impl fmt::Display for Transition {
         write!(
             f,
             "T{}: {} -> {} [mask: {}]",
-            self.id, self.from, self.to, self.mask
+            self.id, self.from, self.to, self.action_set
         )
     }
 }
@@ -246,23 +446,28 @@ pub struct State {
     pub clause: Option<Clause>,
     pub situation: StateSituation,
     pub conflict_information: Option<ConflictInformation>,
-    pub trace: Vec<usize>,
+    /// Transição pela qual o estado foi descoberto (`None` no estado inicial)
+    ///
+    /// Basta uma: o caminho completo é reconstruído seguindo essas transições de volta
+    /// (`Automaton::trace_to`).
+    pub trace: Option<usize>,
 }
 
 impl State {
     pub fn with_auto_id(clause: Option<Clause>) -> Self {
-        let id = STATE_COUNTER.fetch_add(1, Ordering::SeqCst);
+        let id = next_id(true, &STATE_COUNTER);
         State {
             id,
             clause,
             situation: StateSituation::NotChecked,
             conflict_information: None,
-            trace: Vec::new(),
+            trace: None,
         }
     }
 
+    /// Registra a transição de descoberta; só a primeira é mantida
     pub fn push_trace(&mut self, transition_id: usize) {
-        self.trace.push(transition_id);
+        self.trace.get_or_insert(transition_id);
     }
 }
 
@@ -302,6 +507,201 @@ impl fmt::Display for State {
     }
 }
 
+// ==================== TraceOutcome ====================
+
+/// Resultado da execução de um traço sobre o autômato (`Automaton::run_trace`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceOutcome {
+    /// O traço alcançou um estado de satisfação
+    Satisfaction { state: usize, steps: usize },
+    /// O traço alcançou um estado de violação
+    Violating { state: usize, steps: usize },
+    /// O traço alcançou um estado em conflito
+    Conflicting { state: usize, steps: usize },
+    /// O traço foi consumido e o contrato ainda está em andamento
+    Pending { state: usize, steps: usize },
+    /// Nenhuma transição do estado `state` corresponde ao passo `step`
+    Stuck { state: usize, step: usize },
+}
+
+impl TraceOutcome {
+    fn from_state(state: &State, steps: usize) -> Self {
+        match state.situation {
+            StateSituation::Satisfaction => TraceOutcome::Satisfaction {
+                state: state.id,
+                steps,
+            },
+            StateSituation::Violating => TraceOutcome::Violating {
+                state: state.id,
+                steps,
+            },
+            StateSituation::Conflicting => TraceOutcome::Conflicting {
+                state: state.id,
+                steps,
+            },
+            StateSituation::ConflictFree | StateSituation::NotChecked => TraceOutcome::Pending {
+                state: state.id,
+                steps,
+            },
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TraceOutcome::Satisfaction { .. } => "satisfaction",
+            TraceOutcome::Violating { .. } => "violating",
+            TraceOutcome::Conflicting { .. } => "conflicting",
+            TraceOutcome::Pending { .. } => "pending",
+            TraceOutcome::Stuck { .. } => "stuck",
+        }
+    }
+}
+
+impl fmt::Display for TraceOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TraceOutcome::Stuck { state, step } => {
+                write!(f, "stuck at state {} on step {}", state, step)
+            }
+            TraceOutcome::Satisfaction { state, steps }
+            | TraceOutcome::Violating { state, steps }
+            | TraceOutcome::Conflicting { state, steps }
+            | TraceOutcome::Pending { state, steps } => {
+                write!(
+                    f,
+                    "{} at state {} after {} step(s)",
+                    self.name(),
+                    state,
+                    steps
+                )
+            }
+        }
+    }
+}
+
+// ==================== TraceRegex ====================
+
+/// Expressão regular sobre rótulos de transições, usada por `Automaton::to_regex`
+///
+/// Os construtores (`concat`, `union`, `star`) simplificam enquanto montam
+/// (ε neutro na concatenação, alternativas repetidas removidas, estrela idempotente),
+/// para que a eliminação de estados não produza expressões desnecessariamente longas.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TraceRegex {
+    Epsilon,
+    Symbol(String),
+    Concat(Vec<TraceRegex>),
+    Union(Vec<TraceRegex>),
+    Star(Box<TraceRegex>),
+}
+
+impl TraceRegex {
+    fn concat(self, other: TraceRegex) -> TraceRegex {
+        let mut parts = Vec::new();
+        for part in [self, other] {
+            match part {
+                TraceRegex::Epsilon => {}
+                TraceRegex::Concat(inner) => parts.extend(inner),
+                part => parts.push(part),
+            }
+        }
+        match parts.len() {
+            0 => TraceRegex::Epsilon,
+            1 => parts.pop().unwrap_or(TraceRegex::Epsilon),
+            _ => TraceRegex::Concat(parts),
+        }
+    }
+
+    fn union(self, other: TraceRegex) -> TraceRegex {
+        let mut parts: Vec<TraceRegex> = Vec::new();
+        for part in [self, other] {
+            let alternatives = match part {
+                TraceRegex::Union(inner) => inner,
+                part => vec![part],
+            };
+            for alternative in alternatives {
+                if !parts.contains(&alternative) {
+                    parts.push(alternative);
+                }
+            }
+        }
+        if parts.len() == 1 {
+            parts.pop().unwrap_or(TraceRegex::Epsilon)
+        } else {
+            TraceRegex::Union(parts)
+        }
+    }
+
+    fn star(self) -> TraceRegex {
+        match self {
+            TraceRegex::Epsilon => TraceRegex::Epsilon,
+            TraceRegex::Star(_) => self,
+            // (ε | r)* = r*
+            TraceRegex::Union(parts) => {
+                let rest: Vec<TraceRegex> = parts
+                    .into_iter()
+                    .filter(|p| *p != TraceRegex::Epsilon)
+                    .collect();
+                let inner = rest
+                    .into_iter()
+                    .reduce(TraceRegex::union)
+                    .unwrap_or(TraceRegex::Epsilon);
+                match inner {
+                    TraceRegex::Epsilon | TraceRegex::Star(_) => inner,
+                    inner => TraceRegex::Star(Box::new(inner)),
+                }
+            }
+            other => TraceRegex::Star(Box::new(other)),
+        }
+    }
+
+    fn is_atomic(&self) -> bool {
+        matches!(
+            self,
+            TraceRegex::Epsilon | TraceRegex::Symbol(_) | TraceRegex::Star(_)
+        )
+    }
+}
+
+impl fmt::Display for TraceRegex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TraceRegex::Epsilon => write!(f, "ε"),
+            TraceRegex::Symbol(label) => write!(f, "{}", label),
+            TraceRegex::Concat(parts) => {
+                let rendered: Vec<String> = parts
+                    .iter()
+                    .map(|p| match p {
+                        // `(x)?` já vem entre parênteses
+                        TraceRegex::Union(alternatives)
+                            if !alternatives.contains(&TraceRegex::Epsilon) =>
+                        {
+                            format!("({})", p)
+                        }
+                        p => p.to_string(),
+                    })
+                    .collect();
+                write!(f, "{}", rendered.join(" "))
+            }
+            TraceRegex::Union(parts) => {
+                // Uma alternativa vazia vira o opcional `?`
+                let rest: Vec<String> = parts
+                    .iter()
+                    .filter(|p| **p != TraceRegex::Epsilon)
+                    .map(|p| p.to_string())
+                    .collect();
+                if rest.len() < parts.len() {
+                    write!(f, "({})?", rest.join(" | "))
+                } else {
+                    write!(f, "{}", rest.join(" | "))
+                }
+            }
+            TraceRegex::Star(inner) if inner.is_atomic() => write!(f, "{}*", inner),
+            TraceRegex::Star(inner) => write!(f, "({})*", inner),
+        }
+    }
+}
+
 // ==================== Automaton ====================
 
 #[derive(Debug, Clone)]
@@ -313,6 +713,47 @@ pub struct Automaton {
     pub state_map: FxHashMap<Clause, usize>,
 }
 
+/// Máximo de transições percorridas por `Automaton::satisfaction_paths`
+pub const SATISFACTION_PATH_STEPS: usize = 1_000_000;
+
+/// Estado da busca em profundidade de `Automaton::satisfaction_paths`
+struct SatisfactionPathSearch {
+    satisfaction: FxHashSet<usize>,
+    visited: FxHashSet<usize>,
+    current: Vec<usize>,
+    paths: Vec<Vec<usize>>,
+    limit: usize,
+    steps_left: usize,
+}
+
+impl SatisfactionPathSearch {
+    fn collect(&mut self, outgoing: &FxHashMap<usize, Vec<&Transition>>, state_id: usize) {
+        let Some(transitions) = outgoing.get(&state_id) else {
+            return;
+        };
+
+        for transition in transitions {
+            if self.paths.len() >= self.limit || self.steps_left == 0 {
+                return;
+            }
+            self.steps_left -= 1;
+            if !self.visited.insert(transition.to) {
+                continue;
+            }
+            self.current.push(transition.id);
+
+            if self.satisfaction.contains(&transition.to) {
+                self.paths.push(self.current.clone());
+            } else {
+                self.collect(outgoing, transition.to);
+            }
+
+            self.current.pop();
+            self.visited.remove(&transition.to);
+        }
+    }
+}
+
 impl Automaton {
     pub fn new(contract: Contract) -> Self {
         let full_contract = contract.get_full_contract();
@@ -325,8 +766,10 @@ impl Automaton {
 
         if let Some(ref initial_state) = initial {
             states.insert(initial_state.clone());
+            // O estado inicial mantém o contrato como escrito, mas é indexado pela forma
+            // canônica, assim como os estados criados na construção
             if let Some(ref clause) = initial_state.clause {
-                state_map.insert(clause.clone(), initial_state.id);
+                state_map.insert(clause.canonicalize(), initial_state.id);
             }
         }
 
@@ -363,7 +806,7 @@ impl Automaton {
     /// Atualiza um estado existente usando uma closure
     ///
     /// # Exemplo
-    /// ```
+    /// ```ignore
     /// automaton.update_state(state_id, |state| {
     ///     state.situation = StateSituation::Conflicting;
     ///     state.push_trace(transition_id);
@@ -380,7 +823,7 @@ impl Automaton {
             clause: None,
             situation: StateSituation::NotChecked,
             conflict_information: None,
-            trace: Vec::new(),
+            trace: None,
         }) {
             let mut state = state;
 
@@ -409,7 +852,7 @@ impl Automaton {
             clause: None,
             situation: StateSituation::NotChecked,
             conflict_information: None,
-            trace: Vec::new(),
+            trace: None,
         });
 
         if let Some(ref s) = state {
@@ -434,9 +877,578 @@ impl Automaton {
             .collect()
     }
 
+    /// Indica se o contrato é satisfeito de forma vazia
+    ///
+    /// Nenhum estado é de violação e o estado inicial já chega à satisfação com o
+    /// conjunto vazio de ações: nada é de fato exigido (por exemplo, um contrato só
+    /// de permissões ou com obrigações sempre puladas).
+    pub fn is_vacuous(&self) -> bool {
+        let Some(initial) = &self.initial else {
+            return false;
+        };
+        if self.count_situation(StateSituation::Violating) > 0 {
+            return false;
+        }
+
+        self.transitions
+            .iter()
+            .filter(|t| t.from == initial.id && t.action_refs().all(|a| a.negation))
+            .any(|t| {
+                self.get_state_by_id(t.to)
+                    .is_some_and(|s| s.situation == StateSituation::Satisfaction)
+            })
+    }
+
+    /// Número de estados em uma situação
+    pub fn count_situation(&self, situation: StateSituation) -> usize {
+        self.states
+            .iter()
+            .filter(|s| s.situation == situation)
+            .count()
+    }
+
+    /// Contagem de estados por situação; os campos somam `states.len()`
+    pub fn situation_counts(&self) -> SituationCounts {
+        SituationCounts {
+            satisfaction: self.count_situation(StateSituation::Satisfaction),
+            violating: self.count_situation(StateSituation::Violating),
+            conflicting: self.count_situation(StateSituation::Conflicting),
+            conflict_free: self.count_situation(StateSituation::ConflictFree),
+            not_checked: self.count_situation(StateSituation::NotChecked),
+        }
+    }
+
+    /// Enumera caminhos simples do estado inicial até estados de satisfação
+    ///
+    /// Busca em profundidade limitada: nenhum estado se repete em um caminho e a
+    /// busca para ao encontrar `limit` caminhos ou após percorrer
+    /// `SATISFACTION_PATH_STEPS` transições, evitando a explosão combinatória mesmo
+    /// quando há poucos caminhos até a satisfação.
+    ///
+    /// # Argumentos
+    /// * `limit` - Número máximo de caminhos retornados
+    ///
+    /// # Retorna
+    /// Caminhos como sequências de ids de transições, em ordem determinística
+    pub fn satisfaction_paths(&self, limit: usize) -> Vec<Vec<usize>> {
+        let Some(ref initial) = self.initial else {
+            return Vec::new();
+        };
+        if limit == 0 {
+            return Vec::new();
+        }
+
+        let mut outgoing: FxHashMap<usize, Vec<&Transition>> = FxHashMap::default();
+        for transition in &self.transitions {
+            outgoing
+                .entry(transition.from)
+                .or_default()
+                .push(transition);
+        }
+        for transitions in outgoing.values_mut() {
+            transitions.sort_by_key(|t| t.id);
+        }
+
+        let mut search = SatisfactionPathSearch {
+            satisfaction: self
+                .states
+                .iter()
+                .filter(|s| s.situation == StateSituation::Satisfaction)
+                .map(|s| s.id)
+                .collect(),
+            visited: FxHashSet::from_iter([initial.id]),
+            current: Vec::new(),
+            paths: Vec::new(),
+            limit,
+            steps_left: SATISFACTION_PATH_STEPS,
+        };
+        search.collect(&outgoing, initial.id);
+        search.paths
+    }
+
+    /// Tags deônticas em vigor em algum estado do autômato
+    ///
+    /// # Argumentos
+    /// * `searcher` - Usado para extrair as tags da cláusula de cada estado
+    ///
+    /// # Retorna
+    /// A união das tags de todos os estados
+    pub fn all_deontic_tags(&self, searcher: &ConflictSearcher) -> FxHashSet<DeonticTag> {
+        self.states
+            .iter()
+            .filter_map(|state| state.clause.as_ref())
+            .flat_map(|clause| searcher.clause_tags(clause))
+            .collect()
+    }
+
+    /// Grupos de estados cujas cláusulas têm a mesma forma canônica (`--verify`)
+    ///
+    /// A construção reaproveita um estado quando a cláusula do próximo estado já está em
+    /// `state_map`; dois estados com cláusulas equivalentes indicam que a deduplicação
+    /// falhou (por exemplo, uma decomposição que não chegou à forma canônica) e que o
+    /// autômato cresceu sem necessidade.
+    ///
+    /// # Retorna
+    /// Os grupos com mais de um estado, cada um com os ids em ordem crescente,
+    /// ordenados pelo menor id
+    pub fn find_dedup_failures(&self) -> Vec<Vec<usize>> {
+        let mut groups: FxHashMap<Clause, Vec<usize>> = FxHashMap::default();
+        for state in &self.states {
+            if let Some(ref clause) = state.clause {
+                groups
+                    .entry(clause.canonicalize())
+                    .or_default()
+                    .push(state.id);
+            }
+        }
+
+        let mut failures: Vec<Vec<usize>> = groups
+            .into_values()
+            .filter(|ids| ids.len() > 1)
+            .map(|mut ids| {
+                ids.sort_unstable();
+                ids
+            })
+            .collect();
+        failures.sort();
+        failures
+    }
+
+    /// Estados não finais cujas transições levam todas a um estado de violação
+    ///
+    /// Esses estados (por exemplo `O(VIOLATION)` ou uma proibição cuja penalidade é
+    /// `O(VIOLATION)` após violada) não têm situação própria: continuam marcados
+    /// como livres de conflito, mas nenhuma execução a partir deles cumpre o contrato.
+    ///
+    /// # Retorna
+    /// Os estados ordenados por id
+    pub fn get_unavoidable_violations(&self) -> Vec<&State> {
+        let situations: FxHashMap<usize, StateSituation> =
+            self.states.iter().map(|s| (s.id, s.situation)).collect();
+
+        // Por estado de origem: se todas as transições de saída levam à violação
+        let mut all_violating: FxHashMap<usize, bool> = FxHashMap::default();
+        for t in &self.transitions {
+            let violating = situations.get(&t.to) == Some(&StateSituation::Violating);
+            *all_violating.entry(t.from).or_insert(true) &= violating;
+        }
+
+        let mut result: Vec<&State> = self
+            .states
+            .iter()
+            .filter(|s| {
+                !matches!(
+                    s.situation,
+                    StateSituation::Violating | StateSituation::Satisfaction
+                )
+            })
+            .filter(|s| all_violating.get(&s.id) == Some(&true))
+            .collect();
+        result.sort();
+        result
+    }
+
+    /// Transições que saem de um estado
+    ///
+    /// # Argumentos
+    /// * `state_id` - ID do estado de origem
+    ///
+    /// # Retorna
+    /// Pares `(id da transição, id do destino)`, ordenados pelo id da transição
+    pub fn successors(&self, state_id: usize) -> Vec<(usize, usize)> {
+        let mut result: Vec<(usize, usize)> = self
+            .transitions
+            .iter()
+            .filter(|t| t.from == state_id)
+            .map(|t| (t.id, t.to))
+            .collect();
+        result.sort_unstable();
+        result
+    }
+
+    /// Transições que chegam a um estado
+    ///
+    /// # Argumentos
+    /// * `state_id` - ID do estado de destino
+    ///
+    /// # Retorna
+    /// Pares `(id da transição, id da origem)`, ordenados pelo id da transição
+    pub fn predecessors(&self, state_id: usize) -> Vec<(usize, usize)> {
+        let mut result: Vec<(usize, usize)> = self
+            .transitions
+            .iter()
+            .filter(|t| t.to == state_id)
+            .map(|t| (t.id, t.from))
+            .collect();
+        result.sort_unstable();
+        result
+    }
+
+    /// Caminho mais curto do estado inicial até um estado
+    ///
+    /// A construção é em largura e cada estado guarda em `trace` a transição pela qual
+    /// foi descoberto pela primeira vez; seguir essas transições de volta dá um caminho
+    /// de comprimento mínimo, sem nova busca.
+    ///
+    /// # Argumentos
+    /// * `state_id` - ID do estado de destino
+    ///
+    /// # Retorna
+    /// IDs das transições, do estado inicial até `state_id` (vazio para o inicial)
+    pub fn trace_to(&self, state_id: usize) -> Vec<usize> {
+        let mut path = Vec::new();
+        let mut visited = FxHashSet::default();
+        let mut current = state_id;
+
+        while visited.insert(current) {
+            let Some(transition_id) = self.get_state_by_id(current).and_then(|s| s.trace) else {
+                break;
+            };
+            let Some(transition) = self.get_transition_by_id(transition_id) else {
+                break;
+            };
+            path.push(transition_id);
+            current = transition.from;
+        }
+
+        path.reverse();
+        path
+    }
+
+    /// Lista de adjacência de saída de todos os estados, montada numa única passada
+    ///
+    /// `successors` percorre todas as transições a cada chamada; algoritmos que
+    /// visitam muitos estados (buscas, projeções, poda) devem montar este mapa uma vez.
+    ///
+    /// # Retorna
+    /// Para cada estado com transições de saída, os ids dos destinos (com repetição
+    /// se houver mais de uma transição para o mesmo estado), em ordem de id da transição
+    pub fn successor_map(&self) -> FxHashMap<usize, Vec<usize>> {
+        let mut transitions: Vec<&Transition> = self.transitions.iter().collect();
+        transitions.sort_by_key(|t| t.id);
+
+        let mut map: FxHashMap<usize, Vec<usize>> = FxHashMap::default();
+        for transition in transitions {
+            map.entry(transition.from).or_default().push(transition.to);
+        }
+        map
+    }
+
+    /// Lista de adjacência de entrada de todos os estados (ver `successor_map`)
+    ///
+    /// # Retorna
+    /// Para cada estado com transições de entrada, os ids das origens
+    pub fn predecessor_map(&self) -> FxHashMap<usize, Vec<usize>> {
+        let mut transitions: Vec<&Transition> = self.transitions.iter().collect();
+        transitions.sort_by_key(|t| t.id);
+
+        let mut map: FxHashMap<usize, Vec<usize>> = FxHashMap::default();
+        for transition in transitions {
+            map.entry(transition.to).or_default().push(transition.from);
+        }
+        map
+    }
+
+    /// Componentes fortemente conexos do autômato (algoritmo de Tarjan)
+    ///
+    /// A busca em profundidade é iterativa, com pilha explícita, para não estourar a
+    /// pilha em autômatos com caminhos longos.
+    ///
+    /// # Retorna
+    /// Os componentes, cada um com os ids dos estados em ordem crescente, ordenados
+    /// pelo menor id
+    pub fn strongly_connected_components(&self) -> Vec<Vec<usize>> {
+        let successors = self.successor_map();
+        let mut ids: Vec<usize> = self.states.iter().map(|s| s.id).collect();
+        ids.sort_unstable();
+
+        let mut next_index = 0;
+        let mut index: FxHashMap<usize, usize> = FxHashMap::default();
+        let mut low_link: FxHashMap<usize, usize> = FxHashMap::default();
+        let mut on_stack: FxHashSet<usize> = FxHashSet::default();
+        let mut stack: Vec<usize> = Vec::new();
+        let mut components = Vec::new();
+
+        for &root in &ids {
+            if index.contains_key(&root) {
+                continue;
+            }
+            // Cada quadro guarda o estado e a posição do próximo sucessor a visitar
+            let mut frames: Vec<(usize, usize)> = vec![(root, 0)];
+            index.insert(root, next_index);
+            low_link.insert(root, next_index);
+            next_index += 1;
+            stack.push(root);
+            on_stack.insert(root);
+
+            while let Some(frame) = frames.last_mut() {
+                let (state, position) = *frame;
+                frame.1 += 1;
+                let next = successors
+                    .get(&state)
+                    .and_then(|targets| targets.get(position))
+                    .copied();
+
+                match next {
+                    Some(to) if !index.contains_key(&to) => {
+                        index.insert(to, next_index);
+                        low_link.insert(to, next_index);
+                        next_index += 1;
+                        stack.push(to);
+                        on_stack.insert(to);
+                        frames.push((to, 0));
+                    }
+                    Some(to) => {
+                        if on_stack.contains(&to) {
+                            let low = low_link[&state].min(index[&to]);
+                            low_link.insert(state, low);
+                        }
+                    }
+                    None => {
+                        frames.pop();
+                        if let Some(&(parent, _)) = frames.last() {
+                            let low = low_link[&parent].min(low_link[&state]);
+                            low_link.insert(parent, low);
+                        }
+                        if low_link[&state] == index[&state] {
+                            let mut component = Vec::new();
+                            while let Some(member) = stack.pop() {
+                                on_stack.remove(&member);
+                                component.push(member);
+                                if member == state {
+                                    break;
+                                }
+                            }
+                            component.sort_unstable();
+                            components.push(component);
+                        }
+                    }
+                }
+            }
+        }
+
+        components.sort_by_key(|c| c[0]);
+        components
+    }
+
+    /// Transições que não mudam o estado (`from == to`)
+    ///
+    /// O conjunto de ações não faz o contrato progredir: em geral o passo ocioso ou o
+    /// exercício de uma permissão.
+    ///
+    /// # Retorna
+    /// Os laços, ordenados pelo id da transição
+    pub fn self_loops(&self) -> Vec<&Transition> {
+        let mut loops: Vec<&Transition> =
+            self.transitions.iter().filter(|t| t.from == t.to).collect();
+        loops.sort_by_key(|t| t.id);
+        loops
+    }
+
+    /// Componentes fortemente conexos que formam ciclos: mais de um estado, ou um
+    /// único estado com transição para si mesmo
+    ///
+    /// Uma execução pode permanecer num desses ciclos indefinidamente sem chegar à
+    /// satisfação, o que merece atenção numa análise de vivacidade.
+    ///
+    /// # Retorna
+    /// Os componentes cíclicos, na ordem de `strongly_connected_components`
+    pub fn cyclic_components(&self) -> Vec<Vec<usize>> {
+        self.strongly_connected_components()
+            .into_iter()
+            .filter(|component| {
+                component.len() > 1
+                    || self
+                        .transitions
+                        .iter()
+                        .any(|t| t.from == component[0] && t.to == component[0])
+            })
+            .collect()
+    }
+
+    /// Linguagem dos traços de satisfação como expressão regular sobre os rótulos das
+    /// transições (eliminação de estados)
+    ///
+    /// Os estados de satisfação são os de aceitação. Cada transição vira um símbolo com
+    /// as suas ações positivas entre chaves (`{(a, pay, b)}`; `{}` para o passo ocioso);
+    /// transições paralelas viram alternativas e ciclos viram a estrela de Kleene. Os
+    /// estados são eliminados em ordem de id, então o resultado é determinístico.
+    ///
+    /// # Argumentos
+    /// * `table` - Tabela de símbolos (já travada pelo chamador)
+    ///
+    /// # Retorna
+    /// A expressão, ou `∅` se nenhum estado de satisfação for alcançável
+    pub fn to_regex(&self, table: &SymbolTable) -> String {
+        let Some(ref initial) = self.initial else {
+            return "∅".to_string();
+        };
+        // Estados artificiais de entrada e saída, fora do espaço de ids
+        let (start, accept) = (usize::MAX, usize::MAX - 1);
+
+        let mut edges: FxHashMap<(usize, usize), TraceRegex> = FxHashMap::default();
+        fn add_edge(
+            edges: &mut FxHashMap<(usize, usize), TraceRegex>,
+            key: (usize, usize),
+            regex: TraceRegex,
+        ) {
+            let merged = match edges.remove(&key) {
+                Some(existing) => existing.union(regex),
+                None => regex,
+            };
+            edges.insert(key, merged);
+        }
+
+        add_edge(&mut edges, (start, initial.id), TraceRegex::Epsilon);
+        let mut state_ids: Vec<usize> = self.states.iter().map(|s| s.id).collect();
+        state_ids.sort_unstable();
+        for state in &self.states {
+            if state.situation == StateSituation::Satisfaction {
+                add_edge(&mut edges, (state.id, accept), TraceRegex::Epsilon);
+            }
+        }
+
+        let mut transitions: Vec<&Transition> = self.transitions.iter().collect();
+        transitions.sort_by_key(|t| t.id);
+        for transition in transitions {
+            let actions: Vec<String> = transition
+                .action_refs()
+                .filter(|a| !a.negation)
+                .map(|a| a.format_with_symbols(table))
+                .collect();
+            let label = TraceRegex::Symbol(format!("{{{}}}", actions.join(", ")));
+            add_edge(&mut edges, (transition.from, transition.to), label);
+        }
+
+        for state in state_ids {
+            let self_loop = edges
+                .remove(&(state, state))
+                .map(TraceRegex::star)
+                .unwrap_or(TraceRegex::Epsilon);
+            let mut incoming: Vec<(usize, TraceRegex)> = Vec::new();
+            let mut outgoing: Vec<(usize, TraceRegex)> = Vec::new();
+            let keys: Vec<(usize, usize)> = edges
+                .keys()
+                .filter(|(from, to)| *from == state || *to == state)
+                .copied()
+                .collect();
+            for key in keys {
+                if let Some(regex) = edges.remove(&key) {
+                    if key.1 == state {
+                        incoming.push((key.0, regex));
+                    } else {
+                        outgoing.push((key.1, regex));
+                    }
+                }
+            }
+            incoming.sort_by_key(|(from, _)| *from);
+            outgoing.sort_by_key(|(to, _)| *to);
+
+            for (from, before) in &incoming {
+                for (to, after) in &outgoing {
+                    let path = before
+                        .clone()
+                        .concat(self_loop.clone())
+                        .concat(after.clone());
+                    add_edge(&mut edges, (*from, *to), path);
+                }
+            }
+        }
+
+        edges
+            .remove(&(start, accept))
+            .map(|regex| regex.to_string())
+            .unwrap_or_else(|| "∅".to_string())
+    }
+
     pub fn get_transition_by_id(&self, id: usize) -> Option<&Transition> {
         self.transitions.iter().find(|t| t.id == id)
     }
+
+    /// Explica uma transição a partir da máscara e das cláusulas dos estados ligados
+    ///
+    /// # Argumentos
+    /// * `id` - ID da transição
+    ///
+    /// # Retorna
+    /// A explicação, ou `None` se não houver transição com esse id
+    pub fn explain_transition(&self, id: usize) -> Option<TransitionExplanation> {
+        let transition = self.get_transition_by_id(id)?;
+        let clause_of = |state_id: usize| {
+            self.get_state_by_id(state_id)
+                .and_then(|s| s.clause.as_ref())
+                .map(|c| c.to_string())
+                .unwrap_or_else(|| "T".to_string())
+        };
+
+        Some(TransitionExplanation {
+            id: transition.id,
+            from: transition.from,
+            to: transition.to,
+            mask: transition.action_set.clone(),
+            actions: transition.action_refs().map(|a| a.to_string()).collect(),
+            before: clause_of(transition.from),
+            after: clause_of(transition.to),
+        })
+    }
+
+    /// Executa um traço de conjuntos de ações concorrentes a partir do estado inicial
+    ///
+    /// Cada passo corresponde à transição cujas ações positivas (não negadas) são
+    /// exatamente as do passo; um passo vazio corresponde ao ramo ocioso. A execução
+    /// para ao alcançar um estado de satisfação ou violação, já que esses estados
+    /// não possuem transições de saída.
+    ///
+    /// # Argumentos
+    /// * `trace` - Sequência de conjuntos de ações relativizadas
+    ///
+    /// # Retorna
+    /// A situação do estado final ou `TraceOutcome::Stuck` se algum passo não tiver transição
+    pub fn run_trace(&self, trace: &[Vec<RelativizedAction>]) -> TraceOutcome {
+        let Some(initial) = self.initial.as_ref() else {
+            return TraceOutcome::Stuck { state: 0, step: 0 };
+        };
+        let mut current = self.get_state_by_id(initial.id).unwrap_or(initial);
+
+        for (step, actions) in trace.iter().enumerate() {
+            if matches!(
+                current.situation,
+                StateSituation::Satisfaction | StateSituation::Violating
+            ) {
+                return TraceOutcome::from_state(current, step);
+            }
+
+            let expected: FxHashSet<&RelativizedAction> =
+                actions.iter().filter(|a| !a.negation).collect();
+
+            let next = self
+                .transitions
+                .iter()
+                .filter(|t| t.from == current.id)
+                .filter(|t| {
+                    let positive: FxHashSet<&RelativizedAction> = t
+                        .action_refs()
+                        .map(|a| a.as_ref())
+                        .filter(|a| !a.negation)
+                        .collect();
+                    positive == expected
+                })
+                .min_by_key(|t| t.id)
+                .and_then(|t| self.get_state_by_id(t.to));
+
+            match next {
+                Some(state) => current = state,
+                None => {
+                    return TraceOutcome::Stuck {
+                        state: current.id,
+                        step,
+                    }
+                }
+            }
+        }
+
+        TraceOutcome::from_state(current, trace.len())
+    }
 }
 
 impl fmt::Display for Automaton {
@@ -447,3 +1459,431 @@ impl fmt::Display for Automaton {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{build, build_with, parse, serial};
+
+    #[test]
+    fn obligation_of_violation_is_unavoidable() {
+        let _table = serial();
+        let automaton = build("{buyer,seller}O(0);");
+        let initial = automaton.initial.as_ref().unwrap().id;
+
+        let unavoidable: Vec<usize> = automaton.get_unavoidable_violations().iter().map(|s| s.id).collect();
+        assert_eq!(unavoidable, [initial]);
+        // Flagged apart from the violating states: the state itself stays conflict-free
+        assert_eq!(automaton.get_state_by_id(initial).unwrap().situation, StateSituation::ConflictFree);
+        assert!(!automaton.conflict_found);
+    }
+
+    #[test]
+    fn violation_penalty_is_unavoidable_once_reached() {
+        let _table = serial();
+        let automaton = build("{seller,buyer}F(cancel)_/{seller,buyer}O(0)/_;");
+        let initial = automaton.initial.as_ref().unwrap().id;
+
+        let unavoidable = automaton.get_unavoidable_violations();
+        assert_eq!(unavoidable.len(), 1);
+        let penalty = unavoidable[0].id;
+        assert_ne!(penalty, initial);
+        assert!(automaton.successors(initial).iter().any(|&(_, to)| to == penalty));
+        assert!(automaton
+            .successors(penalty)
+            .iter()
+            .all(|&(_, to)| automaton.get_state_by_id(to).unwrap().situation == StateSituation::Violating));
+    }
+
+    #[test]
+    fn deontic_tags_are_the_obligation_and_the_prohibition() {
+        let _table = serial();
+        let src = "{buyer,seller}O(pay);\n{seller,buyer}F(cancel);";
+        let automaton = build(src);
+        let contract = parse(src);
+        let searcher = ConflictSearcher::new(contract.individuals.clone(), contract.get_all_conflicts());
+
+        let tags = automaton.all_deontic_tags(&searcher);
+        let json: serde_json::Value = serde_json::from_str(&crate::utils::get_deontic_tags_json(&tags)).unwrap();
+        let names: Vec<&str> = json.as_array().unwrap().iter().map(|t| t["tag"].as_str().unwrap()).collect();
+        assert_eq!(names, ["O(buyer,pay,seller)", "F(seller,cancel,buyer)"]);
+    }
+
+    #[test]
+    fn action_refs_borrow_the_actions_of_both_set_kinds() {
+        for source_len in [5, MASK_ACTION_LIMIT + 10] {
+            let source: Arc<Vec<Arc<RelativizedAction>>> = Arc::new(
+                (0..source_len as i32)
+                    .map(|value| Arc::new(RelativizedAction::new(1, BasicAction { value, ..BasicAction::skip() }, 2)))
+                    .collect(),
+            );
+            let indices = [0, 2, source_len - 1];
+            let transition = Transition::new(0, 1, ActionSet::from_indices(&indices, source_len), source.clone());
+
+            assert_eq!(transition.action_indices().collect::<Vec<_>>(), indices);
+            let refs: Vec<&Arc<RelativizedAction>> = transition.action_refs().collect();
+            assert!(refs.iter().zip(indices).all(|(action, idx)| Arc::ptr_eq(action, &source[idx])));
+            assert_eq!(refs.into_iter().cloned().collect::<Vec<_>>(), transition.actions());
+        }
+    }
+
+    #[test]
+    fn explanation_lists_the_actions_and_both_clauses() {
+        let _table = serial();
+        let automaton = build("{buyer,seller}O(pay);\n{buyer,seller}[pay]({seller,buyer}O(deliver));");
+        let initial = automaton.initial.as_ref().unwrap().id;
+        let paying = automaton
+            .transitions
+            .iter()
+            .find(|t| t.from == initial && t.action_refs().any(|a| !a.negation))
+            .unwrap();
+
+        let explanation = automaton.explain_transition(paying.id).unwrap();
+        assert_eq!((explanation.from, explanation.to), (initial, paying.to));
+        assert_eq!(explanation.actions, ["(buyer, pay, seller)"]);
+        assert!(explanation.before.contains("OBLIGATION(pay)"));
+        assert!(explanation.after.contains("OBLIGATION(deliver)"));
+        assert!(automaton.explain_transition(usize::MAX).is_none());
+    }
+
+    #[test]
+    fn situation_counts_sum_to_the_states() {
+        let _table = serial();
+        let src = "{buyer,seller}O(pay);\n{buyer,seller}[pay]({seller,buyer}O(deliver));\n\
+                   {seller,buyer}[deliver]({seller,buyer}F(deliver));\n{seller,buyer}[deliver]({seller,buyer}O(deliver));";
+        let automaton = build_with(src, &["-c"]);
+        let counts = automaton.situation_counts();
+
+        assert_eq!(counts.total(), automaton.states.len());
+        assert!(counts.satisfaction > 0 && counts.violating > 0 && counts.conflicting > 0 && counts.conflict_free > 0);
+
+        // Columns 10 to 14 of the result CSV
+        let row = crate::utils::get_automaton_data(0, 0, 0, &automaton, &parse(src), ';');
+        let columns: Vec<usize> = row.split(';').skip(9).take(5).map(|c| c.parse().unwrap()).collect();
+        assert_eq!(
+            columns,
+            [counts.satisfaction, counts.violating, counts.conflicting, counts.conflict_free, counts.not_checked]
+        );
+    }
+
+    #[test]
+    fn only_a_permission_contract_is_vacuous() {
+        let _table = serial();
+        assert!(build("{buyer,seller}P(pay);\n{seller}P(ship);").is_vacuous());
+        assert!(!build("{buyer,seller}O(pay);\n{seller}P(ship);").is_vacuous());
+    }
+
+    /// Automaton with the given situations, initial state first, and `(from, to)`
+    /// transitions between their indices; returns it with the state and transition ids.
+    fn automaton(situations: &[StateSituation], edges: &[(usize, usize)]) -> (Automaton, Vec<usize>, Vec<usize>) {
+        let mut automaton = Automaton::new(Contract::default());
+        let states: Vec<usize> = situations
+            .iter()
+            .map(|&situation| {
+                let mut state = State::with_auto_id(None);
+                state.situation = situation;
+                let id = state.id;
+                automaton.add_state(state);
+                id
+            })
+            .collect();
+        automaton.initial = automaton.get_state_by_id(states[0]).cloned();
+
+        let source = Arc::new(Vec::new());
+        let transitions = edges
+            .iter()
+            .map(|&(from, to)| {
+                let transition = Transition::new(states[from], states[to], ActionSet::from_indices(&[], 0), source.clone());
+                let id = transition.id;
+                automaton.add_transition(transition);
+                id
+            })
+            .collect();
+        (automaton, states, transitions)
+    }
+
+    #[test]
+    fn both_paths_to_satisfaction_are_found() {
+        use StateSituation::*;
+        // 0 -> 2 directly, or 0 -> 1 -> 2, with a loop on 1 and a dead end at 3
+        let (automaton, _, t) = automaton(
+            &[ConflictFree, ConflictFree, Satisfaction, Violating],
+            &[(0, 2), (0, 1), (1, 1), (1, 2), (1, 3)],
+        );
+
+        assert_eq!(automaton.satisfaction_paths(10), vec![vec![t[0]], vec![t[1], t[3]]]);
+        assert_eq!(automaton.satisfaction_paths(1), vec![vec![t[0]]]);
+        assert!(automaton.satisfaction_paths(0).is_empty());
+    }
+
+    #[test]
+    fn successors_and_predecessors_of_a_hand_built_automaton() {
+        let _table = serial();
+        let mut automaton = Automaton::new(parse("{buyer,seller}O(pay);"));
+        let a = automaton.initial.as_ref().unwrap().id;
+        let mut add_state = || {
+            let state = State::with_auto_id(None);
+            let id = state.id;
+            automaton.add_state(state);
+            id
+        };
+        let (b, c) = (add_state(), add_state());
+
+        let mut edge = |from, to| {
+            let transition = Transition::new(from, to, ActionSet::Mask(0), Arc::new(Vec::new()));
+            let id = transition.id;
+            automaton.add_transition(transition);
+            id
+        };
+        // a -> b twice, a -> c, b -> c and a loop on c
+        let t1 = edge(a, b);
+        let t2 = edge(a, c);
+        let t3 = edge(b, c);
+        let t4 = edge(c, c);
+        let t5 = edge(a, b);
+
+        assert_eq!(automaton.successors(a), [(t1, b), (t2, c), (t5, b)]);
+        assert_eq!(automaton.successors(b), [(t3, c)]);
+        assert_eq!(automaton.successors(c), [(t4, c)]);
+        assert_eq!(automaton.predecessors(a), []);
+        assert_eq!(automaton.predecessors(b), [(t1, a), (t5, a)]);
+        assert_eq!(automaton.predecessors(c), [(t2, a), (t3, b), (t4, c)]);
+
+        let successors = automaton.successor_map();
+        assert_eq!(successors[&a], [b, c, b]);
+        assert_eq!(successors[&c], [c]);
+        let predecessors = automaton.predecessor_map();
+        assert_eq!(predecessors[&c], [a, b, c]);
+        assert!(!predecessors.contains_key(&a));
+    }
+
+    #[test]
+    fn cycle_is_found_as_a_strongly_connected_component() {
+        let _table = serial();
+        let mut automaton = Automaton::new(parse("{buyer,seller}O(pay);"));
+        let a = automaton.initial.as_ref().unwrap().id;
+        let mut add_state = || {
+            let state = State::with_auto_id(None);
+            let id = state.id;
+            automaton.add_state(state);
+            id
+        };
+        let (b, c, d, e) = (add_state(), add_state(), add_state(), add_state());
+
+        // a -> b <-> c -> d (loop on d) -> e
+        for (from, to) in [(a, b), (b, c), (c, b), (c, d), (d, d), (d, e)] {
+            automaton.add_transition(Transition::new(from, to, ActionSet::Mask(0), Arc::new(Vec::new())));
+        }
+
+        let mut components = automaton.strongly_connected_components();
+        components.sort();
+        assert_eq!(components, [vec![a], vec![b, c], vec![d], vec![e]]);
+        assert_eq!(automaton.cyclic_components(), [vec![b, c], vec![d]]);
+
+        let cycles = automaton.cyclic_components();
+        let summary = crate::utils::print_result(&automaton, 0, 0, 0, 0, None, Some(&cycles));
+        assert!(summary.contains("2 cycle(s) where an execution may never reach satisfaction"));
+        assert!(summary.contains(&format!("   - {{s{}, s{}}}", b, c)));
+        assert!(summary.contains(&format!("   - {{s{}}}", d)));
+    }
+
+    #[test]
+    fn transition_labels_are_the_same_in_every_run() {
+        let _table = serial();
+        let src = "{buyer,seller}O(pay);
+                   {seller,buyer}O(deliver);
+                   {buyer,seller}F(cancel);
+                   {seller,carrier}P(ship);";
+        let labels = |automaton: &Automaton| {
+            let mut labels: Vec<String> = automaton
+                .transitions
+                .iter()
+                .map(|t| {
+                    let actions = t.actions();
+                    // Within a label, actions follow the sorted source map
+                    let keys: Vec<_> = actions
+                        .iter()
+                        .map(|a| (a.sender, a.action.value, a.receiver, a.negation))
+                        .collect();
+                    assert!(keys.windows(2).all(|w| w[0] < w[1]), "{:?}", keys);
+                    actions.iter().map(|a| a.to_string()).collect::<Vec<_>>().join(", ")
+                })
+                .collect();
+            labels.sort();
+            labels
+        };
+
+        let first = labels(&build(src));
+        assert!(first.iter().any(|l| l.contains(", ")), "no label with several actions");
+        for _ in 0..3 {
+            assert_eq!(labels(&build(src)), first);
+        }
+    }
+
+    #[test]
+    fn name_constructors_intern_the_same_ids_as_add_symbol() {
+        let _table = serial();
+        let mut table = SymbolTable::lock();
+        let action = RelativizedAction::from_names("buyer", "pay", "seller", &mut table);
+        assert_eq!(action.sender, table.add_symbol("buyer".to_string(), SymbolType::Individual));
+        assert_eq!(action.action.value, table.add_symbol("pay".to_string(), SymbolType::Action));
+        assert_eq!(action.receiver, table.add_symbol("seller".to_string(), SymbolType::Individual));
+        assert_eq!(RelativizedAction::from_names("", "pay", "", &mut table).sender, -1);
+
+        let pay = BasicAction::with_value(action.action.value);
+        let obligation = DeonticClauseType::Obligation;
+        assert_eq!(
+            DeonticTag::from_names(obligation, "buyer", "pay", "seller", &mut table),
+            DeonticTag::directed(obligation, pay.clone(), action.sender, action.receiver)
+        );
+        assert_eq!(
+            DeonticTag::from_names(obligation, "buyer", "pay", "", &mut table),
+            DeonticTag::relativized(obligation, pay.clone(), action.sender)
+        );
+        assert_eq!(
+            DeonticTag::from_names(obligation, "", "pay", "", &mut table).relativization,
+            RelativizationType::Global
+        );
+        let ids = table.get_actions().len();
+        drop(table);
+
+        // The same tag the searcher derives from the parsed clause
+        let src = "{buyer,seller}O(pay);";
+        let contract = parse(src);
+        let searcher = ConflictSearcher::new(contract.individuals.clone(), contract.get_all_conflicts());
+        let tag = DeonticTag::from_names(obligation, "buyer", "pay", "seller", &mut SymbolTable::lock());
+        assert!(build(src).all_deontic_tags(&searcher).contains(&tag));
+        assert_eq!(SymbolTable::lock().get_actions().len(), ids);
+    }
+
+    #[test]
+    fn verifier_flags_a_state_with_an_equivalent_clause() {
+        let _table = serial();
+        let mut automaton = build("{buyer,seller}O(pay) ^ {seller,buyer}O(deliver);");
+        assert!(automaton.find_dedup_failures().is_empty());
+
+        // The same obligations in the other order: not equal, but equivalent
+        let initial = automaton.initial.clone().unwrap();
+        let reordered = parse("{seller,buyer}O(deliver) ^ {buyer,seller}O(pay);")
+            .clauses
+            .into_iter()
+            .next()
+            .unwrap();
+        assert_ne!(Some(&reordered), initial.clause.as_ref());
+        let duplicate = State::with_auto_id(Some(reordered));
+        let duplicate_id = duplicate.id;
+        automaton.add_state(duplicate);
+
+        assert_eq!(automaton.find_dedup_failures(), [vec![initial.id, duplicate_id]]);
+    }
+
+    #[test]
+    fn linear_automaton_folds_into_a_concatenation() {
+        let _table = serial();
+        let mut automaton = Automaton::new(parse("{buyer,seller}O(pay);"));
+        let first = automaton.initial.as_ref().unwrap().id;
+        let mut table = SymbolTable::lock();
+        let actions = Arc::new(vec![
+            Arc::new(RelativizedAction::from_names("buyer", "pay", "seller", &mut table)),
+            Arc::new(RelativizedAction::from_names("seller", "ship", "buyer", &mut table)),
+        ]);
+        let mut add_state = |situation| {
+            let mut state = State::with_auto_id(None);
+            state.situation = situation;
+            let id = state.id;
+            automaton.add_state(state);
+            id
+        };
+        let paid = add_state(StateSituation::ConflictFree);
+        let shipped = add_state(StateSituation::Satisfaction);
+
+        // pay, then ship
+        let mut edge = |from, to, mask| {
+            let transition = Transition::new(from, to, ActionSet::Mask(mask), actions.clone());
+            automaton.add_transition(transition);
+        };
+        edge(first, paid, 0b01);
+        edge(paid, shipped, 0b10);
+        assert_eq!(automaton.to_regex(&table), "{(buyer, pay, seller)} {(seller, ship, buyer)}");
+
+        // Waiting before shipping becomes a star between the two
+        automaton.add_transition(Transition::new(paid, paid, ActionSet::Mask(0), actions.clone()));
+        assert_eq!(automaton.to_regex(&table), "{(buyer, pay, seller)} {}* {(seller, ship, buyer)}");
+    }
+
+    #[test]
+    fn exercising_a_lasting_permission_is_a_self_loop() {
+        let _table = serial();
+        // `browse` stays permitted after each `browse`, until `pay`
+        let automaton = build("{buyer,seller}[browse*]({buyer,seller}P(browse));\n{buyer,seller}O(pay);");
+        let browse = SymbolTable::lock().get_id("browse", SymbolType::Action).unwrap();
+        let loops = automaton.self_loops();
+        assert_eq!(loops.len(), 1);
+        assert!(loops[0].action_refs().filter(|a| !a.negation).map(|a| a.action.value).eq([browse]));
+
+        // The only dashed edge of the DOT export
+        let dot = crate::utils::AutomatonExporter::dump_to_dot_with(&automaton, false, None, 0, false);
+        let dashed: Vec<&str> = dot.lines().filter(|l| l.contains("style = dashed")).collect();
+        assert_eq!(dashed.len(), 1, "{}", dot);
+        assert!(dashed[0].contains(&format!("S{} -> S{}", loops[0].from, loops[0].to)), "{}", dashed[0]);
+    }
+
+    #[test]
+    fn trace_is_rebuilt_from_the_discovery_transitions() {
+        let _table = serial();
+        let automaton = build(
+            "{buyer,seller}O(pay);\n{buyer,seller}[pay]({seller,buyer}O(ship) ^ {seller,buyer}F(ship));",
+        );
+        let initial = automaton.initial.as_ref().unwrap().id;
+        assert_eq!(automaton.get_state_by_id(initial).unwrap().trace, None);
+
+        let conflict = automaton.get_conflicts()[0].id;
+        let path = automaton.trace_to(conflict);
+        assert!(!path.is_empty());
+        let steps: Vec<&Transition> = path
+            .iter()
+            .map(|id| automaton.get_transition_by_id(*id).unwrap())
+            .collect();
+        assert_eq!(steps[0].from, initial);
+        assert_eq!(steps.last().unwrap().to, conflict);
+        for pair in steps.windows(2) {
+            assert_eq!(pair[0].to, pair[1].from);
+        }
+        // Each state on the way stores only the transition that discovered it
+        for step in &steps {
+            assert_eq!(automaton.get_state_by_id(step.to).unwrap().trace, Some(step.id));
+        }
+
+        let trace = crate::utils::print_trace(&automaton);
+        assert!(trace.contains(&format!("(s{})", conflict)), "{}", trace);
+
+        // Later incoming transitions don't replace the first
+        let mut state = State::with_auto_id(None);
+        state.push_trace(7);
+        state.push_trace(9);
+        assert_eq!(state.trace, Some(7));
+    }
+
+    #[test]
+    fn trace_stops_at_the_initial_state_and_at_broken_links() {
+        use StateSituation::*;
+        // 0 -> 1 -> 2, and 3 reached only through a loop between 3 and 4
+        let (mut automaton, s, t) = automaton(
+            &[ConflictFree, ConflictFree, Conflicting, Conflicting, ConflictFree],
+            &[(0, 1), (1, 2), (3, 4), (4, 3)],
+        );
+        for (state, transition) in [(s[1], t[0]), (s[2], t[1]), (s[3], t[3]), (s[4], t[2])] {
+            assert!(automaton.update_state(state, |state| state.push_trace(transition)));
+        }
+
+        assert_eq!(automaton.trace_to(s[2]), [t[0], t[1]]);
+        assert!(automaton.trace_to(s[0]).is_empty());
+        assert!(automaton.trace_to(usize::MAX).is_empty());
+        // A cycle of discovery transitions ends once a state repeats
+        assert_eq!(automaton.trace_to(s[3]), [t[2], t[3]]);
+
+        // A discovery transition that is not in the automaton ends the path there
+        automaton.update_state(s[1], |state| state.trace = Some(usize::MAX));
+        assert_eq!(automaton.trace_to(s[2]), [t[1]]);
+    }
+}
@@ -1,6 +1,7 @@
 use crate::model::actions::*;
 use crate::utils::*;
-use rustc_hash::FxHashSet;
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
 use std::sync::Arc;
 
@@ -29,6 +30,8 @@ impl fmt::Display for ClauseCompositionType {
 pub enum ConflictType {
     Global,
     Relativized,
+    /// Conflito que só vale para o indivíduo indicado (`(a,b){X}`)
+    Scoped(i32),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -91,6 +94,22 @@ impl fmt::Display for ClauseComposition {
     }
 }
 
+// ==================== ClauseVisitor ====================
+
+/// Visitante de cláusulas usado por `Clause::visit`
+///
+/// A travessia passa pela própria cláusula, pela penalidade (deônticas), pela
+/// cláusula interna (dinâmicas) e pela composição, nessa ordem.
+pub trait ClauseVisitor {
+    fn visit_clause(&mut self, clause: &Clause);
+
+    /// Se `false`, penalidades e cláusulas internas não são visitadas,
+    /// apenas a cadeia de composições
+    fn visit_nested(&self) -> bool {
+        true
+    }
+}
+
 // ==================== Clause ====================
 
 #[derive(Debug, Clone, Eq, Hash, PartialEq)]
@@ -233,6 +252,194 @@ impl Clause {
         }
     }
 
+    /// Percorre a cláusula e todas as suas subcláusulas com um `ClauseVisitor`
+    pub fn visit<V: ClauseVisitor>(&self, visitor: &mut V) {
+        visitor.visit_clause(self);
+
+        if visitor.visit_nested() {
+            match self {
+                Clause::Deontic {
+                    penalty: Some(pen), ..
+                } => pen.visit(visitor),
+                Clause::Dynamic { clause, .. } => clause.visit(visitor),
+                _ => {}
+            }
+        }
+
+        if let Some(comp) = self.get_composition() {
+            comp.other.visit(visitor);
+        }
+    }
+
+    /// Renumera indivíduos e ações da cláusula e de suas subcláusulas
+    ///
+    /// # Argumentos
+    /// * `ids` - Mapeamento de id antigo para id novo (ver `SymbolTable::normalize`)
+    ///
+    /// # Retorna
+    /// Uma nova cláusula com os ids substituídos
+    pub fn remap_symbols(&self, ids: &FxHashMap<i32, i32>) -> Clause {
+        let remap_id = |id: i32| {
+            if id > 0 {
+                ids.get(&id).copied().unwrap_or(id)
+            } else {
+                id
+            }
+        };
+        let remap_composition = |composition: &Option<ClauseComposition>| {
+            composition.as_ref().map(|c| ClauseComposition {
+                composition_type: c.composition_type,
+                other: Arc::new(c.other.remap_symbols(ids)),
+            })
+        };
+
+        match self {
+            Clause::Boolean { value, composition } => Clause::Boolean {
+                value: *value,
+                composition: remap_composition(composition),
+            },
+            Clause::Deontic {
+                sender,
+                receiver,
+                relativization_type,
+                action,
+                deontic_type,
+                penalty,
+                composition,
+            } => Clause::Deontic {
+                sender: remap_id(*sender),
+                receiver: remap_id(*receiver),
+                relativization_type: *relativization_type,
+                action: action.remap(ids),
+                deontic_type: *deontic_type,
+                penalty: penalty.as_ref().map(|p| Arc::new(p.remap_symbols(ids))),
+                composition: remap_composition(composition),
+            },
+            Clause::Dynamic {
+                sender,
+                receiver,
+                relativization_type,
+                action,
+                clause,
+                composition,
+            } => Clause::Dynamic {
+                sender: remap_id(*sender),
+                receiver: remap_id(*receiver),
+                relativization_type: *relativization_type,
+                action: action.remap(ids),
+                clause: Arc::new(clause.remap_symbols(ids)),
+                composition: remap_composition(composition),
+            },
+        }
+    }
+
+    /// Número de nós da cláusula: ela própria, suas composições, penalidades e
+    /// cláusulas internas de dinâmicas
+    ///
+    /// # Retorna
+    /// O total de nós, usado como medida do tamanho de um estado (`--max-clause-size`)
+    pub fn node_count(&self) -> usize {
+        let nested = match self {
+            Clause::Boolean { .. } => 0,
+            Clause::Deontic { penalty, .. } => penalty.as_ref().map_or(0, |p| p.node_count()),
+            Clause::Dynamic { clause, .. } => clause.node_count(),
+        };
+        let composed = self.get_composition().map_or(0, |c| c.other.node_count());
+        1 + nested + composed
+    }
+
+    /// Forma canônica semântica da cláusula
+    ///
+    /// Cadeias de composição formadas só por `And` ou só por `Or` são comutativas e
+    /// idempotentes: os elementos são ordenados de forma determinística e repetições
+    /// são removidas, de modo que `A AND B` e `B AND A` resultam na mesma cláusula.
+    /// Cadeias mistas ou com `Xor` mantêm a ordem. Penalidades e cláusulas internas
+    /// de dinâmicas também são canonicalizadas.
+    ///
+    /// # Retorna
+    /// Uma nova cláusula semanticamente equivalente, usada como chave de cache e de estado
+    pub fn canonicalize(&self) -> Clause {
+        let mut elements = Vec::new();
+        let mut types = Vec::new();
+        let mut current = Some(self);
+        while let Some(clause) = current {
+            let mut head = clause.clone();
+            head.set_composition_to_none();
+            elements.push(head.canonicalize_single());
+            current = clause.get_composition().map(|comp| {
+                types.push(comp.composition_type);
+                comp.other.as_ref()
+            });
+        }
+
+        let commutative = types.first().is_some_and(|first| {
+            matches!(
+                first,
+                ClauseCompositionType::And | ClauseCompositionType::Or
+            ) && types.iter().all(|t| t == first)
+        });
+        if commutative {
+            elements.sort_by_cached_key(Self::structural_hash);
+            elements.dedup();
+            types.truncate(elements.len() - 1);
+        }
+
+        let mut result = elements.pop().unwrap();
+        while let Some(mut head) = elements.pop() {
+            let comp_type = types.pop().unwrap();
+            head.set_composition(ClauseComposition::new(comp_type, result));
+            result = head;
+        }
+        result
+    }
+
+    /// Canonicaliza as subcláusulas de uma cláusula sem composição
+    fn canonicalize_single(self) -> Clause {
+        match self {
+            Clause::Deontic {
+                sender,
+                receiver,
+                relativization_type,
+                action,
+                deontic_type,
+                penalty,
+                composition,
+            } => Clause::Deontic {
+                sender,
+                receiver,
+                relativization_type,
+                action,
+                deontic_type,
+                penalty: penalty.map(|p| Arc::new(p.canonicalize())),
+                composition,
+            },
+            Clause::Dynamic {
+                sender,
+                receiver,
+                relativization_type,
+                action,
+                clause,
+                composition,
+            } => Clause::Dynamic {
+                sender,
+                receiver,
+                relativization_type,
+                action,
+                clause: Arc::new(clause.canonicalize()),
+                composition,
+            },
+            boolean => boolean,
+        }
+    }
+
+    /// Hash estrutural determinístico, usado para ordenar elementos na forma canônica
+    fn structural_hash(&self) -> u64 {
+        use std::hash::{Hash, Hasher};
+        let mut hasher = rustc_hash::FxHasher::default();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+
     // Métodos auxiliares
     pub fn get_receiver(&self) -> &i32 {
         match self {
@@ -312,6 +519,77 @@ impl Clause {
         }
     }
 
+    /// Árvore indentada da cláusula (`--ast`), um nó por linha
+    ///
+    /// Composições viram um nó com o operador e os dois operandos como filhos;
+    /// penalidades e cláusulas internas de dinâmicas aparecem sob o nó que as contém.
+    ///
+    /// # Argumentos
+    /// * `depth` - Profundidade do nó; cada nível indenta dois espaços
+    ///
+    /// # Retorna
+    /// O texto da árvore, terminado em nova linha
+    pub fn format_tree(&self, depth: usize) -> String {
+        let indent = "  ".repeat(depth);
+
+        if let Some(comp) = self.get_composition() {
+            let mut head = self.clone();
+            head.set_composition_to_none();
+            return format!(
+                "{}{}\n{}{}",
+                indent,
+                comp.composition_type,
+                head.format_tree(depth + 1),
+                comp.other.format_tree(depth + 1)
+            );
+        }
+
+        let table = SymbolTable::lock();
+        let relativization = match self {
+            Clause::Boolean { .. } => String::new(),
+            _ => match self.format_relativization(&table) {
+                r if r.is_empty() => " global".to_string(),
+                r => format!(" {}", r),
+            },
+        };
+        drop(table);
+
+        match self {
+            Clause::Boolean { value, .. } => {
+                format!("{}{}\n", indent, if *value { "TRUE" } else { "FALSE" })
+            }
+            Clause::Deontic {
+                deontic_type,
+                action,
+                penalty,
+                ..
+            } => {
+                let mut out = format!(
+                    "{}{}{}\n{}  action: {}\n",
+                    indent,
+                    deontic_type.symbol(),
+                    relativization,
+                    indent,
+                    action
+                );
+                if let Some(pen) = penalty {
+                    out.push_str(&format!("{}  penalty:\n", indent));
+                    out.push_str(&pen.format_tree(depth + 2));
+                }
+                out
+            }
+            Clause::Dynamic { action, clause, .. } => format!(
+                "{}DYNAMIC{}\n{}  action: {}\n{}  clause:\n{}",
+                indent,
+                relativization,
+                indent,
+                action,
+                indent,
+                clause.format_tree(depth + 2)
+            ),
+        }
+    }
+
     fn format_individual(id: i32, symbol_table: &SymbolTable) -> String {
         if id < 0 {
             "GLOBAL".to_string()
@@ -371,8 +649,7 @@ impl fmt::Display for Clause {
                 composition,
                 ..
             } => {
-                let symbol_table = SymbolTable::instance();
-                let table = symbol_table.lock().unwrap();
+                let table = SymbolTable::lock();
                 let relativization = self.format_relativization(&table);
                 drop(table);
 
@@ -392,8 +669,7 @@ impl fmt::Display for Clause {
                 composition,
                 ..
             } => {
-                let symbol_table = SymbolTable::instance();
-                let table = symbol_table.lock().unwrap();
+                let table = SymbolTable::lock();
                 let relativization = self.format_relativization(&table);
                 drop(table);
 
@@ -425,11 +701,41 @@ impl Conflict {
     }
 }
 
+impl Conflict {
+    /// Indica se o conflito vale para uma ação com este remetente e receptor
+    ///
+    /// Conflitos globais e relativizados valem para qualquer parte; um conflito com
+    /// escopo só vale quando o indivíduo do escopo é o remetente ou o receptor.
+    pub fn applies_to(&self, sender: i32, receiver: i32) -> bool {
+        match self.conflict_type {
+            ConflictType::Scoped(party) => sender == party || receiver == party,
+            _ => true,
+        }
+    }
+
+    /// Conflito com o par em ordem crescente de id, para comparar declarações `(a,b)` e `(b,a)`
+    pub fn normalized(&self) -> Conflict {
+        if self.a.value <= self.b.value {
+            self.clone()
+        } else {
+            Conflict::new(self.b.clone(), self.a.clone(), self.conflict_type)
+        }
+    }
+}
+
 impl fmt::Display for Conflict {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let conflict_type_str = match self.conflict_type {
-            ConflictType::Global => "GLOBAL",
-            ConflictType::Relativized => "RELATIVIZED",
+            ConflictType::Global => "GLOBAL".to_string(),
+            ConflictType::Relativized => "RELATIVIZED".to_string(),
+            ConflictType::Scoped(party) => {
+                let table = SymbolTable::lock();
+                let name = table
+                    .get_symbol_by_id(party)
+                    .map(|s| s.value().to_string())
+                    .unwrap_or_else(|| "UNDEF".to_string());
+                format!("SCOPED {{{}}}", name)
+            }
         };
         write!(f, "({},{}: {})", self.a, self.b, conflict_type_str)
     }
@@ -452,21 +758,41 @@ impl std::hash::Hash for Conflict {
 
 #[derive(Debug, Clone)]
 pub struct Contract {
+    /// Título do cabeçalho `title "..."`, para catalogação
+    pub title: Option<String>,
+    /// Descrição do cabeçalho `description "..."`
+    pub description: Option<String>,
     pub clauses: FxHashSet<Clause>,
     pub global_conflicts: Vec<Conflict>,
     pub relativized_conflicts: Vec<Conflict>,
     pub individuals: FxHashSet<i32>,
+    /// Indivíduos declarados no cabeçalho `parties x, y;`, que entram em `individuals`
+    /// mesmo sem aparecer em nenhuma cláusula
+    pub parties: Vec<i32>,
     pub actions: FxHashSet<BasicAction>,
+    /// Texto das cláusulas marcadas com `@disabled` e, no modo `--stats`, das comentadas
+    pub disabled_clauses: Vec<String>,
+    /// Obrigações e proibições escritas sem `_/ /_`, registradas pelo parser (sem a
+    /// penalidade `F` implícita); descumpri-las é violação imediata
+    pub unpenalized: Vec<Clause>,
+    /// Avisos encontrados na validação do contrato (ver `Contract::validate`)
+    pub warnings: Vec<String>,
 }
 
 impl Contract {
     pub fn new() -> Self {
         Contract {
+            title: None,
+            description: None,
             clauses: FxHashSet::default(),
             global_conflicts: Vec::new(),
             relativized_conflicts: Vec::new(),
             individuals: FxHashSet::default(),
+            parties: Vec::new(),
             actions: FxHashSet::default(),
+            disabled_clauses: Vec::new(),
+            unpenalized: Vec::new(),
+            warnings: Vec::new(),
         }
     }
 
@@ -478,75 +804,299 @@ impl Contract {
         contract
     }
 
+    /// Adiciona uma cláusula, extraindo indivíduos e ações apenas se ela for nova
+    ///
+    /// # Retorna
+    /// `true` se a cláusula ainda não fazia parte do contrato
     pub fn add_clause(&mut self, clause: Clause) -> bool {
+        if self.clauses.contains(&clause) {
+            return false;
+        }
         self.extract_from_clause(&clause);
         self.clauses.insert(clause)
     }
 
-    fn extract_from_clause(&mut self, clause: &Clause) {
-        match clause {
-            Clause::Boolean { composition, .. } => {
-                if let Some(comp) = composition {
-                    self.extract_from_clause(&comp.other);
+    /// Declara um indivíduo do contrato que não precisa aparecer em cláusulas
+    ///
+    /// Ele participa das expansões global e relativizada das ações, inclusive com a
+    /// poda de indivíduos, que no mais só mantém os que aparecem na cláusula.
+    pub fn add_party(&mut self, party: i32) {
+        if !self.parties.contains(&party) {
+            self.parties.push(party);
+        }
+        self.individuals.insert(party);
+    }
+
+    /// União deste contrato com outro, para analisar a combinação dos dois
+    ///
+    /// Junta cláusulas (deduplicadas pelo hash estrutural), indivíduos, ações e as duas
+    /// listas de conflitos (um par `(a,b)` e `(b,a)` do mesmo tipo entra uma vez só).
+    /// Os ids não são remapeados: os dois contratos precisam ter sido lidos com a mesma
+    /// `SymbolTable`, sem `clear` entre eles, para que nomes iguais tenham o mesmo id.
+    ///
+    /// # Argumentos
+    /// * `other` - Contrato a juntar a este
+    ///
+    /// # Retorna
+    /// Um novo contrato, com título e descrição deste, sem avisos; chame `validate`
+    /// para recalculá-los
+    pub fn merge(&self, other: &Contract) -> Contract {
+        let mut merged = self.clone();
+        merged.warnings.clear();
+
+        for clause in &other.clauses {
+            merged.add_clause(clause.clone());
+        }
+        merged.individuals.extend(other.individuals.iter().copied());
+        for party in &other.parties {
+            merged.add_party(*party);
+        }
+        merged.actions.extend(other.actions.iter().cloned());
+
+        let merge_conflicts = |into: &mut Vec<Conflict>, from: &[Conflict]| {
+            for conflict in from {
+                let normalized = conflict.normalized();
+                if !into.iter().any(|c| c.normalized() == normalized) {
+                    into.push(conflict.clone());
                 }
             }
-            Clause::Deontic {
-                sender,
-                receiver,
-                action,
-                penalty,
-                composition,
-                ..
-            } => {
-                if *sender >= 0 {
-                    self.individuals.insert(*sender);
-                }
-                if *receiver >= 0 {
-                    self.individuals.insert(*receiver);
-                }
+        };
+        merge_conflicts(&mut merged.global_conflicts, &other.global_conflicts);
+        merge_conflicts(
+            &mut merged.relativized_conflicts,
+            &other.relativized_conflicts,
+        );
+
+        merged
+            .disabled_clauses
+            .extend(other.disabled_clauses.iter().cloned());
+        merged.unpenalized.extend(other.unpenalized.iter().cloned());
+
+        merged
+    }
 
-                for basic_action in action.get_basic_actions() {
-                    if !basic_action.skip && !basic_action.violation {
-                        self.actions.insert(basic_action);
-                    }
-                }
+    fn extract_from_clause(&mut self, clause: &Clause) {
+        let mut collector = ContractSymbolCollector {
+            individuals: &mut self.individuals,
+            actions: &mut self.actions,
+        };
+        clause.visit(&mut collector);
+    }
 
-                if let Some(pen) = penalty {
-                    self.extract_from_clause(pen);
+    /// Renumera todos os símbolos do contrato (cláusulas, conflitos, indivíduos e ações)
+    ///
+    /// Deve ser chamado uma única vez, logo após `SymbolTable::normalize`, antes da análise.
+    ///
+    /// # Argumentos
+    /// * `ids` - Mapeamento de id antigo para id novo
+    pub fn remap_symbols(&mut self, ids: &FxHashMap<i32, i32>) {
+        let clauses: Vec<Clause> = self.clauses.drain().collect();
+        self.individuals.clear();
+        self.actions.clear();
+        for clause in clauses {
+            self.add_clause(clause.remap_symbols(ids));
+        }
+        self.unpenalized = self.unpenalized.iter().map(|c| c.remap_symbols(ids)).collect();
+        let parties: Vec<i32> = self.parties.drain(..).collect();
+        for party in parties {
+            self.add_party(*ids.get(&party).unwrap_or(&party));
+        }
+
+        let remap_conflict = |c: &Conflict| {
+            let conflict_type = match c.conflict_type {
+                ConflictType::Scoped(party) => {
+                    ConflictType::Scoped(*ids.get(&party).unwrap_or(&party))
                 }
+                other => other,
+            };
+            Conflict::new(c.a.remap(ids), c.b.remap(ids), conflict_type)
+        };
+        self.global_conflicts = self.global_conflicts.iter().map(remap_conflict).collect();
+        self.relativized_conflicts = self
+            .relativized_conflicts
+            .iter()
+            .map(remap_conflict)
+            .collect();
+    }
 
-                if let Some(comp) = composition {
-                    self.extract_from_clause(&comp.other);
+    /// Valida o contrato e guarda os avisos encontrados em `warnings`
+    ///
+    /// Inclui conflitos redundantes (`lint_conflicts`), penalidades que contradizem
+    /// a própria cláusula (`lint_penalties`) e conflitos sobre ações que não aparecem
+    /// em nenhuma cláusula.
+    pub fn validate(&mut self) {
+        let mut warnings = self.lint_conflicts();
+        warnings.extend(self.lint_penalties());
+
+        let used = |action: &BasicAction| self.actions.iter().any(|a| a.value == action.value);
+        for conflict in self
+            .global_conflicts
+            .iter()
+            .chain(&self.relativized_conflicts)
+        {
+            for action in [&conflict.a, &conflict.b] {
+                if !used(action) {
+                    warnings.push(format!(
+                        "Conflict {} refers to action {} that is not used by any clause",
+                        conflict, action
+                    ));
                 }
             }
-            Clause::Dynamic {
-                sender,
-                receiver,
-                action,
-                clause: inner_clause,
-                composition,
-                ..
-            } => {
-                if *sender >= 0 {
-                    self.individuals.insert(*sender);
-                }
-                if *receiver >= 0 {
-                    self.individuals.insert(*receiver);
-                }
+        }
 
-                for basic_action in action.get_basic_actions() {
-                    if !basic_action.skip && !basic_action.violation {
-                        self.actions.insert(basic_action);
-                    }
-                }
+        self.warnings = warnings;
+    }
+
+    /// Verifica declarações de conflito redundantes
+    ///
+    /// Aponta pares declarados mais de uma vez (em qualquer ordem) e conflitos
+    /// relativizados já cobertos por um conflito global sobre as mesmas ações.
+    ///
+    /// # Retorna
+    /// Lista de avisos, vazia se não houver redundâncias
+    pub fn lint_conflicts(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        let mut seen: FxHashSet<Conflict> = FxHashSet::default();
+
+        for conflict in self
+            .global_conflicts
+            .iter()
+            .chain(&self.relativized_conflicts)
+        {
+            if !seen.insert(conflict.normalized()) {
+                warnings.push(format!("Duplicate conflict declaration {}", conflict));
+            }
+        }
 
-                self.extract_from_clause(inner_clause);
+        for conflict in &self.relativized_conflicts {
+            let mut global = conflict.normalized();
+            global.conflict_type = ConflictType::Global;
+            if seen.contains(&global) {
+                warnings.push(format!(
+                    "Relativized conflict {} is subsumed by the global conflict {}",
+                    conflict, global
+                ));
+            }
+        }
 
-                if let Some(comp) = composition {
-                    self.extract_from_clause(&comp.other);
+        warnings
+    }
+
+    /// Verifica penalidades que contradizem diretamente a cláusula que reparam
+    ///
+    /// Em `O(a)_/F(a)/_` quem deixa de fazer `a` passa a ser proibido de fazê-lo, e em
+    /// `F(a)_/O(a)/_` quem faz `a` passa a ser obrigado a fazê-lo. A busca de conflitos
+    /// só encontra isso se o ramo da penalidade for alcançado; aqui a verificação é
+    /// estrutural, independente da alcançabilidade.
+    ///
+    /// # Retorna
+    /// Um aviso por cláusula e ação contraditória, com a cláusula pelo seu texto e em
+    /// ordem alfabética (a ordem de `clauses` não é estável)
+    pub fn lint_penalties(&self) -> Vec<String> {
+        let mut found = Vec::new();
+        for clause in &self.clauses {
+            let mut collector = PenaltyContradictionCollector::default();
+            clause.visit(&mut collector);
+            for contradiction in collector.contradictions {
+                let entry = (clause.to_string(), contradiction);
+                if !found.contains(&entry) {
+                    found.push(entry);
                 }
             }
         }
+        found.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let table = SymbolTable::lock();
+        found
+            .into_iter()
+            .map(|(clause, (deontic_type, action, penalty_type))| {
+                let name = table
+                    .get_symbol_by_id(action)
+                    .map(|s| s.value.clone())
+                    .unwrap_or_else(|| action.to_string());
+                format!(
+                    "Clause {}: {}({}) has penalty {}({}), which contradicts the clause it repairs",
+                    clause,
+                    deontic_type.short_symbol(),
+                    name,
+                    penalty_type.short_symbol(),
+                    name
+                )
+            })
+            .collect()
+    }
+
+    /// Lista as obrigações e proibições escritas sem penalidade (`--strict`)
+    ///
+    /// Sem `_/ /_`, descumprir a cláusula é violação imediata. Muitas vezes é
+    /// intencional, por isso o aviso só é emitido sob `--strict`.
+    ///
+    /// # Retorna
+    /// Um aviso por cláusula, em ordem alfabética
+    pub fn lint_unpenalized(&self) -> Vec<String> {
+        let mut warnings: Vec<String> = self
+            .unpenalized
+            .iter()
+            .map(|clause| {
+                format!(
+                    "{} has no penalty (_/ /_): not complying is an immediate violation",
+                    clause
+                )
+            })
+            .collect();
+        warnings.sort();
+        warnings
+    }
+
+    /// Verifica ações usadas em cláusulas deônticas com relativizações diferentes (`--strict`)
+    ///
+    /// `{a}O(x)` e `{a,b}O(x)` geram tags (e portanto conflitos) bem diferentes, o que
+    /// costuma ser um descuido ao escrever o contrato.
+    ///
+    /// # Retorna
+    /// Um aviso por ação, com as cláusulas de cada estilo
+    pub fn lint_relativization(&self) -> Vec<String> {
+        let mut uses: BTreeMap<i32, BTreeMap<u8, BTreeSet<String>>> = BTreeMap::new();
+        for clause in &self.clauses {
+            let mut collector = RelativizationCollector::default();
+            clause.visit(&mut collector);
+            for (action, relativization_type) in collector.uses {
+                let style = match relativization_type {
+                    RelativizationType::Global => 0,
+                    RelativizationType::Relativized => 1,
+                    RelativizationType::Directed => 2,
+                };
+                uses.entry(action)
+                    .or_default()
+                    .entry(style)
+                    .or_default()
+                    .insert(clause.to_string());
+            }
+        }
+
+        let table = SymbolTable::lock();
+        uses.into_iter()
+            .filter(|(_, styles)| styles.len() > 1)
+            .map(|(action, styles)| {
+                let name = table
+                    .get_symbol_by_id(action)
+                    .map(|s| s.value.clone())
+                    .unwrap_or_else(|| action.to_string());
+                let styles: Vec<String> = styles
+                    .into_iter()
+                    .map(|(style, clauses)| {
+                        let style = ["global", "relativized", "directed"][style as usize];
+                        let clauses: Vec<String> = clauses.into_iter().collect();
+                        format!("{} in {}", style, clauses.join(", "))
+                    })
+                    .collect();
+                format!(
+                    "Action '{}' is used with mixed relativization: {}",
+                    name,
+                    styles.join("; ")
+                )
+            })
+            .collect()
     }
 
     pub fn get_all_conflicts(&self) -> Vec<Conflict> {
@@ -602,6 +1152,60 @@ impl Contract {
         Some(head)
     }
 
+    /// Hash do conteúdo do contrato, em hexadecimal
+    ///
+    /// Calculado sobre as cláusulas e os conflitos escritos com os nomes dos símbolos e
+    /// ordenados, então não depende da ordem das cláusulas nem dos ids atribuídos no
+    /// parse. Usa `fnv1a_hex` para ser estável entre execuções e versões.
+    pub fn fingerprint(&self) -> String {
+        let mut clauses: Vec<String> = self.clauses.iter().map(|c| c.to_string()).collect();
+        clauses.sort();
+
+        let mut conflicts: Vec<String> = {
+            let table = SymbolTable::lock();
+            self.get_all_conflicts()
+                .iter()
+                .map(|c| {
+                    let mut pair = [
+                        c.a.format_with_symbols(&table),
+                        c.b.format_with_symbols(&table),
+                    ];
+                    pair.sort();
+                    // Pelo nome do indivíduo, para não depender do id
+                    let kind = match c.conflict_type {
+                        ConflictType::Scoped(party) => format!(
+                            "Scoped({})",
+                            table.get_symbol_by_id(party).map_or("?", |s| s.value())
+                        ),
+                        other => format!("{:?}", other),
+                    };
+                    format!("{}:{},{}", kind, pair[0], pair[1])
+                })
+                .collect()
+        };
+        conflicts.sort();
+        conflicts.dedup();
+
+        let mut canonical = format!(
+            "{}\n#conflicts\n{}",
+            clauses.join("\n"),
+            conflicts.join("\n")
+        );
+        // Só quando declaradas, para não mudar a impressão dos contratos sem `parties`
+        if !self.parties.is_empty() {
+            let table = SymbolTable::lock();
+            let mut parties: Vec<&str> = self
+                .parties
+                .iter()
+                .map(|id| table.get_symbol_by_id(*id).map_or("?", |s| s.value()))
+                .collect();
+            parties.sort_unstable();
+            canonical.push_str(&format!("\n#parties\n{}", parties.join("\n")));
+        }
+
+        fnv1a_hex(&canonical)
+    }
+
     fn append_clause_recursive(target: &mut Clause, other: Clause) {
         if let Some(comp) = target.get_composition_mut() {
             Self::append_clause_recursive(Arc::make_mut(&mut comp.other), other);
@@ -617,6 +1221,253 @@ impl Default for Contract {
     }
 }
 
+/// Coleta indivíduos e ações de todas as subcláusulas de um contrato
+struct ContractSymbolCollector<'a> {
+    individuals: &'a mut FxHashSet<i32>,
+    actions: &'a mut FxHashSet<BasicAction>,
+}
+
+impl ClauseVisitor for ContractSymbolCollector<'_> {
+    fn visit_clause(&mut self, clause: &Clause) {
+        match clause {
+            Clause::Boolean { .. } => {}
+            Clause::Deontic {
+                sender,
+                receiver,
+                action,
+                ..
+            }
+            | Clause::Dynamic {
+                sender,
+                receiver,
+                action,
+                ..
+            } => {
+                if *sender >= 0 {
+                    self.individuals.insert(*sender);
+                }
+                if *receiver >= 0 {
+                    self.individuals.insert(*receiver);
+                }
+
+                for basic_action in action.get_basic_actions() {
+                    if !basic_action.skip && !basic_action.violation {
+                        self.actions.insert(basic_action);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Coleta os pares (ação, relativização) das cláusulas deônticas
+#[derive(Default)]
+struct RelativizationCollector {
+    uses: FxHashSet<(i32, RelativizationType)>,
+}
+
+impl ClauseVisitor for RelativizationCollector {
+    fn visit_clause(&mut self, clause: &Clause) {
+        if let Clause::Deontic {
+            relativization_type,
+            action,
+            ..
+        } = clause
+        {
+            for basic_action in action.get_basic_actions() {
+                if basic_action.value > 0 {
+                    self.uses.insert((basic_action.value, *relativization_type));
+                }
+            }
+        }
+    }
+}
+
+/// Coleta cláusulas deônticas cuja penalidade impõe o oposto sobre a mesma ação
+///
+/// Só as cláusulas no topo da penalidade (a cadeia de composição) são comparadas,
+/// e apenas quando têm as mesmas partes e relativização da cláusula reparada.
+#[derive(Default)]
+struct PenaltyContradictionCollector {
+    contradictions: Vec<(DeonticClauseType, i32, DeonticClauseType)>,
+}
+
+impl ClauseVisitor for PenaltyContradictionCollector {
+    fn visit_clause(&mut self, clause: &Clause) {
+        let Clause::Deontic {
+            sender,
+            receiver,
+            relativization_type,
+            action,
+            deontic_type,
+            penalty: Some(penalty),
+            ..
+        } = clause
+        else {
+            return;
+        };
+        let opposite = match deontic_type {
+            DeonticClauseType::Obligation => DeonticClauseType::Prohibition,
+            DeonticClauseType::Prohibition => DeonticClauseType::Obligation,
+            DeonticClauseType::Permission => return,
+        };
+        let actions: Vec<i32> = action
+            .get_basic_actions()
+            .into_iter()
+            .filter(|a| a.value > 0 && !a.negation)
+            .map(|a| a.value)
+            .collect();
+
+        let mut current = Some(penalty.as_ref());
+        while let Some(penalty_clause) = current {
+            if let Clause::Deontic {
+                sender: p_sender,
+                receiver: p_receiver,
+                relativization_type: p_relativization,
+                action: p_action,
+                deontic_type: p_type,
+                ..
+            } = penalty_clause
+            {
+                if *p_type == opposite
+                    && p_sender == sender
+                    && p_receiver == receiver
+                    && p_relativization == relativization_type
+                {
+                    for p_basic in p_action.get_basic_actions() {
+                        if !p_basic.negation && actions.contains(&p_basic.value) {
+                            self.contradictions
+                                .push((*deontic_type, p_basic.value, *p_type));
+                        }
+                    }
+                }
+            }
+            current = penalty_clause.get_composition().map(|c| c.other.as_ref());
+        }
+    }
+}
+
+// ==================== ContractBuilder ====================
+
+/// Construtor programático de contratos, sem passar pelo parser
+///
+/// Os nomes de indivíduos e ações são registrados na `SymbolTable` global.
+/// Um `sender` vazio gera uma cláusula global; um `receiver` vazio gera uma
+/// cláusula relativizada, da mesma forma que em `Clause::deontic_directed`.
+///
+/// # Exemplo
+/// ```
+/// # use recall_lib::model::contracts::ContractBuilder;
+/// let contract = ContractBuilder::new()
+///     .obligation("buyer", "pay", "seller")
+///     .prohibition("seller", "cancel", "buyer")
+///     .global_conflict("pay", "cancel")
+///     .build();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ContractBuilder {
+    contract: Contract,
+}
+
+impl ContractBuilder {
+    pub fn new() -> Self {
+        ContractBuilder {
+            contract: Contract::new(),
+        }
+    }
+
+    pub fn obligation(self, sender: &str, action: &str, receiver: &str) -> Self {
+        self.deontic(DeonticClauseType::Obligation, sender, action, receiver)
+    }
+
+    pub fn prohibition(self, sender: &str, action: &str, receiver: &str) -> Self {
+        self.deontic(DeonticClauseType::Prohibition, sender, action, receiver)
+    }
+
+    pub fn permission(self, sender: &str, action: &str, receiver: &str) -> Self {
+        self.deontic(DeonticClauseType::Permission, sender, action, receiver)
+    }
+
+    pub fn global_conflict(self, a: &str, b: &str) -> Self {
+        self.conflict(a, b, ConflictType::Global)
+    }
+
+    pub fn relativized_conflict(self, a: &str, b: &str) -> Self {
+        self.conflict(a, b, ConflictType::Relativized)
+    }
+
+    pub fn scoped_conflict(self, a: &str, b: &str, party: &str) -> Self {
+        let party = SymbolTable::lock().intern(party, SymbolType::Individual);
+        self.conflict(a, b, ConflictType::Scoped(party))
+    }
+
+    /// Adiciona uma cláusula já construída (composições, dinâmicas, penalidades)
+    pub fn clause(mut self, clause: Clause) -> Self {
+        self.contract.add_clause(clause);
+        self
+    }
+
+    pub fn build(self) -> Contract {
+        self.contract
+    }
+
+    fn deontic(
+        mut self,
+        deontic_type: DeonticClauseType,
+        sender: &str,
+        action: &str,
+        receiver: &str,
+    ) -> Self {
+        let (sender, action, receiver) = {
+            let mut table = SymbolTable::lock();
+            (
+                table.intern(sender, SymbolType::Individual),
+                table.intern(action, SymbolType::Action),
+                table.intern(receiver, SymbolType::Individual),
+            )
+        };
+
+        // Mesmo padrão do parser: O e F recebem penalidade de violação por omissão
+        let penalty = match deontic_type {
+            DeonticClauseType::Permission => None,
+            _ => Some(Clause::boolean_false()),
+        };
+
+        let clause = Clause::deontic_directed(
+            sender,
+            receiver,
+            Action::Basic(BasicAction::with_value(action)),
+            deontic_type,
+            penalty,
+        );
+        self.contract.add_clause(clause);
+        self
+    }
+
+    fn conflict(mut self, a: &str, b: &str, conflict_type: ConflictType) -> Self {
+        let (a, b) = {
+            let mut table = SymbolTable::lock();
+            (
+                table.intern(a, SymbolType::Action),
+                table.intern(b, SymbolType::Action),
+            )
+        };
+
+        let conflict = Conflict::new(
+            BasicAction::with_value(a),
+            BasicAction::with_value(b),
+            conflict_type,
+        );
+        match conflict_type {
+            ConflictType::Global => self.contract.global_conflicts.push(conflict),
+            ConflictType::Relativized | ConflictType::Scoped(_) => {
+                self.contract.relativized_conflicts.push(conflict)
+            }
+        }
+        self
+    }
+}
+
 impl fmt::Display for Contract {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         if self.clauses.is_empty() {
@@ -650,3 +1501,235 @@ impl fmt::Display for Contract {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{build, build_contract, parse, serial};
+
+    #[test]
+    fn builder_matches_parsed_contract() {
+        let _table = serial();
+        let built = ContractBuilder::new()
+            .obligation("buyer", "pay", "seller")
+            .prohibition("seller", "cancel", "buyer")
+            .permission("buyer", "inspect", "")
+            .global_conflict("pay", "cancel")
+            .build();
+        let parsed = parse(
+            "conflict { global { (pay, cancel) }; };
+             {buyer,seller}O(pay);
+             {seller,buyer}F(cancel);
+             {buyer}P(inspect);",
+        );
+
+        assert_eq!(built.clauses, parsed.clauses);
+        assert_eq!(built.global_conflicts, parsed.global_conflicts);
+        assert_eq!(built.relativized_conflicts, parsed.relativized_conflicts);
+        assert_eq!(built.individuals, parsed.individuals);
+        assert_eq!(built.actions, parsed.actions);
+    }
+
+    /// Records the kind of each visited clause
+    struct KindRecorder {
+        kinds: Vec<&'static str>,
+        nested: bool,
+    }
+
+    impl ClauseVisitor for KindRecorder {
+        fn visit_clause(&mut self, clause: &Clause) {
+            self.kinds.push(match clause {
+                Clause::Boolean { .. } => "bool",
+                Clause::Deontic { deontic_type: DeonticClauseType::Obligation, .. } => "O",
+                Clause::Deontic { deontic_type: DeonticClauseType::Permission, .. } => "P",
+                Clause::Deontic { deontic_type: DeonticClauseType::Prohibition, .. } => "F",
+                Clause::Dynamic { .. } => "[]",
+            });
+        }
+
+        fn visit_nested(&self) -> bool {
+            self.nested
+        }
+    }
+
+    #[test]
+    fn visitor_reaches_penalty_and_composition() {
+        let _table = serial();
+        let contract = parse(
+            "{buyer,seller}O(pay)_/{buyer,seller}O(fine)/_ ^ {seller,buyer}[deliver]({buyer,seller}F(cancel));",
+        );
+        assert_eq!(contract.clauses.len(), 1);
+        let clause = contract.clauses.iter().next().unwrap();
+
+        let mut all = KindRecorder { kinds: Vec::new(), nested: true };
+        clause.visit(&mut all);
+        // The parser closes nested clauses with a `true` composition
+        assert_eq!(all.kinds, ["O", "O", "bool", "[]", "F", "bool"]);
+
+        let mut chain = KindRecorder { kinds: Vec::new(), nested: false };
+        clause.visit(&mut chain);
+        assert_eq!(chain.kinds, ["O", "[]"]);
+    }
+
+    #[test]
+    fn mixed_relativization_names_the_action_and_its_clauses() {
+        let _table = serial();
+        let contract = parse("{buyer}O(pay);\n{buyer,seller}F(pay);\n{seller,buyer}O(deliver);");
+        let warnings = contract.lint_relativization();
+        assert_eq!(warnings.len(), 1, "{:?}", warnings);
+        assert_eq!(
+            warnings[0],
+            "Action 'pay' is used with mixed relativization: relativized in {buyer}OBLIGATION(pay)_/F/_; \
+             directed in {buyer,seller}PROHIBITION(pay)_/F/_"
+        );
+
+        assert!(parse("{buyer,seller}O(pay);\n{buyer,seller}F(pay);").lint_relativization().is_empty());
+    }
+
+    #[test]
+    fn penalty_that_contradicts_its_clause_is_reported() {
+        let _table = serial();
+        let contract = parse("{buyer,seller}O(pay)_/{buyer,seller}F(pay)/_;\n{seller,buyer}O(ship);");
+        let warnings = contract.lint_penalties();
+        assert_eq!(warnings.len(), 1, "{:?}", warnings);
+        assert!(warnings[0].contains("O(pay) has penalty F(pay)"), "{}", warnings[0]);
+        assert!(warnings[0].starts_with("Clause {buyer,seller}OBLIGATION(pay)"), "{}", warnings[0]);
+
+        // Reported even though the penalty is never reached
+        let mut contract = contract;
+        contract.validate();
+        assert!(contract.warnings.iter().any(|w| w == &warnings[0]));
+
+        assert!(parse("{buyer,seller}O(pay)_/{buyer,seller}O(fine)/_;").lint_penalties().is_empty());
+        assert!(parse("{buyer,seller}O(pay)_/{seller,buyer}F(pay)/_;").lint_penalties().is_empty());
+    }
+
+    #[test]
+    fn obligation_without_a_penalty_is_reported_only_when_linted() {
+        let _table = serial();
+        let mut contract = parse(
+            "{buyer,seller}O(pay);\n{buyer,seller}[pay]({seller,buyer}F(cancel));\n\
+             {seller,buyer}O(ship)_/{seller,buyer}O(refund)/_;",
+        );
+        assert_eq!(
+            contract.lint_unpenalized(),
+            [
+                "{buyer,seller}OBLIGATION(pay) has no penalty (_/ /_): not complying is an immediate violation",
+                "{seller,buyer}OBLIGATION(refund) has no penalty (_/ /_): not complying is an immediate violation",
+                "{seller,buyer}PROHIBITION(cancel) has no penalty (_/ /_): not complying is an immediate violation",
+            ]
+        );
+
+        // Not a plain warning: `--deny-warnings` only fails on it under `--strict`
+        contract.validate();
+        assert!(contract.warnings.is_empty(), "{:?}", contract.warnings);
+    }
+
+    #[test]
+    fn clauses_in_another_order_share_one_state() {
+        let _table = serial();
+        let src = "{buyer,seller}[ship]({buyer,seller}O(pay) ^ {buyer,seller}O(sign));
+                   {buyer,seller}[bill]({buyer,seller}O(sign) ^ {buyer,seller}O(pay));";
+        let automaton = build(src);
+        let initial = automaton.initial.as_ref().unwrap().id;
+
+        // `ship`, `bill` and both at once lead to the same obligations, in any order
+        let targets: FxHashSet<usize> = automaton
+            .successors(initial)
+            .into_iter()
+            .map(|(_, to)| to)
+            .filter(|&to| {
+                let clause = automaton.get_state_by_id(to).unwrap().clause.as_ref().map(|c| c.to_string());
+                clause.is_some_and(|c| c.contains("OBLIGATION(pay)") && c.contains("OBLIGATION(sign)"))
+            })
+            .collect();
+        assert_eq!(targets.len(), 1);
+
+        let canonical: FxHashSet<Clause> = automaton
+            .states
+            .iter()
+            .filter_map(|s| s.clause.as_ref().map(Clause::canonicalize))
+            .collect();
+        assert_eq!(canonical.len(), automaton.states.iter().filter(|s| s.clause.is_some()).count());
+    }
+
+    #[test]
+    fn lint_conflicts_reports_duplicate_and_subsumed_pairs() {
+        let _table = serial();
+        let contract = parse(
+            "conflict { global { (pay, cancel), (cancel, pay) }; relativized { (pay, cancel) }; };
+             {buyer,seller}O(pay);",
+        );
+
+        let warnings = contract.lint_conflicts();
+        assert_eq!(warnings.len(), 2, "{:?}", warnings);
+        assert!(warnings[0].starts_with("Duplicate conflict declaration"));
+        assert!(warnings[1].contains("is subsumed by the global conflict"));
+    }
+
+    #[test]
+    fn fingerprint_ignores_order_but_not_meaning() {
+        let _table = serial();
+        let original = parse(
+            "conflict { global { (pay, cancel) }; };
+             {buyer,seller}O(pay); {seller,buyer}F(cancel);",
+        )
+        .fingerprint();
+        let reordered = parse(
+            "conflict { global { (cancel, pay) }; };
+             {seller,buyer}F(cancel); {buyer,seller}O(pay);",
+        )
+        .fingerprint();
+        let changed = parse(
+            "conflict { global { (pay, cancel) }; };
+             {buyer,seller}O(pay); {seller,buyer}P(cancel);",
+        )
+        .fingerprint();
+
+        assert_eq!(original.len(), 16);
+        assert_eq!(original, reordered);
+        assert_ne!(original, changed);
+    }
+
+    #[test]
+    fn duplicate_clause_is_not_extracted_again() {
+        let _table = serial();
+        let parsed = parse("{buyer,seller}O(pay)_/{seller,buyer}F(cancel)/_;");
+        let clause = parsed.clauses.iter().next().unwrap().clone();
+
+        let mut contract = Contract::default();
+        assert!(contract.add_clause(clause.clone()));
+        assert_eq!(contract.individuals.len(), 2);
+        assert_eq!(contract.actions.len(), 2);
+
+        // Emptied so that a second extraction would show up
+        contract.individuals.clear();
+        contract.actions.clear();
+        assert!(!contract.add_clause(clause));
+        assert_eq!(contract.clauses.len(), 1);
+        assert!(contract.individuals.is_empty());
+        assert!(contract.actions.is_empty());
+    }
+
+    #[test]
+    fn merging_two_conflict_free_contracts_can_conflict() {
+        let _table = serial();
+        let delivery = "conflict { global { (deliver, cancel) }; };\n{seller,buyer}O(deliver);";
+        let ban = "{seller,buyer}F(deliver);";
+        assert!(!build(delivery).conflict_found);
+        assert!(!build(ban).conflict_found);
+
+        // Parsed with the same table, so `deliver` has one id in both
+        let (first, second) = (parse(delivery), parse(ban));
+        let merged = first.merge(&second);
+        assert_eq!(merged.clauses.len(), 2);
+        assert_eq!(merged.get_all_conflicts().len(), 1);
+        assert_eq!(merged.individuals, first.individuals);
+        assert!(build_contract(merged, &[]).conflict_found);
+
+        // Merging a contract with itself changes nothing
+        let same = first.merge(&first);
+        assert_eq!(same.clauses, first.clauses);
+        assert_eq!(same.get_all_conflicts().len(), 1);
+    }
+}
@@ -1,8 +1,9 @@
 use crate::model::actions::*;
 use crate::model::contracts::*;
-use crate::parser::Rule;
+use crate::parser::{RCLParser, Rule};
 use crate::utils::*;
 use pest::iterators::{Pair, Pairs};
+use pest::Parser;
 use std::fmt;
 
 // ==================== Error Types ====================
@@ -34,9 +35,245 @@ impl std::error::Error for AstError {}
 
 type Result<T> = std::result::Result<T, AstError>;
 
+// ==================== Commented Clauses ====================
+
+/// Maior aninhamento de parênteses de `input`, fora de comentários e textos
+///
+/// Lido direto do texto, antes do pest, que também recursa a cada parêntese.
+pub fn parenthesis_depth(input: &str) -> usize {
+    let (mut depth, mut max) = (0usize, 0);
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '(' => {
+                depth += 1;
+                max = max.max(depth);
+            }
+            ')' => depth = depth.saturating_sub(1),
+            '"' => {
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => {
+                            chars.next();
+                        }
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                chars.by_ref().find(|&c| c == '\n');
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                for c in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            _ => {}
+        }
+    }
+    max
+}
+
+/// Maior aninhamento de ações (`alpha`/`beta`) na árvore de `pair`
+///
+/// Percorrida com pilha explícita, antes de `build_ast`, cuja construção das ações
+/// é recursiva. Nunca passa de `Action::depth` da ação construída.
+pub fn action_nesting(pair: Pair<Rule>) -> usize {
+    let mut max = 0;
+    let mut stack = vec![(pair, 0)];
+    while let Some((pair, level)) = stack.pop() {
+        let level = match pair.as_rule() {
+            Rule::alpha | Rule::beta => level + 1,
+            _ => level,
+        };
+        max = max.max(level);
+        stack.extend(pair.into_inner().map(|inner| (inner, level)));
+    }
+    max
+}
+
+/// Retorna o texto das cláusulas desativadas por comentários
+///
+/// Cada comentário cujo conteúdo é uma (ou mais) cláusula(s) válida(s) conta como
+/// cláusula desativada. As cláusulas não são construídas, então a `SymbolTable`
+/// não é alterada.
+pub fn extract_commented_clauses(input: &str) -> Vec<String> {
+    let mut disabled = Vec::new();
+
+    let scan = match RCLParser::parse(Rule::comment_scan, input) {
+        Ok(mut pairs) => pairs.next(),
+        Err(_) => None,
+    };
+
+    let Some(scan) = scan else {
+        return disabled;
+    };
+
+    for comment in scan.into_inner() {
+        if comment.as_rule() != Rule::comment_text {
+            continue;
+        }
+
+        let text = comment.as_str();
+        let body = if let Some(block) = text.strip_prefix("/*") {
+            block.strip_suffix("*/").unwrap_or(block)
+        } else {
+            text.trim_start_matches('/')
+        };
+
+        if let Ok(mut pairs) = RCLParser::parse(Rule::commented_clauses, body) {
+            if let Some(clauses) = pairs.next() {
+                for clause in clauses.into_inner() {
+                    if clause.as_rule() == Rule::clause {
+                        disabled.push(clause.as_str().trim().to_string());
+                    }
+                }
+            }
+        }
+    }
+
+    disabled
+}
+
+// ==================== Syntax Diagnostics ====================
+
+/// Reúne todos os erros de sintaxe do contrato, em vez de só o primeiro
+///
+/// O pest para no primeiro erro. Aqui o texto é dividido nos `;` de nível superior
+/// (fora de chaves e comentários) e cada trecho é verificado isoladamente, como
+/// bloco de conflitos ou como cláusula. As posições são convertidas para linha e
+/// coluna no texto completo. Nada é construído, então a `SymbolTable` não muda.
+///
+/// # Argumentos
+/// * `input` - Texto completo do contrato
+///
+/// # Retorna
+/// Um `AstError::ParseError` por trecho inválido, na ordem do texto; vazio se todos
+/// os trechos forem válidos
+pub fn collect_syntax_errors(input: &str) -> Vec<AstError> {
+    let mut errors = Vec::new();
+    let segments = split_segments(input);
+    let last = segments.len().saturating_sub(1);
+
+    for (index, (start, text)) in segments.into_iter().enumerate() {
+        let (line, column) = line_col(input, start);
+        match RCLParser::parse(Rule::segment_check, text) {
+            Ok(mut pairs) => {
+                // Texto depois do último `;` que não é só espaço ou comentário
+                // (o `EOI` também aparece entre os filhos do trecho)
+                let is_blank = pairs
+                    .next()
+                    .is_none_or(|pair| pair.into_inner().all(|p| p.as_rule() == Rule::EOI));
+                if index == last && !is_blank {
+                    let (end_line, end_column) = line_col(input, start + text.len());
+                    errors.push(AstError::ParseError(format!(
+                        "line {}, column {}: expected ';' after the last clause",
+                        end_line, end_column
+                    )));
+                }
+            }
+            Err(err) => {
+                let (err_line, err_column) = match err.line_col {
+                    pest::error::LineColLocation::Pos(pos) => pos,
+                    pest::error::LineColLocation::Span(pos, _) => pos,
+                };
+                let abs_line = line + err_line - 1;
+                let abs_column = if err_line == 1 {
+                    column + err_column - 1
+                } else {
+                    err_column
+                };
+                errors.push(AstError::ParseError(format!(
+                    "line {}, column {}: {}",
+                    abs_line,
+                    abs_column,
+                    err.variant.message()
+                )));
+            }
+        }
+    }
+
+    errors
+}
+
+/// Divide o texto nos `;` fora de chaves, strings e comentários
+///
+/// # Retorna
+/// Pares (posição inicial em bytes, texto do trecho sem o `;`)
+fn split_segments(input: &str) -> Vec<(usize, &str)> {
+    let bytes = input.as_bytes();
+    let mut segments = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+                continue;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i += 2;
+                while i < bytes.len() && !(bytes[i] == b'*' && bytes.get(i + 1) == Some(&b'/')) {
+                    i += 1;
+                }
+                i += 2;
+                continue;
+            }
+            b'"' => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != b'"' {
+                    if bytes[i] == b'\\' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+            }
+            b'{' => depth += 1,
+            b'}' => depth = depth.saturating_sub(1),
+            b';' if depth == 0 => {
+                segments.push((start, &input[start..i]));
+                start = i + 1;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    segments.push((start, &input[start.min(input.len())..]));
+
+    segments
+}
+
+/// Linha e coluna (a partir de 1) de uma posição em bytes
+fn line_col(input: &str, offset: usize) -> (usize, usize) {
+    let before = &input[..offset.min(input.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rfind('\n').map_or(before.chars().count(), |nl| {
+        before[nl + 1..].chars().count()
+    }) + 1;
+    (line, column)
+}
+
 // ==================== AST Builder ====================
 
 pub fn build_ast(pair: Pair<Rule>) -> Result<Contract> {
+    build_ast_with(pair, false)
+}
+
+/// Constrói o contrato, opcionalmente incluindo as cláusulas marcadas com `@disabled`
+///
+/// Sem `include_disabled`, essas cláusulas não são construídas (a `SymbolTable` não
+/// muda) e seu texto vai para `Contract::disabled_clauses`.
+pub fn build_ast_with(pair: Pair<Rule>, include_disabled: bool) -> Result<Contract> {
     if pair.as_rule() != Rule::main {
         return Err(AstError::UnexpectedRule {
             expected: Rule::main,
@@ -47,16 +284,36 @@ pub fn build_ast(pair: Pair<Rule>) -> Result<Contract> {
         .into_inner()
         .next()
         .ok_or_else(|| AstError::BuildError("Empty contract file.".to_string()))?;
-    build_contract(inner_pair)
+    build_contract(inner_pair, include_disabled)
 }
 
-fn build_contract(pair: Pair<Rule>) -> Result<Contract> {
+fn build_contract(pair: Pair<Rule>, include_disabled: bool) -> Result<Contract> {
     let mut contract = Contract::new();
-    let symbol_table = SymbolTable::instance();
-    let mut table = symbol_table.lock().unwrap();
+    let mut table = SymbolTable::lock();
 
     for inner_pair in pair.into_inner() {
         match inner_pair.as_rule() {
+            Rule::header => {
+                for entry in inner_pair.into_inner() {
+                    if entry.as_rule() == Rule::parties {
+                        for party in entry.into_inner().filter(|p| p.as_rule() == Rule::ID) {
+                            let id = table.intern(party.as_str(), SymbolType::Individual);
+                            contract.add_party(id);
+                        }
+                        continue;
+                    }
+                    let rule = entry.as_rule();
+                    let text = entry
+                        .into_inner()
+                        .find(|p| p.as_rule() == Rule::STRING)
+                        .map(|p| unquote(p.as_str()));
+                    match rule {
+                        Rule::title => contract.title = text,
+                        Rule::description => contract.description = text,
+                        _ => {}
+                    }
+                }
+            }
             Rule::conflict => {
                 for conflict_part in inner_pair.into_inner() {
                     if conflict_part.as_rule() == Rule::conflict_body {
@@ -65,24 +322,28 @@ fn build_contract(pair: Pair<Rule>) -> Result<Contract> {
                                 Rule::cfGlobal_block => {
                                     for p in body_part.into_inner() {
                                         if p.as_rule() == Rule::cfPair {
-                                            let (act1, act2) = build_cf_pair(p, &mut table)?;
-                                            contract.global_conflicts.push(Conflict::new(
+                                            let (act1, act2, scope) = build_cf_pair(p, &mut table)?;
+                                            push_conflict(
+                                                &mut contract,
                                                 act1,
                                                 act2,
+                                                scope,
                                                 ConflictType::Global,
-                                            ));
+                                            );
                                         }
                                     }
                                 }
                                 Rule::cfRel_block => {
                                     for p in body_part.into_inner() {
                                         if p.as_rule() == Rule::cfPair {
-                                            let (act1, act2) = build_cf_pair(p, &mut table)?;
-                                            contract.relativized_conflicts.push(Conflict::new(
+                                            let (act1, act2, scope) = build_cf_pair(p, &mut table)?;
+                                            push_conflict(
+                                                &mut contract,
                                                 act1,
                                                 act2,
+                                                scope,
                                                 ConflictType::Relativized,
-                                            ));
+                                            );
                                         }
                                     }
                                 }
@@ -93,9 +354,27 @@ fn build_contract(pair: Pair<Rule>) -> Result<Contract> {
                 }
             }
             Rule::clause => {
-                let clause = build_clause(inner_pair, &mut table)?;
+                let clause = build_clause(inner_pair.clone(), &mut table)?;
+                collect_unpenalized(inner_pair, &mut table, &mut contract.unpenalized)?;
                 contract.add_clause(clause);
             }
+            Rule::disabled_clause => {
+                let clause_pair = inner_pair
+                    .into_inner()
+                    .find(|p| p.as_rule() == Rule::clause)
+                    .ok_or_else(|| {
+                        AstError::BuildError("Expected clause after @disabled".to_string())
+                    })?;
+                if include_disabled {
+                    let clause = build_clause(clause_pair.clone(), &mut table)?;
+                    collect_unpenalized(clause_pair, &mut table, &mut contract.unpenalized)?;
+                    contract.add_clause(clause);
+                } else {
+                    contract
+                        .disabled_clauses
+                        .push(clause_pair.as_str().trim().to_string());
+                }
+            }
             Rule::EOI | Rule::END => {}
             _ => {
                 return Err(AstError::BuildError(format!(
@@ -106,10 +385,99 @@ fn build_contract(pair: Pair<Rule>) -> Result<Contract> {
         }
     }
 
+    // Os avisos formatam símbolos, então a tabela precisa estar livre
+    drop(table);
+    contract.validate();
+
     Ok(contract)
 }
 
-fn build_cf_pair(pair: Pair<Rule>, table: &mut SymbolTable) -> Result<(BasicAction, BasicAction)> {
+/// Obrigações e proibições de `pair` (inclusive dentro de penalidades e dinâmicas)
+/// cuja regra `penalty` ficou vazia, para o aviso de `Contract::lint_unpenalized`
+///
+/// Chamada depois de `build_clause`, então não cria símbolos novos. As cláusulas são
+/// guardadas sem a penalidade `F` implícita.
+fn collect_unpenalized(
+    pair: Pair<Rule>,
+    table: &mut SymbolTable,
+    out: &mut Vec<Clause>,
+) -> Result<()> {
+    let rule = pair.as_rule();
+    if matches!(rule, Rule::co_atom | Rule::cf_atom) {
+        let missing = pair
+            .clone()
+            .into_inner()
+            .find(|p| p.as_rule() == Rule::penalty)
+            .is_some_and(|p| p.into_inner().next().is_none());
+        if missing {
+            let mut clause = if rule == Rule::co_atom {
+                build_co_atom(pair.clone(), table)?
+            } else {
+                build_cf_atom(pair.clone(), table)?
+            };
+            if let Clause::Deontic { penalty, .. } = &mut clause {
+                *penalty = None;
+            }
+            if !out.contains(&clause) {
+                out.push(clause);
+            }
+        }
+    }
+    for inner in pair.into_inner() {
+        collect_unpenalized(inner, table, out)?;
+    }
+    Ok(())
+}
+
+/// Conteúdo de um literal `STRING`, sem as aspas e com `\"` e `\\` resolvidos
+fn unquote(literal: &str) -> String {
+    let inner = &literal[1..literal.len() - 1];
+    let mut text = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            if let Some(escaped) = chars.next() {
+                text.push(escaped);
+            }
+        } else {
+            text.push(c);
+        }
+    }
+    text
+}
+
+/// Guarda um conflito do bloco de conflitos
+///
+/// Um par com escopo `(a,b){X}` vira `ConflictType::Scoped(X)` e vai para
+/// `relativized_conflicts`, seja qual for o bloco em que foi declarado.
+fn push_conflict(
+    contract: &mut Contract,
+    a: BasicAction,
+    b: BasicAction,
+    scope: Option<i32>,
+    block_type: ConflictType,
+) {
+    match (scope, block_type) {
+        (Some(party), _) => {
+            contract
+                .relativized_conflicts
+                .push(Conflict::new(a, b, ConflictType::Scoped(party)))
+        }
+        (None, ConflictType::Global) => {
+            contract
+                .global_conflicts
+                .push(Conflict::new(a, b, ConflictType::Global))
+        }
+        (None, _) => contract
+            .relativized_conflicts
+            .push(Conflict::new(a, b, block_type)),
+    }
+}
+
+fn build_cf_pair(
+    pair: Pair<Rule>,
+    table: &mut SymbolTable,
+) -> Result<(BasicAction, BasicAction, Option<i32>)> {
     let mut pairs = pair.into_inner();
     let id1 = pairs
         .next()
@@ -125,9 +493,15 @@ fn build_cf_pair(pair: Pair<Rule>, table: &mut SymbolTable) -> Result<(BasicActi
     let act1_id = table.add_symbol(id1, SymbolType::Action);
     let act2_id = table.add_symbol(id2, SymbolType::Action);
 
+    let scope = pairs
+        .find(|p| p.as_rule() == Rule::cfScope)
+        .and_then(|scope| scope.into_inner().next())
+        .map(|party| table.add_symbol(party.as_str().to_string(), SymbolType::Individual));
+
     Ok((
         BasicAction::with_value(act1_id),
         BasicAction::with_value(act2_id),
+        scope,
     ))
 }
 
@@ -450,6 +824,11 @@ fn build_cf_atom(pair: Pair<Rule>, table: &mut SymbolTable) -> Result<Clause> {
     })
 }
 
+/// Constrói a penalidade `_/clause/_` de uma obrigação ou proibição
+///
+/// A penalidade é uma cláusula completa, então pode ter sua própria penalidade
+/// (cadeia de compensação) em qualquer profundidade. Sem penalidade explícita, o
+/// resultado é `F`.
 fn build_penalty(pair: Pair<Rule>, table: &mut SymbolTable) -> Result<Clause> {
     let mut inner = pair.into_inner();
 
@@ -585,11 +964,20 @@ fn build_alpha(pair: Pair<Rule>, table: &mut SymbolTable) -> Result<Action> {
 }
 
 fn build_alpha_atom(pair: Pair<Rule>, table: &mut SymbolTable) -> Result<Action> {
-    let inner = pair
-        .into_inner()
+    let mut pairs = pair.into_inner();
+    let inner = pairs
         .next()
         .ok_or_else(|| AstError::BuildError("Empty alpha atom.".to_string()))?;
 
+    let action = build_alpha_base(inner, table)?;
+    // `O(a*)`: a semântica fica a cargo do ClauseDecomposer
+    match pairs.next() {
+        Some(op) if op.as_rule() == Rule::UN_OP_IT => Ok(Action::star(action)),
+        _ => Ok(action),
+    }
+}
+
+fn build_alpha_base(inner: Pair<Rule>, table: &mut SymbolTable) -> Result<Action> {
     match inner.as_rule() {
         Rule::SKIP => Ok(Action::Basic(BasicAction::skip())),
         Rule::VIOLATION => Ok(Action::Basic(BasicAction::violation())),
@@ -605,6 +993,7 @@ fn build_alpha_atom(pair: Pair<Rule>, table: &mut SymbolTable) -> Result<Action>
             })?;
             build_alpha(alpha_pair, table)
         }
+        Rule::alpha => build_alpha(inner, table),
         _ => Err(AstError::BuildError(format!(
             "Unexpected alpha atom: {:?}",
             inner.as_rule()
@@ -620,6 +1009,7 @@ fn build_beta_term(pair: Pair<Rule>, table: &mut SymbolTable) -> Result<Action>
     let mut action: Option<Action> = None;
     let mut negation = false;
     let mut iteration = false;
+    let mut bound: Option<u32> = None;
 
     for inner_pair in pair.into_inner() {
         match inner_pair.as_rule() {
@@ -629,6 +1019,19 @@ fn build_beta_term(pair: Pair<Rule>, table: &mut SymbolTable) -> Result<Action>
             Rule::UN_OP_IT => {
                 iteration = true;
             }
+            Rule::UN_OP_BOUND => {
+                let digits = &inner_pair.as_str()[1..];
+                match digits.parse::<u32>() {
+                    Ok(n) if n > 0 => bound = Some(n),
+                    _ => {
+                        return Err(AstError::BuildError(format!(
+                            "Invalid iteration bound '{}': expected a number from 1 to {}",
+                            digits,
+                            u32::MAX
+                        )))
+                    }
+                }
+            }
             Rule::ID => {
                 let id = table.add_symbol(inner_pair.as_str().to_string(), SymbolType::Action);
                 let basic_action = Action::Basic(BasicAction::with_value(id));
@@ -675,13 +1078,103 @@ fn build_beta_term(pair: Pair<Rule>, table: &mut SymbolTable) -> Result<Action>
         AstError::BuildError("Could not build action body for beta_term".to_string())
     })?;
 
+    // `!(a)*` é a iteração de `!a`, não a negação de `a*`: a negação vem antes, para que
+    // a ação corresponda à sua forma exibida e se decomponha em ações básicas negadas
+    if negation {
+        final_action = Action::negation(final_action);
+    }
+
     if iteration {
         final_action = Action::star(final_action);
     }
 
-    if negation {
-        final_action = Action::negation(final_action);
+    if let Some(bound) = bound {
+        final_action = Action::bounded_star(final_action, bound);
     }
 
     Ok(final_action)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{config, parse, serial, write_contract};
+
+    #[test]
+    fn stats_count_active_and_commented_clauses() {
+        let _table = serial();
+        let src = "// The buyer pays and the seller delivers
+                   {buyer,seller}O(pay);
+                   // {seller,buyer}F(cancel);
+                   {seller,buyer}O(deliver);";
+
+        let mut contract = parse(src);
+        contract.disabled_clauses.extend(extract_commented_clauses(src));
+
+        assert_eq!(contract.clauses.len(), 2);
+        assert_eq!(contract.disabled_clauses, vec!["{seller,buyer}F(cancel)"]);
+        let stats = crate::utils::print_stats(&contract);
+        assert!(stats.contains("Clauses: 2\n"));
+        assert!(stats.contains("Disabled clauses: 1\n"));
+    }
+
+    #[test]
+    fn disabled_conflicting_clause_is_left_out_unless_included() {
+        let _table = serial();
+        let path = write_contract("disabled.rcl", "{seller,buyer}O(deliver);\n@disabled {seller,buyer}F(deliver);");
+
+        let without = crate::analyze(&config(&path, &[])).unwrap();
+        assert!(!without.conflict_found);
+        let contract = crate::load_contract(&config(&path, &[])).unwrap();
+        assert_eq!(contract.disabled_clauses, vec!["{seller,buyer}F(deliver)"]);
+
+        let with = crate::analyze(&config(&path, &["--include-disabled"])).unwrap();
+        assert!(with.conflict_found);
+    }
+
+    #[test]
+    fn every_malformed_clause_is_reported() {
+        let src = "{buyer,seller}O(pay);\n{buyer,seller}X(ship);\n{seller,buyer}F(cancel);\n{seller,buyer}O(deliver;";
+        let errors: Vec<String> = collect_syntax_errors(src).iter().map(|e| e.to_string()).collect();
+        assert_eq!(errors.len(), 2, "{:?}", errors);
+        assert!(errors[0].contains("line 2, column"), "{}", errors[0]);
+        assert!(errors[1].contains("line 4, column"), "{}", errors[1]);
+
+        let errors = collect_syntax_errors("{buyer,seller}O(pay);\n{seller,buyer}O(deliver)");
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].to_string().contains("expected ';' after the last clause"));
+
+        assert!(collect_syntax_errors("{buyer,seller}O(pay);\n// {seller,buyer}F(cancel);\n").is_empty());
+    }
+
+    #[test]
+    fn loading_a_contract_reports_every_syntax_error() {
+        let _table = serial();
+        let path = write_contract("broken.rcl", "{buyer,seller}O(pay;\n{seller,buyer}O(deliver);\n{seller,buyer}(ship);");
+        let message = crate::load_contract(&config(&path, &[])).unwrap_err().to_string();
+        assert!(message.contains("line 1, column"), "{}", message);
+        assert!(message.contains("line 3, column"), "{}", message);
+        assert!(!message.contains("after the last clause"), "{}", message);
+    }
+
+    #[test]
+    fn title_and_description_are_read_from_the_header() {
+        let _table = serial();
+        let contract = parse(
+            "title \"Sale of \\\"goods\\\"\";\ndescription \"Buyer pays, seller delivers\";\n{buyer,seller}O(pay);",
+        );
+        assert_eq!(contract.title.as_deref(), Some("Sale of \"goods\""));
+        assert_eq!(contract.description.as_deref(), Some("Buyer pays, seller delivers"));
+        assert_eq!(contract.clauses.len(), 1);
+
+        let untitled = parse("{buyer,seller}O(pay);");
+        assert_eq!((untitled.title, untitled.description), (None, None));
+    }
+
+    #[test]
+    fn parenthesis_depth_skips_comments_and_strings() {
+        let src = "title \"(((\\\"(\";\n// ((((\n/* (((( */\n{a,b}[x]({b,a}O(pay&(ship)));";
+        assert_eq!(parenthesis_depth(src), 3);
+        assert_eq!(parenthesis_depth("{a,b}O(pay);"), 1);
+    }
+}
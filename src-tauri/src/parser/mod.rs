@@ -3,4 +3,7 @@ mod parser;
 
 pub use parser::{RCLParser, Rule};
 
-pub use ast_builder::build_ast;
+pub use ast_builder::{
+    action_nesting, build_ast, build_ast_with, collect_syntax_errors, extract_commented_clauses,
+    parenthesis_depth,
+};
@@ -0,0 +1,111 @@
+//! Helpers shared by the unit tests.
+//!
+//! The symbol table and the state/transition id counters are process-wide, and the
+//! tests of one binary run in parallel threads. Tests that parse contracts or format
+//! symbols take `serial()` first, so renumbering (`--sort-symbols`) or renaming
+//! (`--anonymize`) in one test can't change the names seen by another. State and
+//! transition ids keep growing across tests; compare them relative to
+//! `automaton.initial`, never as absolute numbers.
+
+use crate::algorithms::automata_constructor::AutomataConstructor;
+use crate::utils::EventSink;
+use crate::{parser, Automaton, Contract, Logger, RunConfiguration};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+static SERIAL: Mutex<()> = Mutex::new(());
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Serializes the tests that use the global symbol table.
+pub fn serial() -> MutexGuard<'static, ()> {
+    SERIAL.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Parses and builds a contract the way `load_contract` does.
+pub fn parse(src: &str) -> Contract {
+    use pest::Parser;
+    let main_pair = parser::RCLParser::parse(parser::Rule::main, src)
+        .expect("contract should parse")
+        .next()
+        .expect("main rule");
+    parser::build_ast(main_pair).expect("contract should build")
+}
+
+/// Fresh empty directory under the system temp dir.
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "recall_test_{}_{}_{}",
+        std::process::id(),
+        TEMP_COUNTER.fetch_add(1, Ordering::SeqCst),
+        name
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("temp dir");
+    dir
+}
+
+/// Writes `src` to `<fresh temp dir>/<name>` and returns its path.
+pub fn write_contract(name: &str, src: &str) -> String {
+    let path = temp_dir(name.trim_end_matches(".rcl")).join(name);
+    std::fs::write(&path, src).expect("contract file");
+    path.to_string_lossy().into_owned()
+}
+
+/// Configuration for `path` as `parse_command_line` builds it, with `args` appended.
+/// Quiet (`--csv-only`), so the logger doesn't print to stdout.
+pub fn config(path: &str, args: &[&str]) -> RunConfiguration {
+    let mut command = vec![path.to_string()];
+    command.extend(args.iter().map(|a| a.to_string()));
+    let mut config = crate::parse_command_line(&command);
+    config.set_csv_only(true);
+    config
+}
+
+/// Builds the automaton of `src` with `args` (e.g. `&["-c"]`), logging to a temp dir.
+pub fn build_with(src: &str, args: &[&str]) -> Automaton {
+    build_from(&write_contract("contract.rcl", src), parse(src), args)
+}
+
+/// Builds the automaton of an already parsed (or merged) contract with `args`.
+pub fn build_contract(contract: Contract, args: &[&str]) -> Automaton {
+    build_from(&write_contract("contract.rcl", &contract.to_string()), contract, args)
+}
+
+fn build_from(path: &str, contract: Contract, args: &[&str]) -> Automaton {
+    let config = config(path, args);
+    let mut logger = Logger::new(config.clone()).expect("logger");
+    AutomataConstructor::new(config)
+        .process(contract, &mut logger)
+        .expect("automaton should build")
+}
+
+/// Builds the automaton of `src` with the default options.
+pub fn build(src: &str) -> Automaton {
+    build_with(src, &[])
+}
+
+/// Event sink that keeps every event it receives, in order.
+#[derive(Default)]
+pub struct RecordingSink {
+    events: Mutex<Vec<(String, serde_json::Value)>>,
+}
+
+impl RecordingSink {
+    /// Payloads of the events named `name` received so far.
+    pub fn payloads(&self, name: &str) -> Vec<serde_json::Value> {
+        self.events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(event, _)| event == name)
+            .map(|(_, payload)| payload.clone())
+            .collect()
+    }
+}
+
+impl EventSink for RecordingSink {
+    fn emit_event(&self, name: &str, payload: serde_json::Value) {
+        self.events.lock().unwrap().push((name.to_string(), payload));
+    }
+}
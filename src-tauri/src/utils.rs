@@ -1,14 +1,14 @@
 use super::*;
 use chrono::Local;
 use rayon::prelude::*;
-use rustc_hash::FxHashSet;
-use std::collections::HashMap;
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs::{File, OpenOptions};
 use fs2::FileExt;
 use std::io::{self, BufWriter, Write};
 use std::path::Path as LogPath;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
-use std::sync::{Arc, Mutex, OnceLock};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
 use std::time::Duration;
 use sysinfo::{Pid, ProcessesToUpdate, System};
 use serde::Serialize;
@@ -78,11 +78,19 @@ mod macos_mem {
 #[derive(Clone, Debug)]
 pub struct CompressedConcurrentActions {
     pub source_map: Arc<Vec<Arc<RelativizedAction>>>,
-    pub valid_masks: Vec<u32>,
+    pub valid_sets: Vec<ActionSet>,
 }
 
 // ==================== memory management =================
 
+/// Sinaliza que o `MemoryGuard` detectou uso acima do limite; a construção do
+/// autômato consulta este sinal e termina com `AnalysisError::MemoryExceeded`
+static MEMORY_EXCEEDED: AtomicBool = AtomicBool::new(false);
+
+/// Tempo que o guarda espera a análise terminar sozinha depois de sinalizar o
+/// excesso, antes de encerrar o processo à força
+const MEMORY_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
 pub struct MemoryGuard {
     max_usage_mb: u64,
     logger: Logger,
@@ -109,7 +117,26 @@ impl MemoryGuard {
         self
     }
 
+    /// Se o limite de memória foi excedido desde o início do monitoramento
+    pub fn limit_exceeded() -> bool {
+        MEMORY_EXCEEDED.load(Ordering::Relaxed)
+    }
+
+    /// Sinaliza o excesso de memória, como faz o monitor
+    pub fn signal_exceeded(exceeded: bool) {
+        MEMORY_EXCEEDED.store(exceeded, Ordering::Relaxed);
+    }
+
+    /// Inicia o monitor de memória em uma thread separada
+    ///
+    /// Ao exceder o limite, o monitor apenas sinaliza (`limit_exceeded`) para que a
+    /// análise termine com `AnalysisError::MemoryExceeded`; só se ela não terminar em
+    /// `MEMORY_GRACE_PERIOD` o processo é encerrado à força.
+    ///
+    /// # Retorna
+    /// O sinal que encerra o monitor quando marcado
     pub fn start_monitoring(&self) -> Arc<AtomicBool> {
+        Self::signal_exceeded(false);
         let should_terminate = Arc::new(AtomicBool::new(false));
         let clone = should_terminate.clone();
         let max_usage = self.max_usage_mb;
@@ -121,6 +148,7 @@ impl MemoryGuard {
 
         std::thread::spawn(move || {
             let mut sys = System::new_all();
+            let mut exceeded_at: Option<std::time::Instant> = None;
 
             loop {
                 if clone.load(Ordering::Relaxed) {
@@ -161,14 +189,28 @@ impl MemoryGuard {
                         max_total_shared.store(total_mb, Ordering::Relaxed);
                     }
 
-                    if total_mb > max_usage {
+                    if total_mb > max_usage && exceeded_at.is_none() {
                         let total_label = "Total Memory";
                         let elapsed_ms = start_time.elapsed().as_millis();
                         let msg = format!("CRITICAL: Memory usage exceeded! RAM: {}MB, {}: {}MB (Limit: {}MB) - Execution Time: {}ms", 
                             rss_mb, total_label, total_mb, max_usage, elapsed_ms);
 
                         logger.log(LogType::Necessary, &msg);
+                        if logger.configuration.is_test() {
+                            println!("{}", msg);
+                        }
+
+                        if let Some(ref handle) = app_handle {
+                            use tauri::Emitter;
+                            let _ = handle.emit("memory-overflow", &msg);
+                        }
+
+                        Self::signal_exceeded(true);
+                        exceeded_at = Some(std::time::Instant::now());
+                    }
 
+                    if exceeded_at.is_some_and(|at| at.elapsed() > MEMORY_GRACE_PERIOD) {
+                        let elapsed_ms = start_time.elapsed().as_millis();
                         let msg_term = "TERMINATING PROCESS TO PREVENT SYSTEM CRASH";
                         logger.log(LogType::Necessary, msg_term);
 
@@ -185,19 +227,12 @@ impl MemoryGuard {
                         summary
                             .push_str("-------------------------------------------------------\n");
 
-                        if logger.configuration.is_test() {
-                            println!("{}", msg);
-                        } else {
+                        if !logger.configuration.is_test() {
                             logger.log(LogType::Minimal, &summary);
                         }
+                        logger.flush();
 
-                        if let Some(ref handle) = app_handle {
-                            use tauri::Emitter;
-                            let _ = handle.emit("memory-overflow", &msg);
-                            std::thread::sleep(Duration::from_millis(500));
-                        }
-
-                        std::process::exit(137);
+                        std::process::exit(crate::error::AnalysisError::MemoryExceeded.exit_code());
                     }
                 }
 
@@ -265,6 +300,15 @@ impl std::fmt::Display for Symbol {
 
 static INSTANCE: OnceLock<Arc<Mutex<SymbolTable>>> = OnceLock::new();
 
+thread_local! {
+    /// Tabela isolada que substitui a global nesta thread (ver `SymbolTable::isolated`)
+    static SCOPED: std::cell::Cell<Option<&'static Mutex<SymbolTable>>> =
+        const { std::cell::Cell::new(None) };
+}
+
+/// Tabelas isoladas livres, reaproveitadas pelas próximas chamadas de `SymbolTable::isolated`
+static SPARE_TABLES: Mutex<Vec<&'static Mutex<SymbolTable>>> = Mutex::new(Vec::new());
+
 #[derive(Debug, Clone)]
 pub struct SymbolTable {
     id_base: i32,
@@ -287,6 +331,54 @@ impl SymbolTable {
             .clone()
     }
 
+    /// Bloqueia a tabela global (ou a isolada da thread, ver `isolated`), recuperando-a
+    /// se o mutex estiver envenenado
+    ///
+    /// Um pânico com a tabela bloqueada (por exemplo um limite de tamanho) não deve
+    /// fazer todos os acessos seguintes falharem: a tabela só é alterada pelo parser,
+    /// então o conteúdo continua consistente.
+    pub fn lock() -> MutexGuard<'static, SymbolTable> {
+        let table = SCOPED.with(|scoped| scoped.get()).unwrap_or_else(|| {
+            INSTANCE.get_or_init(|| Arc::new(Mutex::new(SymbolTable::new())))
+        });
+        table.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Executa `f` com uma tabela de símbolos própria, vazia e separada da global
+    ///
+    /// `f` roda num pool rayon de uma thread em que `lock` devolve a tabela isolada,
+    /// inclusive nos `par_iter` internos da análise, e os ids de estados e transições
+    /// começam do zero (`use_thread_ids`). Assim vários contratos podem ser analisados
+    /// em paralelo no mesmo processo, cada um como se estivesse num processo próprio.
+    ///
+    /// # Retorna
+    /// O resultado de `f`, ou `None` se o pool não puder ser criado
+    pub fn isolated<R: Send>(f: impl FnOnce() -> R + Send) -> Option<R> {
+        let spare = SPARE_TABLES
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .pop();
+        let table: &'static Mutex<SymbolTable> =
+            spare.unwrap_or_else(|| Box::leak(Box::new(Mutex::new(SymbolTable::new()))));
+        table.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clear();
+
+        let result = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .start_handler(move |_| {
+                SCOPED.with(|scoped| scoped.set(Some(table)));
+                crate::model::automata::use_thread_ids();
+            })
+            .build()
+            .ok()
+            .map(|pool| pool.install(f));
+
+        SPARE_TABLES
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .push(table);
+        result
+    }
+
     pub fn add_symbol(&mut self, value: String, symbol_type: SymbolType) -> i32 {
         if let Some(&id) = self.lookup.get(&(value.clone(), symbol_type)) {
             return id;
@@ -300,10 +392,37 @@ impl SymbolTable {
         id
     }
 
+    /// Como `add_symbol`, mas aceita `&str` e devolve `-1` para o nome vazio
+    ///
+    /// `-1` é o id usado no modelo para a parte ausente (remetente ou destinatário de
+    /// cláusulas relativizadas e globais).
+    pub fn intern(&mut self, name: &str, symbol_type: SymbolType) -> i32 {
+        if name.is_empty() {
+            -1
+        } else {
+            self.add_symbol(name.to_string(), symbol_type)
+        }
+    }
+
+    pub fn get_id(&self, value: &str, symbol_type: SymbolType) -> Option<i32> {
+        self.lookup.get(&(value.to_string(), symbol_type)).copied()
+    }
+
     pub fn get_symbol_by_id(&self, id: i32) -> Option<&Symbol> {
         self.dictionary.iter().find(|s| s.id == id)
     }
 
+    /// Nome de um indivíduo para exibição
+    ///
+    /// Ids negativos são a parte ausente das cláusulas globais e relativizadas e viram
+    /// `GLOBAL`, como em `Clause`; um id desconhecido vira `?`.
+    pub fn individual_name(&self, id: i32) -> &str {
+        if id < 0 {
+            return "GLOBAL";
+        }
+        self.get_symbol_by_id(id).map_or("?", |s| s.value.as_str())
+    }
+
     pub fn get_dictionary(&self) -> &[Symbol] {
         &self.dictionary
     }
@@ -322,6 +441,65 @@ impl SymbolTable {
             .collect()
     }
 
+    /// Renumera os símbolos em ordem lexicográfica de `(tipo, valor)`
+    ///
+    /// Torna os ids independentes da ordem em que os símbolos aparecem no contrato.
+    ///
+    /// # Retorna
+    /// Mapeamento de id antigo para id novo, a ser aplicado com `Contract::remap_symbols`
+    pub fn normalize(&mut self) -> FxHashMap<i32, i32> {
+        let type_order = |t: SymbolType| match t {
+            SymbolType::Action => 0,
+            SymbolType::Individual => 1,
+        };
+        self.dictionary.sort_by(|a, b| {
+            type_order(a.symbol_type)
+                .cmp(&type_order(b.symbol_type))
+                .then_with(|| a.value.cmp(&b.value))
+        });
+
+        let mut ids = FxHashMap::default();
+        self.lookup.clear();
+        for (index, symbol) in self.dictionary.iter_mut().enumerate() {
+            let new_id = index as i32 + 1;
+            ids.insert(symbol.id, new_id);
+            symbol.id = new_id;
+            self.lookup
+                .insert((symbol.value.clone(), symbol.symbol_type), new_id);
+        }
+        self.id_base = self.dictionary.len() as i32 + 1;
+
+        ids
+    }
+
+    /// Substitui os nomes dos símbolos por `a1, a2, ...` (ações) e `p1, p2, ...` (indivíduos)
+    ///
+    /// Os nomes são atribuídos em ordem de id dentro de cada tipo, então o mesmo
+    /// contrato sempre gera o mesmo renomeamento. Os ids não mudam, logo o contrato
+    /// e o autômato continuam válidos; só a formatação passa a usar os novos nomes.
+    pub fn anonymize(&mut self) {
+        let mut order: Vec<usize> = (0..self.dictionary.len()).collect();
+        order.sort_by_key(|&index| self.dictionary[index].id);
+
+        let (mut actions, mut individuals) = (0, 0);
+        self.lookup.clear();
+        for index in order {
+            let symbol = &mut self.dictionary[index];
+            symbol.value = match symbol.symbol_type {
+                SymbolType::Action => {
+                    actions += 1;
+                    format!("a{}", actions)
+                }
+                SymbolType::Individual => {
+                    individuals += 1;
+                    format!("p{}", individuals)
+                }
+            };
+            self.lookup
+                .insert((symbol.value.clone(), symbol.symbol_type), symbol.id);
+        }
+    }
+
     pub fn clear(&mut self) {
         self.id_base = 1;
         self.dictionary.clear();
@@ -366,6 +544,47 @@ pub enum LogLevel {
     Verbose,
 }
 
+// ==================== global_log_mode.rs ====================
+/// Como o log global (compartilhado entre execuções) é aberto a cada análise
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlobalLogMode {
+    /// Acrescenta ao arquivo existente (padrão)
+    Append,
+    /// Descarta o conteúdo anterior, mantendo só a execução atual
+    Truncate,
+    /// Renomeia o arquivo para `<log>.1` quando passa do limite em MB e começa outro
+    Rotate(u64),
+    /// Um arquivo por execução, com data e hora no nome
+    Timestamp,
+}
+
+impl std::fmt::Display for GlobalLogMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            GlobalLogMode::Append => write!(f, "append"),
+            GlobalLogMode::Truncate => write!(f, "truncate"),
+            GlobalLogMode::Timestamp => write!(f, "timestamp"),
+            GlobalLogMode::Rotate(max_mb) => write!(f, "rotate:{}", max_mb),
+        }
+    }
+}
+
+impl GlobalLogMode {
+    /// Interpreta `append`, `truncate`, `timestamp` ou `rotate:<MB>`
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "append" => Some(GlobalLogMode::Append),
+            "truncate" => Some(GlobalLogMode::Truncate),
+            "timestamp" => Some(GlobalLogMode::Timestamp),
+            other => other
+                .strip_prefix("rotate:")
+                .and_then(|mb| mb.parse::<u64>().ok())
+                .filter(|mb| *mb > 0)
+                .map(GlobalLogMode::Rotate),
+        }
+    }
+}
+
 // ==================== log_type.rs ====================
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub enum LogType {
@@ -374,9 +593,124 @@ pub enum LogType {
     Additional,
 }
 
+impl LogType {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "minimal" => Some(LogType::Minimal),
+            "necessary" => Some(LogType::Necessary),
+            "additional" => Some(LogType::Additional),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            LogType::Minimal => "minimal",
+            LogType::Necessary => "necessary",
+            LogType::Additional => "additional",
+        }
+    }
+}
+
+/// Tipos de log enviados à interface como `log-message` por padrão (`--emit-logs`)
+pub const DEFAULT_EMITTED_LOG_TYPES: [LogType; 2] = [LogType::Minimal, LogType::Necessary];
+
+// ==================== export_format.rs ====================
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExportFormat {
+    Dot,
+    Json,
+    Mermaid,
+    GraphMl,
+    Text,
+    Smv,
+    Html,
+}
+
+impl ExportFormat {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "dot" => Some(ExportFormat::Dot),
+            "json" => Some(ExportFormat::Json),
+            "mermaid" | "mmd" => Some(ExportFormat::Mermaid),
+            "graphml" => Some(ExportFormat::GraphMl),
+            "text" | "txt" => Some(ExportFormat::Text),
+            "smv" | "nusmv" => Some(ExportFormat::Smv),
+            "html" => Some(ExportFormat::Html),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExportFormat::Dot => "dot",
+            ExportFormat::Json => "json",
+            ExportFormat::Mermaid => "mermaid",
+            ExportFormat::GraphMl => "graphml",
+            ExportFormat::Text => "text",
+            ExportFormat::Smv => "smv",
+            ExportFormat::Html => "html",
+        }
+    }
+
+    /// Sufixo acrescentado ao nome do contrato no arquivo exportado
+    pub fn file_suffix(&self) -> &'static str {
+        match self {
+            ExportFormat::Dot => ".dot",
+            ExportFormat::Json => ".json",
+            ExportFormat::Mermaid => ".mmd",
+            ExportFormat::GraphMl => ".graphml",
+            ExportFormat::Text => ".txt",
+            ExportFormat::Smv => ".smv",
+            ExportFormat::Html => ".html",
+        }
+    }
+}
+
+impl std::fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
 // ==================== run_configuration.rs ====================
 
-#[derive(Debug, Clone)]
+/// Quantidade padrão de máscaras processadas por lote paralelo na construção do autômato
+pub const DEFAULT_BATCH_SIZE: usize = 500;
+
+/// Máscaras por thread quando o tamanho do lote é automático (`--batch-size 0`)
+pub const AUTO_BATCH_SIZE_PER_THREAD: usize = 64;
+
+/// Caracteres da cláusula mostrados no tooltip de cada nó do DOT
+pub const DEFAULT_TOOLTIP_LENGTH: usize = 200;
+
+/// Aninhamento máximo de uma ação composta (`--max-action-depth`)
+pub const DEFAULT_MAX_ACTION_DEPTH: usize = 1000;
+
+/// Versão do esquema da linha `RESULT_CSV` (colunas de `get_automaton_data`)
+///
+/// Deve ser incrementada sempre que colunas forem adicionadas, removidas ou reordenadas;
+/// o analisador a anuncia na linha `CSV_SCHEMA:` e o lote recusa versões diferentes.
+pub const CSV_SCHEMA_VERSION: u32 = 1;
+
+/// Delimitador padrão das linhas CSV (`RESULT_CSV`, relatórios em lote)
+pub const DEFAULT_CSV_DELIMITER: char = ';';
+
+/// Lê o valor de `--delimiter`: um único caractere, ou `tab`
+///
+/// Letras, dígitos, pontos, hífens e aspas são recusados, pois aparecem nos próprios valores.
+pub fn parse_csv_delimiter(value: &str) -> Option<char> {
+    if value.eq_ignore_ascii_case("tab") || value == "\\t" {
+        return Some('\t');
+    }
+    let mut chars = value.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if !c.is_alphanumeric() && !matches!(c, '.' | '"' | '-' | '\n') => Some(c),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct RunConfiguration {
     contract_file_name: String,
     result_file_name: String,
@@ -391,6 +725,50 @@ pub struct RunConfiguration {
     log_level: LogLevel,
     global_log_filename: String,
     test: bool,
+    export_formats: Vec<ExportFormat>,
+    emitted_log_types: Vec<LogType>,
+    stats: bool,
+    trace_file_name: Option<String>,
+    sort_symbols: bool,
+    print_states: bool,
+    collapse_epsilon: bool,
+    group_by_party: bool,
+    batch_size: usize,
+    certificate: bool,
+    satisfaction_paths: Option<usize>,
+    deny_warnings: bool,
+    estimate: bool,
+    anonymize: bool,
+    deontic_tags: bool,
+    threads: usize,
+    explain_transition: Option<usize>,
+    strict: bool,
+    include_disabled: bool,
+    watch: bool,
+    max_clause_size: Option<usize>,
+    timeout: Option<u64>,
+    memory_limit: Option<u64>,
+    export_contract: bool,
+    csv_only: bool,
+    events: bool,
+    print_ast: bool,
+    global_log_mode: GlobalLogMode,
+    cycles: bool,
+    conflicts_csv: bool,
+    echo_command: bool,
+    tooltip_length: usize,
+    merge_file_name: Option<String>,
+    no_idle: bool,
+    minimal_conflict: bool,
+    baseline_file_name: Option<String>,
+    verify: bool,
+    party_matched_conflicts: bool,
+    csv_delimiter: char,
+    decision_table: bool,
+    conflict_subgraph: bool,
+    max_action_depth: usize,
+    break_on: Option<String>,
+    break_halt: bool,
 }
 
 impl RunConfiguration {
@@ -409,6 +787,50 @@ impl RunConfiguration {
             log_level: LogLevel::Normal,
             global_log_filename: String::new(),
             test: false,
+            export_formats: Vec::new(),
+            emitted_log_types: DEFAULT_EMITTED_LOG_TYPES.to_vec(),
+            stats: false,
+            trace_file_name: None,
+            sort_symbols: false,
+            print_states: false,
+            collapse_epsilon: false,
+            group_by_party: false,
+            batch_size: DEFAULT_BATCH_SIZE,
+            certificate: false,
+            satisfaction_paths: None,
+            deny_warnings: false,
+            anonymize: false,
+            deontic_tags: false,
+            threads: 0,
+            explain_transition: None,
+            strict: false,
+            include_disabled: false,
+            watch: false,
+            max_clause_size: None,
+            timeout: None,
+            memory_limit: None,
+            export_contract: false,
+            csv_only: false,
+            events: false,
+            print_ast: false,
+            global_log_mode: GlobalLogMode::Append,
+            cycles: false,
+            conflicts_csv: false,
+            echo_command: false,
+            tooltip_length: DEFAULT_TOOLTIP_LENGTH,
+            merge_file_name: None,
+            no_idle: false,
+            minimal_conflict: false,
+            baseline_file_name: None,
+            verify: false,
+            party_matched_conflicts: false,
+            csv_delimiter: DEFAULT_CSV_DELIMITER,
+            decision_table: false,
+            conflict_subgraph: false,
+            max_action_depth: DEFAULT_MAX_ACTION_DEPTH,
+            break_on: None,
+            break_halt: false,
+            estimate: false,
         }
     }
 
@@ -431,12 +853,151 @@ impl RunConfiguration {
     pub fn is_export_min_automaton(&self) -> bool {
         self.export_min_automaton
     }
+    pub fn is_export_contract(&self) -> bool {
+        self.export_contract
+    }
+    pub fn is_csv_only(&self) -> bool {
+        self.csv_only
+    }
+    pub fn is_events(&self) -> bool {
+        self.events
+    }
+    pub fn is_print_ast(&self) -> bool {
+        self.print_ast
+    }
+    pub fn global_log_mode(&self) -> GlobalLogMode {
+        self.global_log_mode
+    }
     pub fn is_use_prunning(&self) -> bool {
         self.use_prunning
     }
     pub fn is_test(&self) -> bool {
         self.test
     }
+    pub fn is_stats(&self) -> bool {
+        self.stats
+    }
+    pub fn is_sort_symbols(&self) -> bool {
+        self.sort_symbols
+    }
+    pub fn is_anonymize(&self) -> bool {
+        self.anonymize
+    }
+    pub fn is_deontic_tags(&self) -> bool {
+        self.deontic_tags
+    }
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+    pub fn is_include_disabled(&self) -> bool {
+        self.include_disabled
+    }
+    pub fn is_watch(&self) -> bool {
+        self.watch
+    }
+    pub fn is_print_states(&self) -> bool {
+        self.print_states
+    }
+    pub fn is_collapse_epsilon(&self) -> bool {
+        self.collapse_epsilon
+    }
+    pub fn is_group_by_party(&self) -> bool {
+        self.group_by_party
+    }
+    pub fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+    pub fn is_cycles(&self) -> bool {
+        self.cycles
+    }
+    pub fn is_conflicts_csv(&self) -> bool {
+        self.conflicts_csv
+    }
+    pub fn is_echo_command(&self) -> bool {
+        self.echo_command
+    }
+    pub fn tooltip_length(&self) -> usize {
+        self.tooltip_length
+    }
+    pub fn merge_file_name(&self) -> Option<&str> {
+        self.merge_file_name.as_deref()
+    }
+    pub fn is_no_idle(&self) -> bool {
+        self.no_idle
+    }
+    pub fn is_minimal_conflict(&self) -> bool {
+        self.minimal_conflict
+    }
+    pub fn baseline_file_name(&self) -> Option<&str> {
+        self.baseline_file_name.as_deref()
+    }
+    pub fn is_verify(&self) -> bool {
+        self.verify
+    }
+    pub fn is_party_matched_conflicts(&self) -> bool {
+        self.party_matched_conflicts
+    }
+    pub fn csv_delimiter(&self) -> char {
+        self.csv_delimiter
+    }
+    pub fn is_decision_table(&self) -> bool {
+        self.decision_table
+    }
+    pub fn is_conflict_subgraph(&self) -> bool {
+        self.conflict_subgraph
+    }
+    pub fn max_action_depth(&self) -> usize {
+        self.max_action_depth
+    }
+    pub fn break_on(&self) -> Option<&str> {
+        self.break_on.as_deref()
+    }
+    pub fn is_break_halt(&self) -> bool {
+        self.break_halt
+    }
+    pub fn is_certificate(&self) -> bool {
+        self.certificate
+    }
+    pub fn satisfaction_paths(&self) -> Option<usize> {
+        self.satisfaction_paths
+    }
+    pub fn explain_transition(&self) -> Option<usize> {
+        self.explain_transition
+    }
+    pub fn is_deny_warnings(&self) -> bool {
+        self.deny_warnings
+    }
+    pub fn is_estimate(&self) -> bool {
+        self.estimate
+    }
+
+    /// Tamanho de lote efetivo: o configurado ou, se 0, proporcional ao número de threads
+    ///
+    /// Lotes maiores aproveitam melhor muitos núcleos, mas mantêm mais cláusulas
+    /// decompostas em memória ao mesmo tempo.
+    pub fn effective_batch_size(&self) -> usize {
+        if self.batch_size == 0 {
+            rayon::current_num_threads() * AUTO_BATCH_SIZE_PER_THREAD
+        } else {
+            self.batch_size
+        }
+    }
+    /// Número de threads da análise; 0 usa todos os núcleos
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+    pub fn max_clause_size(&self) -> Option<usize> {
+        self.max_clause_size
+    }
+    /// Tempo máximo de construção do autômato, em segundos (`--timeout`)
+    pub fn timeout(&self) -> Option<u64> {
+        self.timeout
+    }
+    /// Limite de memória do processo em MB (`--memory-limit`); sem ele, o guarda
+    /// calcula um limite seguro a partir da RAM e do swap do sistema
+    pub fn memory_limit(&self) -> Option<u64> {
+        self.memory_limit
+    }
     pub fn decompositions_file_name(&self) -> &str {
         &self.decompositions_file_name
     }
@@ -452,6 +1013,195 @@ impl RunConfiguration {
     pub fn global_log_filename(&self) -> &str {
         &self.global_log_filename
     }
+    pub fn export_formats(&self) -> &[ExportFormat] {
+        &self.export_formats
+    }
+    pub fn emitted_log_types(&self) -> &[LogType] {
+        &self.emitted_log_types
+    }
+    pub fn trace_file_name(&self) -> Option<&str> {
+        self.trace_file_name.as_deref()
+    }
+
+    /// Nome do arquivo exportado para um formato, derivado do arquivo do contrato
+    pub fn export_file_name(&self, format: ExportFormat) -> String {
+        if format == ExportFormat::Dot && !self.automaton_file_name.is_empty() {
+            return self.automaton_file_name.clone();
+        }
+
+        let contract_path = LogPath::new(&self.contract_file_name);
+        let file_stem = FileUtil::contract_stem(&self.contract_file_name);
+        let parent = contract_path.parent().unwrap_or(LogPath::new(""));
+
+        parent
+            .join(format!("{}{}", file_stem, format.file_suffix()))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    /// Nome do arquivo com a cláusula inicial e os conflitos (`<stem>_contract.txt`)
+    pub fn contract_export_file_name(&self) -> String {
+        self.sibling_file_name("_contract.txt")
+    }
+
+    /// Nome do arquivo com a cláusula completa de cada estado (`<stem>_clauses.txt`)
+    pub fn state_clauses_file_name(&self) -> String {
+        self.sibling_file_name("_clauses.txt")
+    }
+
+    /// Nome do CSV com os conflitos encontrados (`<stem>_conflicts.csv`)
+    pub fn conflicts_csv_file_name(&self) -> String {
+        self.sibling_file_name("_conflicts.csv")
+    }
+
+    pub fn decision_table_file_name(&self) -> String {
+        self.sibling_file_name("_decisions.csv")
+    }
+
+    /// Nome do DOT com o subgrafo que leva aos conflitos (`<stem>_conflicts.dot`)
+    pub fn conflict_subgraph_file_name(&self) -> String {
+        self.sibling_file_name("_conflicts.dot")
+    }
+
+    /// Linha de comando normalizada que reproduz esta configuração
+    ///
+    /// Opções com valor (`--global-log`, `--batch-size`, `--threads`) saem sempre,
+    /// mesmo no valor padrão; as booleanas só quando ativas. Os nomes de arquivo
+    /// derivados do contrato não aparecem, pois `parse_command_line` os recalcula;
+    /// só o diretório dos logs, se não for o do contrato (`--log-dir`).
+    ///
+    /// # Retorna
+    /// Os argumentos, começando pelo arquivo do contrato, tais que
+    /// `parse_command_line(&args) == *self`
+    pub fn command_line(&self) -> Vec<String> {
+        let mut args = vec![self.contract_file_name.clone()];
+
+        let flags = [
+            (self.log_level == LogLevel::Verbose, "-v"),
+            (self.export_decompositions, "-g"),
+            (!self.use_prunning, "-n"),
+            (self.no_idle, "--no-idle"),
+            (self.continue_on_conflict, "-c"),
+            (self.export_min_automaton, "-m"),
+            (self.test, "-t"),
+            (self.csv_only, "--csv-only"),
+            (self.events, "--events"),
+            (self.conflicts_csv, "--conflicts-csv"),
+            (self.decision_table, "--decision-table"),
+            (self.conflict_subgraph, "--conflict-subgraph"),
+            (self.export_contract, "--export-contract"),
+            (self.stats, "--stats"),
+            (self.include_disabled, "--include-disabled"),
+            (self.sort_symbols, "--sort-symbols"),
+            (self.anonymize, "--anonymize"),
+            (self.print_states, "--states"),
+            (self.deontic_tags, "--tags"),
+            (self.collapse_epsilon, "--collapse-epsilon"),
+            (self.group_by_party, "--group-by-party"),
+            (self.cycles, "--cycles"),
+            (self.certificate, "--certificate"),
+            (self.deny_warnings, "--deny-warnings"),
+            (self.strict, "--strict"),
+            (self.print_ast, "--ast"),
+            (self.estimate, "--estimate"),
+            (self.minimal_conflict, "--minimal-conflict"),
+            (self.watch, "--watch"),
+            (self.echo_command, "--echo-command"),
+            (self.verify, "--verify"),
+            (self.party_matched_conflicts, "--party-matched-conflicts"),
+            (self.break_halt, "--break-halt"),
+        ];
+        args.extend(flags.iter().filter(|(on, _)| *on).map(|(_, flag)| flag.to_string()));
+
+        args.push(format!("--global-log={}", self.global_log_mode));
+
+        // `-g` já liga o DOT; sem ele, o DOT só pode ter vindo de `--format`
+        let mut formats = self.export_formats.clone();
+        if self.export_automaton && !self.export_decompositions && !formats.contains(&ExportFormat::Dot) {
+            formats.insert(0, ExportFormat::Dot);
+        }
+        if !formats.is_empty() {
+            let names: Vec<&str> = formats.iter().map(|f| f.name()).collect();
+            args.push(format!("--format={}", names.join(",")));
+        }
+
+        if self.emitted_log_types != DEFAULT_EMITTED_LOG_TYPES {
+            let names: Vec<&str> = self.emitted_log_types.iter().map(|t| t.name()).collect();
+            args.push(format!("--emit-logs={}", names.join(",")));
+        }
+
+        if let Some(limit) = self.satisfaction_paths {
+            args.push(format!("--satisfaction-paths={}", limit));
+        }
+        if let Some(id) = self.explain_transition {
+            args.push(format!("--explain={}", id));
+        }
+        if let Some(ref trace) = self.trace_file_name {
+            args.push(format!("--trace={}", trace));
+        }
+        if let Some(ref merge) = self.merge_file_name {
+            args.push("--merge".to_string());
+            args.push(merge.clone());
+        }
+        if let Some(ref baseline) = self.baseline_file_name {
+            args.push("--baseline".to_string());
+            args.push(baseline.clone());
+        }
+        if self.csv_delimiter != DEFAULT_CSV_DELIMITER {
+            args.push("--delimiter".to_string());
+            args.push(match self.csv_delimiter {
+                '\t' => "tab".to_string(),
+                other => other.to_string(),
+            });
+        }
+
+        args.push("--threads".to_string());
+        args.push(self.threads.to_string());
+        args.push("--batch-size".to_string());
+        args.push(self.batch_size.to_string());
+        args.push("--tooltip-length".to_string());
+        args.push(self.tooltip_length.to_string());
+        if let Some(size) = self.max_clause_size {
+            args.push("--max-clause-size".to_string());
+            args.push(size.to_string());
+        }
+        if let Some(seconds) = self.timeout {
+            args.push("--timeout".to_string());
+            args.push(seconds.to_string());
+        }
+        if let Some(mb) = self.memory_limit {
+            args.push("--memory-limit".to_string());
+            args.push(mb.to_string());
+        }
+        if self.max_action_depth != DEFAULT_MAX_ACTION_DEPTH {
+            args.push("--max-action-depth".to_string());
+            args.push(self.max_action_depth.to_string());
+        }
+        if let Some(ref text) = self.break_on {
+            args.push("--break-on".to_string());
+            args.push(text.clone());
+        }
+        if self.result_file_name != self.sibling_file_name(".result") {
+            if let Some(dir) = LogPath::new(&self.result_file_name).parent() {
+                args.push("--log-dir".to_string());
+                args.push(dir.to_string_lossy().into_owned());
+            }
+        }
+
+        args
+    }
+
+    /// Arquivo ao lado do contrato, com o nome do contrato seguido de `suffix`
+    fn sibling_file_name(&self, suffix: &str) -> String {
+        let contract_path = LogPath::new(&self.contract_file_name);
+        let file_stem = FileUtil::contract_stem(&self.contract_file_name);
+        let parent = contract_path.parent().unwrap_or(LogPath::new(""));
+
+        parent
+            .join(format!("{}{}", file_stem, suffix))
+            .to_string_lossy()
+            .into_owned()
+    }
 
     // Setters
     pub fn set_contract_file_name(&mut self, name: String) {
@@ -472,6 +1222,21 @@ impl RunConfiguration {
     pub fn set_export_min_automaton(&mut self, value: bool) {
         self.export_min_automaton = value;
     }
+    pub fn set_export_contract(&mut self, value: bool) {
+        self.export_contract = value;
+    }
+    pub fn set_csv_only(&mut self, value: bool) {
+        self.csv_only = value;
+    }
+    pub fn set_events(&mut self, value: bool) {
+        self.events = value;
+    }
+    pub fn set_print_ast(&mut self, value: bool) {
+        self.print_ast = value;
+    }
+    pub fn set_global_log_mode(&mut self, mode: GlobalLogMode) {
+        self.global_log_mode = mode;
+    }
     pub fn set_use_prunning(&mut self, value: bool) {
         self.use_prunning = value;
     }
@@ -493,6 +1258,123 @@ impl RunConfiguration {
     pub fn set_test(&mut self, value: bool) {
         self.test = value;
     }
+    pub fn set_export_formats(&mut self, formats: Vec<ExportFormat>) {
+        self.export_formats = formats;
+    }
+    pub fn set_emitted_log_types(&mut self, log_types: Vec<LogType>) {
+        self.emitted_log_types = log_types;
+    }
+    pub fn set_stats(&mut self, value: bool) {
+        self.stats = value;
+    }
+    pub fn set_trace_file_name(&mut self, name: Option<String>) {
+        self.trace_file_name = name;
+    }
+    pub fn set_sort_symbols(&mut self, value: bool) {
+        self.sort_symbols = value;
+    }
+    pub fn set_anonymize(&mut self, value: bool) {
+        self.anonymize = value;
+    }
+    pub fn set_deontic_tags(&mut self, value: bool) {
+        self.deontic_tags = value;
+    }
+    pub fn set_strict(&mut self, value: bool) {
+        self.strict = value;
+    }
+    pub fn set_include_disabled(&mut self, value: bool) {
+        self.include_disabled = value;
+    }
+    pub fn set_watch(&mut self, value: bool) {
+        self.watch = value;
+    }
+    pub fn set_threads(&mut self, value: usize) {
+        self.threads = value;
+    }
+    pub fn set_max_clause_size(&mut self, value: Option<usize>) {
+        self.max_clause_size = value;
+    }
+    pub fn set_timeout(&mut self, value: Option<u64>) {
+        self.timeout = value;
+    }
+    pub fn set_memory_limit(&mut self, value: Option<u64>) {
+        self.memory_limit = value;
+    }
+    pub fn set_print_states(&mut self, value: bool) {
+        self.print_states = value;
+    }
+    pub fn set_collapse_epsilon(&mut self, value: bool) {
+        self.collapse_epsilon = value;
+    }
+    pub fn set_group_by_party(&mut self, value: bool) {
+        self.group_by_party = value;
+    }
+    pub fn set_batch_size(&mut self, value: usize) {
+        self.batch_size = value;
+    }
+    pub fn set_cycles(&mut self, value: bool) {
+        self.cycles = value;
+    }
+    pub fn set_conflicts_csv(&mut self, value: bool) {
+        self.conflicts_csv = value;
+    }
+    pub fn set_echo_command(&mut self, value: bool) {
+        self.echo_command = value;
+    }
+    pub fn set_tooltip_length(&mut self, value: usize) {
+        self.tooltip_length = value;
+    }
+    pub fn set_merge_file_name(&mut self, name: Option<String>) {
+        self.merge_file_name = name;
+    }
+    pub fn set_no_idle(&mut self, value: bool) {
+        self.no_idle = value;
+    }
+    pub fn set_minimal_conflict(&mut self, value: bool) {
+        self.minimal_conflict = value;
+    }
+    pub fn set_baseline_file_name(&mut self, name: Option<String>) {
+        self.baseline_file_name = name;
+    }
+    pub fn set_verify(&mut self, value: bool) {
+        self.verify = value;
+    }
+    pub fn set_party_matched_conflicts(&mut self, value: bool) {
+        self.party_matched_conflicts = value;
+    }
+    pub fn set_csv_delimiter(&mut self, value: char) {
+        self.csv_delimiter = value;
+    }
+    pub fn set_decision_table(&mut self, value: bool) {
+        self.decision_table = value;
+    }
+    pub fn set_conflict_subgraph(&mut self, value: bool) {
+        self.conflict_subgraph = value;
+    }
+    pub fn set_max_action_depth(&mut self, value: usize) {
+        self.max_action_depth = value;
+    }
+    pub fn set_break_on(&mut self, value: Option<String>) {
+        self.break_on = value;
+    }
+    pub fn set_break_halt(&mut self, value: bool) {
+        self.break_halt = value;
+    }
+    pub fn set_certificate(&mut self, value: bool) {
+        self.certificate = value;
+    }
+    pub fn set_satisfaction_paths(&mut self, limit: Option<usize>) {
+        self.satisfaction_paths = limit;
+    }
+    pub fn set_explain_transition(&mut self, id: Option<usize>) {
+        self.explain_transition = id;
+    }
+    pub fn set_deny_warnings(&mut self, value: bool) {
+        self.deny_warnings = value;
+    }
+    pub fn set_estimate(&mut self, value: bool) {
+        self.estimate = value;
+    }
 }
 
 impl Default for RunConfiguration {
@@ -501,17 +1383,252 @@ impl Default for RunConfiguration {
     }
 }
 
-// ==================== file_util.rs ====================
-
-pub struct FileUtil;
-
-impl FileUtil {
-    pub fn write_to_file(filename: &str, lines: &[&str]) -> io::Result<bool> {
-        let file = File::create(filename)?;
-        let mut writer = BufWriter::new(file);
+/// Resumo legível das opções efetivas, usado no log de início da análise
+///
+/// Ao contrário de `{:?}`, não expõe nomes de campos internos nem os nomes de arquivo
+/// derivados do contrato, que `parse_command_line` sempre recalcula.
+impl std::fmt::Display for RunConfiguration {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let on_off = |value: bool| if value { "on" } else { "off" };
 
-        for line in lines {
-            writeln!(writer, "{}", line)?;
+        let mut exports: Vec<&str> = Vec::new();
+        if self.export_automaton || self.export_decompositions {
+            exports.push("dot");
+        }
+        if self.export_min_automaton {
+            exports.push("min");
+        }
+        if self.export_decompositions {
+            exports.push("decompositions");
+        }
+        for format in &self.export_formats {
+            if !(*format == ExportFormat::Dot && exports.contains(&"dot")) {
+                exports.push(format.name());
+            }
+        }
+        for (on, name) in [
+            (self.conflicts_csv, "conflicts-csv"),
+            (self.decision_table, "decisions"),
+            (self.conflict_subgraph, "conflict-subgraph"),
+            (self.export_contract, "contract"),
+        ] {
+            if on {
+                exports.push(name);
+            }
+        }
+
+        let max_clause_size = self
+            .max_clause_size
+            .map_or_else(|| "unlimited".to_string(), |n| n.to_string());
+        let threads = if self.threads == 0 {
+            "all cores".to_string()
+        } else {
+            self.threads.to_string()
+        };
+        let logs: Vec<&str> = self.emitted_log_types.iter().map(|t| t.name()).collect();
+
+        write!(
+            f,
+            "Contract: {}, Verbose: {}, Pruning: {}, Idle branch: {}, Continue-on-conflict: {}, \
+             Export: {}, Party-matched conflicts: {}, Threads: {}, Batch size: {}, \
+             Max clause size: {}, Max action depth: {}, Global log: {}, Emitted logs: {}",
+            self.contract_file_name,
+            on_off(self.log_level == LogLevel::Verbose),
+            on_off(self.use_prunning),
+            on_off(!self.no_idle),
+            on_off(self.continue_on_conflict),
+            if exports.is_empty() { "none".to_string() } else { exports.join("+") },
+            on_off(self.party_matched_conflicts),
+            threads,
+            self.batch_size,
+            max_clause_size,
+            self.max_action_depth,
+            self.global_log_mode,
+            logs.join(","),
+        )
+    }
+}
+
+// ==================== file_util.rs ====================
+
+/// Contratos de exemplo embutidos no binário (`--example`)
+pub const EXAMPLE_CONTRACTS: &[(&str, &str)] = &[
+    ("conflict_free.rcl", include_str!("../examples/conflict_free.rcl")),
+    ("conflicting.rcl", include_str!("../examples/conflicting.rcl")),
+    ("multi_party.rcl", include_str!("../examples/multi_party.rcl")),
+];
+
+pub struct FileUtil;
+
+impl FileUtil {
+    /// Nome do contrato sem extensão; `.gz` também é removido (`a.rcl.gz` resulta em `a`)
+    pub fn contract_stem(path: &str) -> String {
+        let file_name = LogPath::new(path)
+            .file_name()
+            .and_then(|s| s.to_str())
+            .unwrap_or("contract");
+        let file_name = file_name.strip_suffix(".gz").unwrap_or(file_name);
+
+        LogPath::new(file_name)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("contract")
+            .to_string()
+    }
+
+    /// Grava os contratos de exemplo (`EXAMPLE_CONTRACTS`) no diretório, criando-o se preciso
+    ///
+    /// # Argumentos
+    /// * `dir` - Diretório de destino; arquivos com o mesmo nome são sobrescritos
+    ///
+    /// # Retorna
+    /// Os caminhos dos arquivos gravados
+    pub fn write_examples(dir: &str) -> io::Result<Vec<String>> {
+        std::fs::create_dir_all(dir)?;
+        let mut written = Vec::with_capacity(EXAMPLE_CONTRACTS.len());
+        for (name, content) in EXAMPLE_CONTRACTS {
+            let path = LogPath::new(dir).join(name);
+            std::fs::write(&path, content)?;
+            written.push(path.to_string_lossy().into_owned());
+        }
+        Ok(written)
+    }
+
+    /// Contrato mínimo formado por uma única cláusula
+    ///
+    /// Sem cabeçalho nem bloco de conflitos; só garante o `;` final exigido pela
+    /// gramática.
+    ///
+    /// # Argumentos
+    /// * `clause` - Texto de uma cláusula RCL, com ou sem `;`
+    pub fn clause_contract(clause: &str) -> String {
+        format!("{};\n", clause.trim().trim_end_matches(';').trim_end())
+    }
+
+    /// Grava `clause_contract(clause)` num arquivo temporário, para analisar a cláusula
+    /// isoladamente pelo mesmo caminho de um contrato em disco
+    ///
+    /// # Argumentos
+    /// * `clause` - Texto de uma cláusula RCL
+    ///
+    /// # Retorna
+    /// O caminho do arquivo gravado (`clause_contract_path`); os arquivos exportados da
+    /// análise ficam ao lado dele, até `remove_clause_files`
+    pub fn write_clause_contract(clause: &str) -> io::Result<String> {
+        let path = Self::clause_contract_path();
+        std::fs::write(&path, Self::clause_contract(clause))?;
+        Ok(path)
+    }
+
+    /// Caminho do contrato temporário de `write_clause_contract`:
+    /// `recall_clause_<pid>.rcl` no diretório temporário
+    pub fn clause_contract_path() -> String {
+        std::env::temp_dir()
+            .join(format!("recall_clause_{}.rcl", std::process::id()))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    /// Remove o contrato temporário de `write_clause_contract` e os arquivos gerados ao
+    /// lado dele (`.log`, `.result`, exportações como `_contract.txt` ou `.dot`)
+    ///
+    /// Erros são ignorados: o arquivo pode nem ter chegado a ser gravado.
+    ///
+    /// # Argumentos
+    /// * `path` - Caminho do contrato temporário
+    pub fn remove_clause_files(path: &str) {
+        let stem = Self::contract_stem(path);
+        let dir = LogPath::new(path).parent().unwrap_or(LogPath::new("."));
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            let generated = name
+                .to_str()
+                .and_then(|name| name.strip_prefix(stem.as_str()))
+                .is_some_and(|rest| rest.starts_with('.') || rest.starts_with('_'));
+            if generated {
+                let _ = std::fs::remove_file(entry.path());
+            }
+        }
+    }
+
+    /// Observa um arquivo e chama `on_change` a cada modificação, até ela retornar `false`
+    ///
+    /// O diretório pai é observado (editores costumam salvar substituindo o arquivo) e só
+    /// eventos do próprio arquivo são considerados. Eventos seguidos são agrupados: a
+    /// chamada só acontece depois de `debounce` sem novos eventos.
+    ///
+    /// # Argumentos
+    /// * `path` - Arquivo observado
+    /// * `debounce` - Intervalo de silêncio exigido antes de disparar `on_change`
+    /// * `on_change` - Chamada a cada modificação agrupada; retorna se a observação continua
+    pub fn watch(
+        path: &str,
+        debounce: Duration,
+        mut on_change: impl FnMut() -> bool,
+    ) -> notify::Result<()> {
+        use notify::{RecursiveMode, Watcher};
+        use std::sync::mpsc;
+
+        let target = std::fs::canonicalize(path)?;
+        let dir = target
+            .parent()
+            .map(|p| p.to_path_buf())
+            .unwrap_or_else(|| LogPath::new(".").to_path_buf());
+
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(tx)?;
+        watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+
+        let touches_target = |event: &notify::Result<notify::Event>| match event {
+            Ok(event) => {
+                (event.kind.is_modify() || event.kind.is_create())
+                    && event.paths.iter().any(|p| p.file_name() == target.file_name())
+            }
+            Err(_) => false,
+        };
+
+        while let Ok(event) = rx.recv() {
+            if !touches_target(&event) {
+                continue;
+            }
+            // Espera as gravações seguidas terminarem antes de reanalisar
+            while rx.recv_timeout(debounce).is_ok() {}
+            if !on_change() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Indica se o caminho é de um contrato, compactado (`.rcl.gz`) ou não (`.rcl`)
+    pub fn is_contract_file(path: &str) -> bool {
+        path.ends_with(".rcl") || path.ends_with(".rcl.gz")
+    }
+
+    /// Lê o texto do contrato, descompactando-o com gzip se terminar em `.gz`
+    pub fn read_contract(path: &str) -> io::Result<String> {
+        use std::io::Read;
+
+        let file = Self::open_protected(path, false, false, false)?;
+        let mut input = String::new();
+        if path.ends_with(".gz") {
+            flate2::read::GzDecoder::new(file).read_to_string(&mut input)?;
+        } else {
+            let mut file = file;
+            file.read_to_string(&mut input)?;
+        }
+        Ok(input)
+    }
+
+    pub fn write_to_file(filename: &str, lines: &[&str]) -> io::Result<bool> {
+        let file = File::create(filename)?;
+        let mut writer = BufWriter::new(file);
+
+        for line in lines {
+            writeln!(writer, "{}", line)?;
         }
 
         writer.flush()?;
@@ -548,6 +1665,9 @@ impl FileUtil {
 
 // ==================== logger.rs ====================
 
+/// Sequência CSI (`ESC [` parâmetros, intermediários, byte final), compilada uma vez
+static ANSI_CSI: OnceLock<regex::Regex> = OnceLock::new();
+
 #[derive(Clone)]
 pub struct Logger {
     level: LogLevel,
@@ -556,7 +1676,22 @@ pub struct Logger {
     bw_global: Arc<Mutex<Option<BufWriter<File>>>>,
     bw_local: Arc<Mutex<Option<BufWriter<File>>>>,
     contract_name: String,
-    app_handle: Option<tauri::AppHandle>,
+    /// Destino de `event` e dos tipos de log emitidos (`log-message`); sem ele, os
+    /// eventos só são impressos com `--events`
+    event_sink: Option<Arc<dyn EventSink>>,
+    /// `--csv-only`: nada é impresso no stdout, só gravado nos arquivos de log
+    quiet: bool,
+}
+
+/// Destino dos eventos estruturados informados por `Logger::event`
+pub trait EventSink: Send + Sync {
+    fn emit_event(&self, name: &str, payload: serde_json::Value);
+}
+
+impl EventSink for tauri::AppHandle {
+    fn emit_event(&self, name: &str, payload: serde_json::Value) {
+        let _ = self.emit(name, payload);
+    }
 }
 
 #[derive(Serialize, Clone)]
@@ -568,14 +1703,12 @@ pub struct LogPayload {
 
 impl Logger {
     pub fn new(configuration: RunConfiguration) -> std::io::Result<Self> {
-        let global_log_filename = configuration.global_log_filename().to_string();
-        let contract_name = LogPath::new(configuration.contract_file_name())
-            .file_stem()
-            .and_then(|s| s.to_str())
-            .unwrap_or("contract")
-            .to_string();
+        let contract_name = FileUtil::contract_stem(configuration.contract_file_name());
 
-        let global_file = FileUtil::open_protected(&global_log_filename, false, true, false)?;
+        let (global_log_filename, global_file) = Self::open_global_log(
+            configuration.global_log_filename(),
+            configuration.global_log_mode(),
+        )?;
 
         let local_file = FileUtil::open_protected(configuration.result_file_name(), true, false, true)?;
 
@@ -586,33 +1719,99 @@ impl Logger {
             bw_global: Arc::new(Mutex::new(Some(BufWriter::new(global_file)))),
             bw_local: Arc::new(Mutex::new(Some(BufWriter::new(local_file)))),
             contract_name,
-            app_handle: None,
+            event_sink: None,
+            quiet: configuration.is_csv_only(),
         })
     }
 
+    /// Abre o log global conforme o modo configurado (`--global-log`)
+    ///
+    /// # Retorna
+    /// O caminho efetivamente aberto (diferente do configurado no modo `Timestamp`) e o arquivo
+    fn open_global_log(path: &str, mode: GlobalLogMode) -> std::io::Result<(String, File)> {
+        match mode {
+            GlobalLogMode::Append => {
+                Ok((path.to_string(), FileUtil::open_protected(path, false, true, false)?))
+            }
+            GlobalLogMode::Truncate => {
+                Ok((path.to_string(), FileUtil::open_protected(path, true, false, true)?))
+            }
+            GlobalLogMode::Rotate(max_mb) => {
+                let size = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+                if size >= max_mb * 1024 * 1024 {
+                    std::fs::rename(path, format!("{}.1", path))?;
+                }
+                Ok((path.to_string(), FileUtil::open_protected(path, false, true, false)?))
+            }
+            GlobalLogMode::Timestamp => {
+                let log_path = LogPath::new(path);
+                let stem = log_path.file_stem().and_then(|s| s.to_str()).unwrap_or("recall");
+                let extension = log_path.extension().and_then(|s| s.to_str()).unwrap_or("log");
+                let timestamp = Local::now().format("%Y-%m-%d_%H-%M-%S");
+                let stamped = log_path
+                    .with_file_name(format!("{}_{}.{}", stem, timestamp, extension))
+                    .to_string_lossy()
+                    .into_owned();
+                let file = FileUtil::open_protected(&stamped, false, true, false)?;
+                Ok((stamped, file))
+            }
+        }
+    }
+
+    /// Remove sequências de controle ANSI (CSI: cores, movimentos de cursor, limpeza
+    /// de linha) do texto, que vai para arquivos de log e para a interface
     pub fn strip_ansi_codes(text: &str) -> String {
-        let re = regex::Regex::new(r"\x1B\[[0-9;]*m").unwrap();
-        re.replace_all(text, "").to_string()
+        // Caminho comum: mensagens sem ESC não passam pelo regex
+        if !text.contains('\x1B') {
+            return text.to_string();
+        }
+        ANSI_CSI
+            .get_or_init(|| regex::Regex::new(r"\x1B\[[0-?]*[ -/]*[@-~]").unwrap())
+            .replace_all(text, "")
+            .to_string()
     }
 
-    pub fn with_app_handle(mut self, app_handle: tauri::AppHandle) -> Self {
-        self.app_handle = Some(app_handle);
+    pub fn with_app_handle(self, app_handle: tauri::AppHandle) -> Self {
+        self.with_event_sink(Arc::new(app_handle))
+    }
+
+    pub fn with_event_sink(mut self, sink: Arc<dyn EventSink>) -> Self {
+        self.event_sink = Some(sink);
         self
     }
 
+    /// Informa um evento estruturado (por exemplo `conflict-found`) assim que ele ocorre
+    ///
+    /// Na aplicação, vai para o destino de eventos (a interface); no sidecar iniciado
+    /// pela interface (`--events`), vai para o stdout como uma linha `EVENT_JSON:` que o
+    /// lado Tauri reemite. Fora isso é descartado: na linha de comando fica só a
+    /// mensagem legível registrada junto com cada evento.
+    pub fn event(&self, name: &str, payload: serde_json::Value) {
+        if let Some(ref sink) = self.event_sink {
+            sink.emit_event(name, payload);
+        } else if self.configuration.is_events() && !self.quiet {
+            println!(
+                "EVENT_JSON:{}",
+                serde_json::json!({ "event": name, "payload": payload })
+            );
+        }
+    }
+
     pub fn log(&self, log_type: LogType, text: &str) {
         let date_info = self.get_date_info();
         let formatted_text = self.format(text);
 
-        if let Some(ref handle) = self.app_handle {
-            let _ = handle.emit(
-                "log-message",
-                LogPayload {
-                    log_type,
-                    message: formatted_text.clone(),
-                    date: date_info.clone(),
-                },
-            );
+        // Filtrado antes de emitir: cada evento é uma mensagem IPC para a interface
+        let emitted = self.configuration.emitted_log_types().contains(&log_type);
+        if let Some(sink) = self.event_sink.as_ref().filter(|_| emitted) {
+            let payload = LogPayload {
+                log_type,
+                message: formatted_text.clone(),
+                date: date_info.clone(),
+            };
+            if let Ok(payload) = serde_json::to_value(payload) {
+                sink.emit_event("log-message", payload);
+            }
         }
 
         match self.level {
@@ -622,13 +1821,13 @@ impl Logger {
                         let msg =
                             format!("{} [{}]: {}", date_info, self.contract_name, formatted_text);
                         self.write_global(&msg);
-                        println!("{}", formatted_text);
+                        self.print(&formatted_text);
                     }
                     LogType::Necessary => {
                         let msg =
                             format!("{} [{}]: {}", date_info, self.contract_name, formatted_text);
                         self.write_global(&msg);
-                        println!("{}", formatted_text);
+                        self.print(&formatted_text);
                     }
                     LogType::Additional => {}
                 }
@@ -641,11 +1840,31 @@ impl Logger {
                     format!("{} [{}]: {}", date_info, self.contract_name, formatted_text);
                 self.write_local(&local_msg);
                 self.write_global(&global_msg);
-                println!("{}", formatted_text);
+                self.print(&formatted_text);
             }
         }
     }
 
+    /// Descarrega os buffers do log global e do `.result`
+    ///
+    /// Cada linha já é descarregada ao ser escrita; isto serve para quem encerra o
+    /// processo fora do fluxo normal (por exemplo, o tratador de Ctrl+C).
+    pub fn flush(&self) {
+        for writer in [&self.bw_global, &self.bw_local] {
+            if let Ok(mut lock) = writer.lock() {
+                if let Some(ref mut writer) = *lock {
+                    let _ = writer.flush();
+                }
+            }
+        }
+    }
+
+    fn print(&self, text: &str) {
+        if !self.quiet {
+            println!("{}", text);
+        }
+    }
+
     fn write_global(&self, line: &str) {
         if let Ok(mut lock) = self.bw_global.lock() {
             if let Some(ref mut writer) = *lock {
@@ -689,15 +1908,69 @@ impl Logger {
 pub struct ContractUtil;
 
 impl ContractUtil {
+    /// Conjuntos válidos de ações por busca com retrocesso, usada acima de `MASK_ACTION_LIMIT`
+    ///
+    /// Todo superconjunto de um conjunto inválido também é inválido (contém o mesmo par
+    /// em conflito), então um ramo é abandonado assim que a ação acrescentada invalida o
+    /// conjunto. O custo cresce com a quantidade de conjuntos válidos, não com `2^n`.
+    ///
+    /// # Argumentos
+    /// * `src` - Ações, na ordem do `source_map`
+    /// * `conflicts` - Conflitos do contrato
+    /// * `start` - Primeiro índice que ainda pode entrar no conjunto
+    /// * `current` - Índices do conjunto em construção
+    /// * `current_set` - As mesmas ações de `current`, para `is_valid`
+    /// * `out` - Conjuntos válidos encontrados
+    fn extend_valid_sets(
+        src: &[Arc<RelativizedAction>],
+        conflicts: &[Conflict],
+        start: usize,
+        current: &mut Vec<usize>,
+        current_set: &mut FxHashSet<Arc<RelativizedAction>>,
+        out: &mut Vec<ActionSet>,
+        logger: &mut Logger,
+    ) {
+        for idx in start..src.len() {
+            current.push(idx);
+            current_set.insert(src[idx].clone());
+
+            if Self::is_valid(current_set, conflicts) {
+                if out.len() >= SPARSE_SET_LIMIT {
+                    let msg = format!("CRITICAL: More than {} concurrent action combinations for {} actions.", SPARSE_SET_LIMIT, src.len());
+                    logger.log(LogType::Necessary, &msg);
+                    panic!("{}", msg);
+                }
+                out.push(ActionSet::from_indices(current, src.len()));
+                Self::extend_valid_sets(src, conflicts, idx + 1, current, current_set, out, logger);
+            }
+
+            current_set.remove(&src[idx]);
+            current.pop();
+        }
+    }
+
+    /// Verifica se um conjunto de ações concorrentes não contém conflitos
+    ///
+    /// Ações negadas (`!a`, como a do ramo ocioso ou a de `[!a]C`) indicam justamente
+    /// que `a` não ocorre, e por isso não contam como execução de `a` num conflito.
+    ///
+    /// # Argumentos
+    /// * `actions` - Conjunto de ações relativizadas executadas juntas
+    /// * `conflicts` - Conflitos do contrato
+    ///
+    /// # Retorna
+    /// `true` se o conjunto não é vazio e nenhum conflito é disparado
     pub fn is_valid(actions: &FxHashSet<Arc<RelativizedAction>>, conflicts: &[Conflict]) -> bool {
         if actions.is_empty() {
             return false;
         }
 
+        let performs = |ra: &Arc<RelativizedAction>, value: i32| !ra.negation && ra.action.value == value;
+
         for conflict in conflicts {
             let count_a = actions
                 .iter()
-                .filter(|ra| ra.action.value == conflict.a.value)
+                .filter(|ra| performs(ra, conflict.a.value))
                 .count();
 
             if count_a == 0 {
@@ -706,7 +1979,7 @@ impl ContractUtil {
 
             let count_b = actions
                 .iter()
-                .filter(|ra| ra.action.value == conflict.b.value)
+                .filter(|ra| performs(ra, conflict.b.value))
                 .count();
 
             if count_b == 0 {
@@ -717,13 +1990,27 @@ impl ContractUtil {
                 return false;
             }
 
+            if let ConflictType::Scoped(party) = conflict.conflict_type {
+                let involves = |ra: &Arc<RelativizedAction>| ra.sender == party || ra.receiver == party;
+                let scoped_conflict = actions
+                    .iter()
+                    .any(|ra| performs(ra, conflict.a.value) && involves(ra))
+                    && actions
+                        .iter()
+                        .any(|ra| performs(ra, conflict.b.value) && involves(ra));
+
+                if scoped_conflict {
+                    return false;
+                }
+            }
+
             if conflict.conflict_type == ConflictType::Relativized {
                 let has_relativized_conflict = actions
                     .iter()
-                    .filter(|ra| ra.action.value == conflict.a.value)
+                    .filter(|ra| performs(ra, conflict.a.value))
                     .any(|ra_a| {
                         actions.iter().any(|ra_b| {
-                            ra_b.action.value == conflict.b.value && ra_b.sender == ra_a.sender
+                            performs(ra_b, conflict.b.value) && ra_b.sender == ra_a.sender
                         })
                     });
 
@@ -745,12 +2032,38 @@ impl ContractUtil {
 
         let n = relativized_actions.len();
 
-        if n > 30 {
-            let msg = format!("CRITICAL: Can't calculate the set of concurrent relativized actions. (Number of actions {}). Maximum supported is 30.", n);
+        if n > SPARSE_ACTION_LIMIT {
+            let msg = format!("CRITICAL: Can't calculate the set of concurrent relativized actions. (Number of actions {}). Maximum supported is {}.", n, SPARSE_ACTION_LIMIT);
             logger.log(LogType::Necessary, &msg);
             panic!("{}", msg);
         }
 
+        // A ordem do conjunto é arbitrária; ordenar fixa o índice de cada ação e, com ele,
+        // a ordem das ações nos rótulos das transições
+        let mut src: Vec<Arc<RelativizedAction>> = relativized_actions.into_iter().collect();
+        src.sort_by_key(|a| (a.sender, a.action.value, a.receiver, a.negation));
+
+        if n > MASK_ACTION_LIMIT {
+            let mut valid_sets = Vec::new();
+            Self::extend_valid_sets(&src, conflicts, 0, &mut Vec::new(), &mut FxHashSet::default(), &mut valid_sets, logger);
+            valid_sets.sort_unstable_by(|a, b| b.len().cmp(&a.len()).then(a.cmp(b)));
+
+            logger.log(
+                LogType::Necessary,
+                &format!(
+                    "Calculated {} concurrent actions for {} actions by search in {:?}",
+                    valid_sets.len(),
+                    n,
+                    current_time.elapsed()
+                ),
+            );
+
+            return CompressedConcurrentActions {
+                source_map: Arc::new(src),
+                valid_sets,
+            };
+        }
+
         let size: u64 = 1u64 << n; // 2^n
         let mut check_vec: Vec<u32> = Vec::new();
         if let Err(_) = check_vec.try_reserve(size as usize) {
@@ -761,7 +2074,6 @@ impl ContractUtil {
             panic!("{}", msg);
         }
 
-        let src: Vec<Arc<RelativizedAction>> = relativized_actions.into_iter().collect();
         let src_arc = Arc::new(src);
 
         let size: u64 = 1u64 << n; // 2^n
@@ -788,7 +2100,9 @@ impl ContractUtil {
             })
             .collect();
 
-        valid_masks.sort_by(|a, b| b.count_ones().cmp(&a.count_ones()));
+        // Mais ações primeiro; em caso de empate, a própria máscara fixa a ordem, e com
+        // ela a numeração dos estados criados durante a construção
+        valid_masks.sort_unstable_by(|a, b| b.count_ones().cmp(&a.count_ones()).then(a.cmp(b)));
 
         let mut sys = System::new_all();
         sys.refresh_all();
@@ -813,13 +2127,22 @@ impl ContractUtil {
 
         CompressedConcurrentActions {
             source_map: src_arc,
-            valid_masks,
+            valid_sets: valid_masks.into_iter().map(ActionSet::Mask).collect(),
         }
     }
 }
 
 // ==================== automaton_exporter.rs ====================
 
+/// Origem de um grafo exportado, registrada no comentário de cabeçalho do DOT
+/// e no objeto `contract` do JSON
+pub struct DotSource<'a> {
+    pub contract_file: &'a str,
+    pub fingerprint: String,
+    pub title: Option<&'a str>,
+    pub description: Option<&'a str>,
+}
+
 pub struct AutomatonExporter;
 
 impl AutomatonExporter {
@@ -836,13 +2159,7 @@ impl AutomatonExporter {
                 String::from("")
             };
 
-            let situation_str = match state.situation {
-                StateSituation::Violating => "violating",
-                StateSituation::Satisfaction => "satisfaction",
-                StateSituation::Conflicting => "conflicting",
-                StateSituation::ConflictFree => "conflictFree",
-                StateSituation::NotChecked => "notChecked",
-            };
+            let situation_str = Self::situation_name(state.situation);
 
             output.push_str(&format!("{};{};{}\n", state.id, clause_str, situation_str));
         }
@@ -850,90 +2167,202 @@ impl AutomatonExporter {
         output
     }
 
+    /// Lista os estados em uma tabela alinhada para leitura no terminal
+    ///
+    /// Cláusulas maiores que `STATES_TABLE_CLAUSE_WIDTH` caracteres são truncadas com `...`.
+    pub fn dump_states_table(automaton: &Automaton) -> String {
+        const STATES_TABLE_CLAUSE_WIDTH: usize = 60;
+
+        let mut states: Vec<_> = automaton.states.iter().collect();
+        states.sort_by_key(|s| s.id);
+
+        let id_width = states
+            .iter()
+            .map(|s| s.id.to_string().len())
+            .max()
+            .unwrap_or(0)
+            .max("ID".len());
+
+        let mut output = format!("{:>id_width$}  {:<3}  {}\n", "ID", "SIT", "CLAUSE");
+        output.push_str(&format!(
+            "{}  {}  {}\n",
+            "-".repeat(id_width),
+            "-".repeat(3),
+            "-".repeat(STATES_TABLE_CLAUSE_WIDTH)
+        ));
+
+        for state in states {
+            let clause_str = match state.clause {
+                Some(ref clause) => clause.to_string(),
+                None => String::from("<empty>"),
+            };
+            let clause_str = if clause_str.chars().count() > STATES_TABLE_CLAUSE_WIDTH {
+                let truncated: String = clause_str
+                    .chars()
+                    .take(STATES_TABLE_CLAUSE_WIDTH - 3)
+                    .collect();
+                format!("{}...", truncated)
+            } else {
+                clause_str
+            };
+
+            let situation_code = match state.situation {
+                StateSituation::Violating => "V",
+                StateSituation::Satisfaction => "S",
+                StateSituation::Conflicting => "C",
+                StateSituation::ConflictFree => "F",
+                StateSituation::NotChecked => "N",
+            };
+
+            output.push_str(&format!(
+                "{:>id_width$}  {:<3}  {}\n",
+                state.id, situation_code, clause_str
+            ));
+        }
+
+        output.push_str(
+            "\nSIT: V = violating, S = satisfaction, C = conflicting, F = conflict-free, N = not checked\n",
+        );
+        output
+    }
+
     pub fn dump_to_dot(automaton: &Automaton) -> String {
-        let mut output = String::from("digraph contract {\nrankdir=LR;\n");
+        Self::dump_to_dot_with(automaton, false, None, DEFAULT_TOOLTIP_LENGTH, false)
+    }
+
+    /// Comentário de cabeçalho do DOT: contrato de origem (se conhecido), data de
+    /// geração e contagens dos estados e transições desenhados
+    fn dot_header(states: usize, transitions: usize, source: Option<&DotSource>) -> String {
+        let mut output = String::from("/*\n");
+        if let Some(source) = source {
+            output.push_str(&format!(" * Contract: {}\n", source.contract_file));
+            output.push_str(&format!(" * Fingerprint: {}\n", source.fingerprint));
+            // Um "*/" no texto fecharia o comentário antes da hora
+            if let Some(title) = source.title {
+                output.push_str(&format!(" * Title: {}\n", title.replace("*/", "* /")));
+            }
+            if let Some(description) = source.description {
+                output.push_str(&format!(
+                    " * Description: {}\n",
+                    description.replace("*/", "* /").replace('\n', " ")
+                ));
+            }
+        }
+        output.push_str(&format!(
+            " * Generated: {}\n",
+            Local::now().format("%Y-%m-%d %H:%M:%S")
+        ));
+        output.push_str(&format!(" * States: {}\n", states));
+        output.push_str(&format!(" * Transitions: {}\n", transitions));
+        output.push_str(" */\n");
+        output
+    }
+
+    /// Exporta o autômato em DOT, opcionalmente juntando estados ligados por transições ε
+    ///
+    /// Com `collapse_epsilon`, estados de mesma situação ligados apenas por transições
+    /// sem ações positivas são desenhados como um único nó (o de menor id) e essas
+    /// transições são omitidas. O autômato em si não é alterado.
+    ///
+    /// # Argumentos
+    /// * `automaton` - O autômato a exportar
+    /// * `collapse_epsilon` - Se `true`, junta os estados ligados por ε
+    /// * `source` - Contrato de origem, registrado no comentário de cabeçalho
+    /// * `tooltip_length` - Máximo de caracteres da cláusula no tooltip (0 = sem limite)
+    /// * `group_by_party` - Se `true`, os rótulos agrupam as ações por emissor
+    pub fn dump_to_dot_with(
+        automaton: &Automaton,
+        collapse_epsilon: bool,
+        source: Option<&DotSource>,
+        tooltip_length: usize,
+        group_by_party: bool,
+    ) -> String {
+        let representatives = if collapse_epsilon {
+            Self::epsilon_representatives(automaton)
+        } else {
+            FxHashMap::default()
+        };
+        let rep = |id: usize| *representatives.get(&id).unwrap_or(&id);
+
+        let mut output =
+            Self::dot_header(automaton.states.len(), automaton.transitions.len(), source);
+        output.push_str("digraph contract {\nrankdir=LR;\n");
 
         output.push_str("node [shape = point, color=white, fontcolor=white]; start;\n");
 
         for state in automaton.states.iter() {
+            if rep(state.id) != state.id {
+                continue;
+            }
             if state.situation == StateSituation::NotChecked
                 || state.situation == StateSituation::ConflictFree
             {
-                let tooltip = if let Some(ref clause) = state.clause {
-                    format!("{}", clause)
-                } else {
-                    String::from("")
-                };
+                let tooltip = Self::dot_tooltip(state.clause.as_ref(), tooltip_length);
                 output.push_str(&format!(
                     "node [shape = circle, color=black, fontcolor=black, tooltip=\"{}\"]; S{} ;\n",
-                    tooltip.replace("\"", "\\\""),
+                    tooltip,
                     state.id
                 ));
             }
         }
 
         for state in automaton.states.iter() {
-            if state.situation == StateSituation::Violating {
-                let tooltip = if let Some(ref clause) = state.clause {
-                    format!("{}", clause)
-                } else {
-                    String::from("")
-                };
+            if state.situation == StateSituation::Violating && rep(state.id) == state.id {
+                let tooltip = Self::dot_tooltip(state.clause.as_ref(), tooltip_length);
                 output.push_str(&format!(
                     "node [shape = circle, color=red, fontcolor=white, style=filled, fillcolor=red, tooltip=\"{}\"]; S{} ;\n",
-                    tooltip.replace("\"", "\\\""),
+                    tooltip,
                     state.id
                 ));
             }
         }
 
         for state in automaton.states.iter() {
-            if state.situation == StateSituation::Satisfaction {
-                let tooltip = if let Some(ref clause) = state.clause {
-                    format!("{}", clause)
-                } else {
-                    String::from("")
-                };
+            if state.situation == StateSituation::Satisfaction && rep(state.id) == state.id {
+                let tooltip = Self::dot_tooltip(state.clause.as_ref(), tooltip_length);
                 output.push_str(&format!(
                     "node [shape = circle, color=green, fontcolor=white, style=filled, fillcolor=green, tooltip=\"{}\"]; S{} ;\n",
-                    tooltip.replace("\"", "\\\""),
+                    tooltip,
                     state.id
                 ));
             }
         }
 
         for state in automaton.states.iter() {
-            if state.situation == StateSituation::Conflicting {
-                let tooltip = if let Some(ref clause) = state.clause {
-                    format!("{}", clause)
-                } else {
-                    String::from("")
-                };
+            if state.situation == StateSituation::Conflicting && rep(state.id) == state.id {
+                let tooltip = Self::dot_tooltip(state.clause.as_ref(), tooltip_length);
                 output.push_str(&format!(
                     "node [shape = circle, color=orange, fontcolor=white, style=filled, fillcolor=orange, tooltip=\"{}\"]; S{} ;\n",
-                    tooltip.replace("\"", "\\\""),
+                    tooltip,
                     state.id
                 ));
             }
         }
 
         if let Some(ref initial) = automaton.initial {
-            output.push_str(&format!("start -> S{}\n", initial.id));
+            output.push_str(&format!("start -> S{}\n", rep(initial.id)));
         }
 
         {
-            let symbol_table = SymbolTable::instance();
-            let table = symbol_table.lock().unwrap();
+            let table = SymbolTable::lock();
 
+            let mut drawn = FxHashSet::default();
             for transition in automaton.transitions.iter() {
-                let actions_vec = transition.actions();
-                let actions_str = Self::format_actions(&actions_vec, &table);
-                output.push_str(&format!(
-                    "\tS{} -> S{} [ label = \"{}\" ];\n",
-                    transition.from,
-                    transition.to,
-                    actions_str.replace("\"", "\\\"")
-                ));
+                let (from, to) = (rep(transition.from), rep(transition.to));
+                if collapse_epsilon && from == to && transition.action_refs().all(|a| a.negation) {
+                    continue;
+                }
+                let actions_str = Self::transition_label(transition, &table, group_by_party);
+                let edge = format!(
+                    "\tS{} -> S{} [ label = \"{}\"{} ];\n",
+                    from,
+                    to,
+                    actions_str.replace("\"", "\\\""),
+                    Self::self_loop_style(from, to)
+                );
+                if drawn.insert(edge.clone()) {
+                    output.push_str(&edge);
+                }
             }
         }
 
@@ -941,12 +2370,181 @@ impl AutomatonExporter {
         output
     }
 
+    /// Cláusula do estado pronta para o atributo `tooltip` do DOT
+    ///
+    /// Cláusulas muito aninhadas geram textos enormes; acima de `max_length` caracteres
+    /// o texto é cortado com reticências (a cláusula completa fica em `dump_state_clauses`).
+    /// Barras invertidas, aspas e quebras de linha são escapadas.
+    fn dot_tooltip(clause: Option<&Clause>, max_length: usize) -> String {
+        let Some(clause) = clause else {
+            return String::new();
+        };
+        let mut text = format!("{}", clause);
+        if max_length > 0 && text.chars().count() > max_length {
+            text = text.chars().take(max_length).collect::<String>() + "...";
+        }
+        text.replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\r', "")
+            .replace('\n', "\\n")
+    }
+
+    /// Cláusula completa de cada estado, uma linha por estado (`S<id>: <cláusula>`)
+    ///
+    /// Complementa os tooltips truncados do DOT.
+    pub fn dump_state_clauses(automaton: &Automaton) -> String {
+        let mut states: Vec<_> = automaton.states.iter().collect();
+        states.sort_by_key(|s| s.id);
+
+        let mut output = String::new();
+        for state in states {
+            if let Some(ref clause) = state.clause {
+                output.push_str(&format!("S{}: {}\n", state.id, clause));
+            }
+        }
+        output
+    }
+
+    /// Representante (menor id) de cada grupo de estados de mesma situação ligados por ε
+    fn epsilon_representatives(automaton: &Automaton) -> FxHashMap<usize, usize> {
+        fn find(parent: &mut FxHashMap<usize, usize>, id: usize) -> usize {
+            let next = *parent.get(&id).unwrap_or(&id);
+            if next == id {
+                return id;
+            }
+            let root = find(parent, next);
+            parent.insert(id, root);
+            root
+        }
+
+        let mut parent: FxHashMap<usize, usize> = FxHashMap::default();
+        for transition in automaton.transitions.iter() {
+            if !transition.action_refs().all(|a| a.negation) {
+                continue;
+            }
+            let same_situation = match (
+                automaton.get_state_by_id(transition.from),
+                automaton.get_state_by_id(transition.to),
+            ) {
+                (Some(from), Some(to)) => from.situation == to.situation,
+                _ => false,
+            };
+            if !same_situation {
+                continue;
+            }
+
+            let a = find(&mut parent, transition.from);
+            let b = find(&mut parent, transition.to);
+            if a != b {
+                parent.insert(a.max(b), a.min(b));
+            }
+        }
+
+        let ids: Vec<usize> = parent.keys().copied().collect();
+        ids.into_iter()
+            .map(|id| (id, find(&mut parent, id)))
+            .collect()
+    }
+
+    /// Documenta exatamente o que foi analisado: a cláusula do estado inicial (a
+    /// composição de todas as cláusulas, na ordem de `Contract::get_full_contract`),
+    /// os conflitos declarados e a impressão digital do contrato
+    pub fn dump_contract(automaton: &Automaton, contract: &Contract) -> String {
+        let mut output = format!("Fingerprint: {}\n\n", contract.fingerprint());
+
+        output.push_str("Initial clause:\n");
+        match automaton.initial.as_ref().and_then(|s| s.clause.as_ref()) {
+            Some(clause) => output.push_str(&format!("{}\n", clause)),
+            None => output.push_str("(none)\n"),
+        }
+
+        let conflicts = contract.get_all_conflicts();
+        output.push_str(&format!("\nConflicts ({}):\n", conflicts.len()));
+        for conflict in &conflicts {
+            output.push_str(&format!("{}\n", conflict));
+        }
+
+        output
+    }
+
+    /// Tabela de decisão (`AutomataConstructor::decision_table`) em CSV
+    ///
+    /// Uma linha por escolha de ações no estado inicial, com a situação após o
+    /// primeiro passo e o resultado final; `open` indica que o resultado ainda
+    /// depende das escolhas seguintes.
+    pub fn dump_decision_table(automaton: &Automaton) -> String {
+        let mut output = String::from("transition;actions;target_state;first_step;outcome\n");
+
+        let rows = crate::algorithms::automata_constructor::AutomataConstructor::decision_table(automaton);
+        let table = SymbolTable::lock();
+        for row in rows {
+            let actions: Vec<String> = row
+                .actions
+                .iter()
+                .map(|a| a.format_with_symbols(&table))
+                .collect();
+            let outcome = match row.outcome {
+                StateSituation::ConflictFree | StateSituation::NotChecked => "open",
+                other => Self::situation_name(other),
+            };
+            output.push_str(&format!(
+                "{};{};{};{};{}\n",
+                row.transition,
+                if actions.is_empty() { "ε".to_string() } else { actions.join(", ") },
+                row.target,
+                Self::situation_name(row.first_step),
+                outcome
+            ));
+        }
+        output
+    }
+
+    /// Conflitos encontrados em formato tabular, uma linha por estado conflitante
+    ///
+    /// Colunas: `state_id;conflicting_tag;conflicts_with;source_type;trace_length`,
+    /// onde `conflicts_with` lista as tags em conflito separadas por vírgula e
+    /// `source_type` é a relativização da tag (global, relativized ou directed).
+    pub fn dump_conflicts_csv(automaton: &Automaton) -> String {
+        let mut output =
+            String::from("state_id;conflicting_tag;conflicts_with;source_type;trace_length\n");
+
+        let mut states = automaton.get_conflicts();
+        states.sort_by_key(|s| s.id);
+
+        let table = SymbolTable::lock();
+        for state in states {
+            let Some(ref info) = state.conflict_information else {
+                continue;
+            };
+            let mut conflicting: Vec<String> = info
+                .conflicting_tags
+                .iter()
+                .map(|t| t.format_with_symbols(&table))
+                .collect();
+            conflicting.sort();
+            let source_type = match info.tag.relativization {
+                RelativizationType::Global => "global",
+                RelativizationType::Relativized => "relativized",
+                RelativizationType::Directed => "directed",
+            };
+            output.push_str(&format!(
+                "{};{};{};{};{}\n",
+                state.id,
+                info.tag.format_with_symbols(&table),
+                conflicting.join(", "),
+                source_type,
+                automaton.trace_to(state.id).len()
+            ));
+        }
+
+        output
+    }
+
     pub fn dump_to_text(automaton: &Automaton) -> String {
         let mut output = String::new();
 
         // Obter symbol table
-        let symbol_table = SymbolTable::instance();
-        let table = symbol_table.lock().unwrap();
+        let table = SymbolTable::lock();
 
         // A: Ações
         let actions: Vec<String> = table
@@ -970,6 +2568,14 @@ impl AutomatonExporter {
         let states_str: Vec<String> = states_ids.iter().map(|id| id.to_string()).collect();
         output.push_str(&format!("Q:{}\n", states_str.join(";")));
 
+        // Q0: Estado inicial (vazio se não houver)
+        let initial_str = automaton
+            .initial
+            .as_ref()
+            .map(|s| s.id.to_string())
+            .unwrap_or_default();
+        output.push_str(&format!("Q0:{}\n", initial_str));
+
         // V: Estados violating
         let mut violations: Vec<usize> = automaton
             .states
@@ -997,23 +2603,15 @@ impl AutomatonExporter {
         let mut transitions_strs = Vec::new();
         for transition in automaton.transitions.iter() {
             let mut actions_parts = Vec::new();
-            let transition_actions = transition.actions();
-
-            for ra in transition_actions.iter() {
-                let sender_name = table
-                    .get_symbol_by_id(ra.sender)
-                    .map(|s| s.value.as_str())
-                    .unwrap_or("?");
+            for ra in transition.action_refs() {
+                let sender_name = table.individual_name(ra.sender);
 
                 let action_name = table
                     .get_symbol_by_id(ra.action.value)
                     .map(|s| s.value.as_str())
                     .unwrap_or("?");
 
-                let receiver_name = table
-                    .get_symbol_by_id(ra.receiver)
-                    .map(|s| s.value.as_str())
-                    .unwrap_or("?");
+                let receiver_name = table.individual_name(ra.receiver);
 
                 actions_parts.push(format!("{}?{}?{}", sender_name, action_name, receiver_name));
             }
@@ -1032,8 +2630,15 @@ impl AutomatonExporter {
         output
     }
 
-    pub fn dump_to_min_dot(automaton: &Automaton) -> String {
-        let mut output = String::from("digraph contract {\nrankdir=LR;\n");
+    pub fn dump_to_min_dot(
+        automaton: &Automaton,
+        source: Option<&DotSource>,
+        tooltip_length: usize,
+        group_by_party: bool,
+    ) -> String {
+        let mut output =
+            Self::dot_header(automaton.states.len(), automaton.transitions.len(), source);
+        output.push_str("digraph contract {\nrankdir=LR;\n");
 
         output.push_str("node [shape = point, color=white, fontcolor=white]; start;\n");
 
@@ -1041,14 +2646,10 @@ impl AutomatonExporter {
             if state.situation == StateSituation::NotChecked
                 || state.situation == StateSituation::ConflictFree
             {
-                let tooltip = if let Some(ref clause) = state.clause {
-                    format!("{}", clause)
-                } else {
-                    String::from("")
-                };
+                let tooltip = Self::dot_tooltip(state.clause.as_ref(), tooltip_length);
                 output.push_str(&format!(
                     "node [shape = circle, color=black, fontcolor=black, tooltip=\"{}\"]; S{} ;\n",
-                    tooltip.replace("\"", "\\\""),
+                    tooltip,
                     state.id
                 ));
             }
@@ -1056,14 +2657,10 @@ impl AutomatonExporter {
 
         for state in automaton.states.iter() {
             if state.situation == StateSituation::Violating {
-                let tooltip = if let Some(ref clause) = state.clause {
-                    format!("{}", clause)
-                } else {
-                    String::from("")
-                };
+                let tooltip = Self::dot_tooltip(state.clause.as_ref(), tooltip_length);
                 output.push_str(&format!(
                     "node [shape = circle, color=red, fontcolor=white, style=filled, fillcolor=red, tooltip=\"{}\"]; S{} ;\n",
-                    tooltip.replace("\"", "\\\""),
+                    tooltip,
                     state.id
                 ));
             }
@@ -1071,14 +2668,10 @@ impl AutomatonExporter {
 
         for state in automaton.states.iter() {
             if state.situation == StateSituation::Satisfaction {
-                let tooltip = if let Some(ref clause) = state.clause {
-                    format!("{}", clause)
-                } else {
-                    String::from("")
-                };
+                let tooltip = Self::dot_tooltip(state.clause.as_ref(), tooltip_length);
                 output.push_str(&format!(
                     "node [shape = circle, color=green, fontcolor=white, style=filled, fillcolor=green, tooltip=\"{}\"]; S{} ;\n",
-                    tooltip.replace("\"", "\\\""),
+                    tooltip,
                     state.id
                 ));
             }
@@ -1086,14 +2679,10 @@ impl AutomatonExporter {
 
         for state in automaton.states.iter() {
             if state.situation == StateSituation::Conflicting {
-                let tooltip = if let Some(ref clause) = state.clause {
-                    format!("{}", clause)
-                } else {
-                    String::from("")
-                };
+                let tooltip = Self::dot_tooltip(state.clause.as_ref(), tooltip_length);
                 output.push_str(&format!(
                     "node [shape = circle, color=orange, fontcolor=white, style=filled, fillcolor=orange, tooltip=\"{}\"]; S{} ;\n",
-                    tooltip.replace("\"", "\\\""),
+                    tooltip,
                     state.id
                 ));
             }
@@ -1103,36 +2692,36 @@ impl AutomatonExporter {
             output.push_str(&format!("start -> S{}\n", initial.id));
         }
 
-        let mut transition_map: HashMap<(usize, usize), Vec<std::sync::Arc<RelativizedAction>>> =
-            HashMap::new();
+        // Por par de estados, a transição com mais ações (sem clonar as ações)
+        let mut transition_map: HashMap<(usize, usize), &Transition> = HashMap::new();
 
         for transition in automaton.transitions.iter() {
             let key = (transition.from, transition.to);
 
             transition_map
                 .entry(key)
-                .and_modify(|existing_actions| {
-                    if transition.actions().len() > existing_actions.len() {
-                        *existing_actions = transition.actions().clone();
+                .and_modify(|existing| {
+                    if transition.action_set.len() > existing.action_set.len() {
+                        *existing = transition;
                     }
                 })
-                .or_insert_with(|| transition.actions().clone());
+                .or_insert(transition);
         }
 
         let mut sorted_transitions: Vec<_> = transition_map.into_iter().collect();
         sorted_transitions.sort_by_key(|(k, _)| *k);
 
         {
-            let symbol_table = SymbolTable::instance();
-            let table = symbol_table.lock().unwrap();
+            let table = SymbolTable::lock();
 
-            for ((from, to), actions) in sorted_transitions {
-                let actions_str = Self::format_actions(&actions, &table);
+            for ((from, to), transition) in sorted_transitions {
+                let actions_str = Self::transition_label(transition, &table, group_by_party);
                 output.push_str(&format!(
-                    "\tS{} -> S{} [ label = \"{}\" ];\n",
+                    "\tS{} -> S{} [ label = \"{}\"{} ];\n",
                     from,
                     to,
-                    actions_str.replace("\"", "\\\"")
+                    actions_str.replace("\"", "\\\""),
+                    Self::self_loop_style(from, to)
                 ));
             }
         }
@@ -1141,23 +2730,826 @@ impl AutomatonExporter {
         output
     }
 
-    // ==================== Funções auxiliares ====================
+    /// Exporta em DOT apenas o subgrafo que leva aos estados de conflito
+    ///
+    /// Mantém os estados de algum caminho mais curto (`Automaton::trace_to`) do estado
+    /// inicial até cada estado de conflito, e as transições desses caminhos: é o
+    /// contraexemplo, sem o restante do autômato. Os estados de conflito são destacados;
+    /// os demais mantêm as cores de `dump_to_dot`. Sem conflitos, só o estado inicial
+    /// é desenhado.
+    ///
+    /// # Argumentos
+    /// * `automaton` - O autômato a exportar
+    pub fn dump_conflict_subgraph_dot(automaton: &Automaton) -> String {
+        Self::dump_conflict_subgraph_dot_with(automaton, None, DEFAULT_TOOLTIP_LENGTH, false)
+    }
 
-    fn format_actions(
-        actions: &[std::sync::Arc<RelativizedAction>],
-        symbol_table: &SymbolTable,
+    /// `dump_conflict_subgraph_dot` com as mesmas opções de `dump_to_dot_with`
+    ///
+    /// O cabeçalho conta só os estados e transições do subgrafo.
+    ///
+    /// # Argumentos
+    /// * `automaton` - O autômato a exportar
+    /// * `source` - Contrato de origem, registrado no comentário de cabeçalho
+    /// * `tooltip_length` - Máximo de caracteres da cláusula no tooltip (0 = sem limite)
+    /// * `group_by_party` - Se `true`, os rótulos agrupam as ações por emissor
+    pub fn dump_conflict_subgraph_dot_with(
+        automaton: &Automaton,
+        source: Option<&DotSource>,
+        tooltip_length: usize,
+        group_by_party: bool,
     ) -> String {
-        if actions.is_empty() {
-            return String::from("ε");
+        let mut transition_ids: BTreeSet<usize> = BTreeSet::new();
+        let mut state_ids: BTreeSet<usize> = BTreeSet::new();
+
+        if let Some(ref initial) = automaton.initial {
+            state_ids.insert(initial.id);
         }
 
-        let formatted: Vec<String> = actions
+        for state in automaton.get_conflicts() {
+            state_ids.insert(state.id);
+            for transition_id in automaton.trace_to(state.id) {
+                if let Some(transition) = automaton.get_transition_by_id(transition_id) {
+                    state_ids.insert(transition.from);
+                    state_ids.insert(transition.to);
+                    transition_ids.insert(transition_id);
+                }
+            }
+        }
+
+        let mut output = Self::dot_header(state_ids.len(), transition_ids.len(), source);
+        output.push_str("digraph conflicts {\nrankdir=LR;\n");
+
+        output.push_str("node [shape = point, color=white, fontcolor=white]; start;\n");
+
+        for &id in &state_ids {
+            let Some(state) = automaton.get_state_by_id(id) else {
+                continue;
+            };
+            let style = match state.situation {
+                StateSituation::Conflicting => {
+                    "shape = doublecircle, color=orange, fontcolor=white, style=filled, fillcolor=orange, penwidth=2"
+                }
+                StateSituation::Violating => {
+                    "shape = circle, color=red, fontcolor=white, style=filled, fillcolor=red"
+                }
+                StateSituation::Satisfaction => {
+                    "shape = circle, color=green, fontcolor=white, style=filled, fillcolor=green"
+                }
+                StateSituation::NotChecked | StateSituation::ConflictFree => {
+                    "shape = circle, color=black, fontcolor=black"
+                }
+            };
+            let tooltip = Self::dot_tooltip(state.clause.as_ref(), tooltip_length);
+            output.push_str(&format!(
+                "node [{}, tooltip=\"{}\"]; S{} ;\n",
+                style, tooltip, state.id
+            ));
+        }
+
+        if let Some(ref initial) = automaton.initial {
+            output.push_str(&format!("start -> S{}\n", initial.id));
+        }
+
+        {
+            let table = SymbolTable::lock();
+
+            for &id in &transition_ids {
+                let Some(transition) = automaton.get_transition_by_id(id) else {
+                    continue;
+                };
+                let actions_str = Self::transition_label(transition, &table, group_by_party);
+                output.push_str(&format!(
+                    "\tS{} -> S{} [ label = \"{}\"{} ];\n",
+                    transition.from,
+                    transition.to,
+                    actions_str.replace("\"", "\\\""),
+                    Self::self_loop_style(transition.from, transition.to)
+                ));
+            }
+        }
+
+        output.push_str("}\n");
+        output
+    }
+
+    /// Exporta o autômato no formato indicado
+    pub fn export(automaton: &Automaton, format: ExportFormat) -> String {
+        Self::export_with(automaton, format, None, false)
+    }
+
+    /// Exporta o autômato no formato indicado, registrando o contrato de origem nos
+    /// formatos que o suportam (DOT e JSON); `group_by_party` vale para DOT e Mermaid
+    pub fn export_with(
+        automaton: &Automaton,
+        format: ExportFormat,
+        source: Option<&DotSource>,
+        group_by_party: bool,
+    ) -> String {
+        match format {
+            ExportFormat::Dot => Self::dump_to_dot_with(
+                automaton,
+                false,
+                source,
+                DEFAULT_TOOLTIP_LENGTH,
+                group_by_party,
+            ),
+            ExportFormat::Json => Self::dump_to_json_with(automaton, source),
+            ExportFormat::Mermaid => Self::dump_to_mermaid_with(automaton, group_by_party),
+            ExportFormat::GraphMl => Self::dump_to_graphml(automaton),
+            ExportFormat::Text => Self::dump_to_text(automaton),
+            ExportFormat::Smv => Self::dump_to_smv(automaton),
+            ExportFormat::Html => Self::dump_to_html(automaton),
+        }
+    }
+
+    pub fn dump_to_json(automaton: &Automaton) -> String {
+        Self::dump_to_json_with(automaton, None)
+    }
+
+    /// Exporta o autômato em JSON; com `source`, inclui o objeto `contract`
+    /// (arquivo, impressão digital, título e descrição)
+    pub fn dump_to_json_with(automaton: &Automaton, source: Option<&DotSource>) -> String {
+        let mut states: Vec<_> = automaton.states.iter().collect();
+        states.sort_by_key(|s| s.id);
+
+        // Cláusulas formatadas antes de travar a tabela (o Display de Clause também a trava)
+        let states_json: Vec<serde_json::Value> = states
             .iter()
+            .map(|state| {
+                let clause_str = if let Some(ref clause) = state.clause {
+                    format!("{}", clause)
+                } else {
+                    String::new()
+                };
+                serde_json::json!({
+                    "id": state.id,
+                    "clause": clause_str,
+                    "situation": Self::situation_name(state.situation),
+                })
+            })
+            .collect();
+
+        let mut transitions: Vec<_> = automaton.transitions.iter().collect();
+        transitions.sort_by_key(|t| t.id);
+
+        let table = SymbolTable::lock();
+
+        let transitions_json: Vec<serde_json::Value> = transitions
+            .iter()
+            .map(|transition| {
+                let actions: Vec<String> = transition
+                    .action_refs()
+                    .map(|ra| ra.format_with_symbols(&table))
+                    .collect();
+                serde_json::json!({
+                    "id": transition.id,
+                    "from": transition.from,
+                    "to": transition.to,
+                    "actions": actions,
+                })
+            })
+            .collect();
+
+        let mut json = serde_json::json!({
+            "initial": automaton.initial.as_ref().map(|s| s.id),
+            "states": states_json,
+            "transitions": transitions_json,
+            "conflict_found": automaton.conflict_found,
+        });
+        if let Some(source) = source {
+            json["contract"] = serde_json::json!({
+                "file": source.contract_file,
+                "fingerprint": source.fingerprint,
+                "title": source.title,
+                "description": source.description,
+            });
+        }
+
+        serde_json::to_string_pretty(&json).unwrap_or_default()
+    }
+
+    pub fn dump_to_mermaid(automaton: &Automaton) -> String {
+        Self::dump_to_mermaid_with(automaton, false)
+    }
+
+    /// Exporta o autômato como fluxograma Mermaid; com `group_by_party`, os rótulos
+    /// agrupam as ações por emissor
+    pub fn dump_to_mermaid_with(automaton: &Automaton, group_by_party: bool) -> String {
+        let mut output = String::from("flowchart LR\n");
+
+        let mut states: Vec<_> = automaton.states.iter().collect();
+        states.sort_by_key(|s| s.id);
+
+        output.push_str("    start(( ))\n");
+        for state in &states {
+            output.push_str(&format!("    S{}((S{}))\n", state.id, state.id));
+        }
+
+        if let Some(ref initial) = automaton.initial {
+            output.push_str(&format!("    start --> S{}\n", initial.id));
+        }
+
+        let mut transitions: Vec<_> = automaton.transitions.iter().collect();
+        transitions.sort_by_key(|t| t.id);
+
+        {
+            let table = SymbolTable::lock();
+
+            for transition in transitions {
+                let actions_str = Self::transition_label(transition, &table, group_by_party);
+                output.push_str(&format!(
+                    "    S{} -->|\"{}\"| S{}\n",
+                    transition.from,
+                    actions_str.replace("\"", "#quot;"),
+                    transition.to
+                ));
+            }
+        }
+
+        output.push_str("    classDef violating fill:#ff0000,color:#ffffff\n");
+        output.push_str("    classDef satisfaction fill:#008000,color:#ffffff\n");
+        output.push_str("    classDef conflicting fill:#ffa500,color:#ffffff\n");
+
+        for state in &states {
+            let class = match state.situation {
+                StateSituation::Violating => "violating",
+                StateSituation::Satisfaction => "satisfaction",
+                StateSituation::Conflicting => "conflicting",
+                _ => continue,
+            };
+            output.push_str(&format!("    class S{} {}\n", state.id, class));
+        }
+
+        output
+    }
+
+    /// Exporta o autômato como um módulo NuSMV (sistema de transições)
+    ///
+    /// A variável `state` enumera os ids dos estados; `init(state)` é o estado inicial e
+    /// `next(state)` tem um caso por estado de origem, com o conjunto de destinos.
+    /// Estados sem saída permanecem em si mesmos, já que o NuSMV exige relação total.
+    /// As situações viram `DEFINE`s booleanos (`satisfaction`, `violating`, `conflicting`)
+    /// para uso em especificações CTL/LTL.
+    ///
+    /// # Argumentos
+    /// * `automaton` - Autômato a exportar
+    ///
+    /// # Retorna
+    /// O texto do módulo `main`
+    pub fn dump_to_smv(automaton: &Automaton) -> String {
+        let mut output = String::from("MODULE main\n");
+
+        let mut ids: Vec<usize> = automaton.states.iter().map(|s| s.id).collect();
+        ids.sort_unstable();
+        if ids.is_empty() {
+            return output;
+        }
+
+        let id_set = |ids: &[usize]| {
+            let list: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+            format!("{{{}}}", list.join(", "))
+        };
+
+        output.push_str("VAR\n");
+        output.push_str(&format!("    state : {};\n", id_set(&ids)));
+
+        output.push_str("ASSIGN\n");
+        if let Some(ref initial) = automaton.initial {
+            output.push_str(&format!("    init(state) := {};\n", initial.id));
+        }
+
+        let successors = automaton.successor_map();
+        output.push_str("    next(state) := case\n");
+        for id in &ids {
+            if let Some(targets) = successors.get(id) {
+                let mut targets = targets.clone();
+                targets.sort_unstable();
+                targets.dedup();
+                output.push_str(&format!("        state = {} : {};\n", id, id_set(&targets)));
+            }
+        }
+        output.push_str("        TRUE : state;\n");
+        output.push_str("    esac;\n");
+
+        output.push_str("DEFINE\n");
+        for (name, situation) in [
+            ("satisfaction", StateSituation::Satisfaction),
+            ("violating", StateSituation::Violating),
+            ("conflicting", StateSituation::Conflicting),
+        ] {
+            let mut matching: Vec<usize> = automaton
+                .states
+                .iter()
+                .filter(|s| s.situation == situation)
+                .map(|s| s.id)
+                .collect();
+            matching.sort_unstable();
+            if matching.is_empty() {
+                output.push_str(&format!("    {} := FALSE;\n", name));
+            } else {
+                output.push_str(&format!("    {} := state in {};\n", name, id_set(&matching)));
+            }
+        }
+
+        output
+    }
+
+    pub fn dump_to_graphml(automaton: &Automaton) -> String {
+        let mut output = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        output.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+        output.push_str(
+            "  <key id=\"clause\" for=\"node\" attr.name=\"clause\" attr.type=\"string\"/>\n",
+        );
+        output.push_str("  <key id=\"situation\" for=\"node\" attr.name=\"situation\" attr.type=\"string\"/>\n");
+        output.push_str("  <key id=\"initial\" for=\"node\" attr.name=\"initial\" attr.type=\"boolean\"/>\n");
+        output.push_str(
+            "  <key id=\"label\" for=\"edge\" attr.name=\"label\" attr.type=\"string\"/>\n",
+        );
+        output.push_str("  <graph id=\"contract\" edgedefault=\"directed\">\n");
+
+        let initial_id = automaton.initial.as_ref().map(|s| s.id);
+
+        let mut states: Vec<_> = automaton.states.iter().collect();
+        states.sort_by_key(|s| s.id);
+
+        for state in states {
+            let clause_str = if let Some(ref clause) = state.clause {
+                format!("{}", clause)
+            } else {
+                String::new()
+            };
+            output.push_str(&format!("    <node id=\"S{}\">\n", state.id));
+            output.push_str(&format!(
+                "      <data key=\"clause\">{}</data>\n",
+                Self::escape_xml(&clause_str)
+            ));
+            output.push_str(&format!(
+                "      <data key=\"situation\">{}</data>\n",
+                Self::situation_name(state.situation)
+            ));
+            output.push_str(&format!(
+                "      <data key=\"initial\">{}</data>\n",
+                initial_id == Some(state.id)
+            ));
+            output.push_str("    </node>\n");
+        }
+
+        let mut transitions: Vec<_> = automaton.transitions.iter().collect();
+        transitions.sort_by_key(|t| t.id);
+
+        {
+            let table = SymbolTable::lock();
+
+            for transition in transitions {
+                let actions_str = Self::format_actions(transition.action_refs(), &table);
+                output.push_str(&format!(
+                    "    <edge id=\"T{}\" source=\"S{}\" target=\"S{}\">\n",
+                    transition.id, transition.from, transition.to
+                ));
+                output.push_str(&format!(
+                    "      <data key=\"label\">{}</data>\n",
+                    Self::escape_xml(&actions_str)
+                ));
+                output.push_str("    </edge>\n");
+            }
+        }
+
+        output.push_str("  </graph>\n");
+        output.push_str("</graphml>\n");
+        output
+    }
+
+    /// Página HTML autocontida para quem não tem leitor de DOT/JSON: métricas, o
+    /// diagrama como SVG embutido, a lista de conflitos e o rastro até cada conflito
+    ///
+    /// Não depende de nada externo (sem scripts nem folhas de estilo remotas). Os estados
+    /// ficam num círculo em ordem de id, coloridos pela situação como no DOT; o rótulo
+    /// completo de cada aresta aparece ao passar o mouse.
+    ///
+    /// # Argumentos
+    /// * `automaton` - O autômato a exportar
+    ///
+    /// # Retorna
+    /// O documento HTML completo
+    pub fn dump_to_html(automaton: &Automaton) -> String {
+        let counts = automaton.situation_counts();
+        // Formatados antes de travar a tabela (o Display de Clause também a trava)
+        let mut states: Vec<_> = automaton.states.iter().collect();
+        states.sort_by_key(|s| s.id);
+        let clauses: FxHashMap<usize, String> = states
+            .iter()
+            .map(|s| (s.id, s.clause.as_ref().map(|c| c.to_string()).unwrap_or_default()))
+            .collect();
+        let trace = Logger::strip_ansi_codes(&print_trace(automaton));
+
+        let mut output = String::from("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n");
+        output.push_str("<meta charset=\"UTF-8\">\n<title>Contract automaton</title>\n<style>\n");
+        output.push_str("body { font-family: sans-serif; margin: 2em; color: #222; }\n");
+        output.push_str("table { border-collapse: collapse; }\n");
+        output.push_str("td, th { border: 1px solid #ccc; padding: 4px 10px; text-align: left; }\n");
+        output.push_str("pre { background: #f5f5f5; padding: 1em; overflow-x: auto; }\n");
+        output.push_str("svg text { font-size: 12px; }\n");
+        output.push_str("</style>\n</head>\n<body>\n<h1>Contract automaton</h1>\n");
+
+        output.push_str(&format!(
+            "<p><strong>{}</strong></p>\n",
+            if automaton.conflict_found {
+                "A conflict was found in the analyzed contract."
+            } else {
+                "The analyzed contract is conflict-free."
+            }
+        ));
+
+        output.push_str("<h2>Metrics</h2>\n<table>\n");
+        let metrics = [
+            ("States", automaton.states.len()),
+            ("Transitions", automaton.transitions.len()),
+            ("Satisfaction states", counts.satisfaction),
+            ("Violating states", counts.violating),
+            ("Conflicting states", counts.conflicting),
+            ("Conflict-free states", counts.conflict_free),
+            ("Not checked states", counts.not_checked),
+            ("Self-loops", automaton.self_loops().len()),
+        ];
+        for (name, value) in metrics {
+            output.push_str(&format!("<tr><th>{}</th><td>{}</td></tr>\n", name, value));
+        }
+        output.push_str("</table>\n");
+
+        output.push_str("<h2>Diagram</h2>\n");
+        output.push_str(&Self::html_svg(automaton, &states, &clauses));
+
+        output.push_str("<h2>Conflicts</h2>\n");
+        let conflicts = automaton.get_conflicts();
+        if conflicts.is_empty() {
+            output.push_str("<p>None.</p>\n");
+        } else {
+            let mut conflicts = conflicts;
+            conflicts.sort_by_key(|s| s.id);
+            let table = SymbolTable::lock();
+            output.push_str("<ul>\n");
+            for state in conflicts {
+                let conflict = conflict_json(state, &table);
+                let conflicting: Vec<&str> = conflict["conflicting_tags"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|t| t.as_str())
+                    .collect();
+                output.push_str(&format!(
+                    "<li>S{}: {} conflicts with {}</li>\n",
+                    state.id,
+                    Self::escape_xml(conflict["tag"].as_str().unwrap_or("")),
+                    Self::escape_xml(&conflicting.join(", "))
+                ));
+            }
+            output.push_str("</ul>\n");
+        }
+
+        if automaton.conflict_found {
+            output.push_str("<h2>Trace</h2>\n");
+            output.push_str(&format!("<pre>{}</pre>\n", Self::escape_xml(trace.trim())));
+        }
+
+        output.push_str("</body>\n</html>\n");
+        output
+    }
+
+    /// Diagrama SVG de `dump_to_html`: estados num círculo, arestas com seta e, para
+    /// laços, um arco acima do estado
+    fn html_svg(
+        automaton: &Automaton,
+        states: &[&State],
+        clauses: &FxHashMap<usize, String>,
+    ) -> String {
+        const NODE_RADIUS: f64 = 18.0;
+        const MARGIN: f64 = 60.0;
+
+        let radius = (states.len() as f64 * 30.0).max(120.0);
+        let size = 2.0 * (radius + MARGIN);
+        let center = size / 2.0;
+        let positions: FxHashMap<usize, (f64, f64)> = states
+            .iter()
+            .enumerate()
+            .map(|(i, state)| {
+                if states.len() == 1 {
+                    return (state.id, (center, center));
+                }
+                let angle = 2.0 * std::f64::consts::PI * i as f64 / states.len() as f64
+                    - std::f64::consts::FRAC_PI_2;
+                (
+                    state.id,
+                    (center + radius * angle.cos(), center + radius * angle.sin()),
+                )
+            })
+            .collect();
+
+        let mut output = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0:.0}\" height=\"{0:.0}\" viewBox=\"0 0 {0:.0} {0:.0}\">\n",
+            size
+        );
+        output.push_str("<defs><marker id=\"arrow\" viewBox=\"0 0 10 10\" refX=\"10\" refY=\"5\" markerWidth=\"8\" markerHeight=\"8\" orient=\"auto\"><path d=\"M0,0 L10,5 L0,10 z\" fill=\"#555\"/></marker></defs>\n");
+
+        // Uma aresta por par de estados, com os rótulos de todas as transições no tooltip
+        let mut edges: BTreeMap<(usize, usize), Vec<String>> = BTreeMap::new();
+        {
+            let table = SymbolTable::lock();
+            let mut transitions: Vec<_> = automaton.transitions.iter().collect();
+            transitions.sort_by_key(|t| t.id);
+            for transition in transitions {
+                edges
+                    .entry((transition.from, transition.to))
+                    .or_default()
+                    .push(Self::format_actions(transition.action_refs(), &table));
+            }
+        }
+
+        for ((from, to), labels) in &edges {
+            let (Some(&(x1, y1)), Some(&(x2, y2))) = (positions.get(from), positions.get(to))
+            else {
+                continue;
+            };
+            let title = Self::escape_xml(&labels.join(" | "));
+            if from == to {
+                output.push_str(&format!(
+                    "<path d=\"M{:.1},{:.1} C{:.1},{:.1} {:.1},{:.1} {:.1},{:.1}\" fill=\"none\" stroke=\"#555\" stroke-dasharray=\"4\" marker-end=\"url(#arrow)\"><title>{}</title></path>\n",
+                    x1 - 8.0,
+                    y1 - NODE_RADIUS,
+                    x1 - 30.0,
+                    y1 - 60.0,
+                    x1 + 30.0,
+                    y1 - 60.0,
+                    x1 + 8.0,
+                    y1 - NODE_RADIUS,
+                    title
+                ));
+                continue;
+            }
+            let (dx, dy) = (x2 - x1, y2 - y1);
+            let length = (dx * dx + dy * dy).sqrt().max(1.0);
+            let (ux, uy) = (dx / length, dy / length);
+            output.push_str(&format!(
+                "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"#555\" marker-end=\"url(#arrow)\"><title>{}</title></line>\n",
+                x1 + ux * NODE_RADIUS,
+                y1 + uy * NODE_RADIUS,
+                x2 - ux * NODE_RADIUS,
+                y2 - uy * NODE_RADIUS,
+                title
+            ));
+        }
+
+        let initial_id = automaton.initial.as_ref().map(|s| s.id);
+        for state in states {
+            let Some(&(x, y)) = positions.get(&state.id) else {
+                continue;
+            };
+            let (fill, text) = match state.situation {
+                StateSituation::Violating => ("red", "white"),
+                StateSituation::Satisfaction => ("green", "white"),
+                StateSituation::Conflicting => ("orange", "white"),
+                StateSituation::ConflictFree | StateSituation::NotChecked => ("white", "black"),
+            };
+            let stroke_width = if initial_id == Some(state.id) { 3 } else { 1 };
+            output.push_str(&format!(
+                "<g><title>S{} ({}): {}</title><circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{}\" fill=\"{}\" stroke=\"black\" stroke-width=\"{}\"/><text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\" dominant-baseline=\"central\" fill=\"{}\">S{}</text></g>\n",
+                state.id,
+                Self::situation_name(state.situation),
+                Self::escape_xml(clauses.get(&state.id).map_or("", |c| c.as_str())),
+                x,
+                y,
+                NODE_RADIUS,
+                fill,
+                stroke_width,
+                x,
+                y,
+                text,
+                state.id
+            ));
+        }
+
+        output.push_str("</svg>\n<p>The initial state has a thick border; hover over states and edges for clauses and actions.</p>\n");
+        output
+    }
+
+    // ==================== Funções auxiliares ====================
+
+    fn situation_name(situation: StateSituation) -> &'static str {
+        match situation {
+            StateSituation::Violating => "violating",
+            StateSituation::Satisfaction => "satisfaction",
+            StateSituation::Conflicting => "conflicting",
+            StateSituation::ConflictFree => "conflictFree",
+            StateSituation::NotChecked => "notChecked",
+        }
+    }
+
+    fn escape_xml(text: &str) -> String {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    }
+
+    fn format_actions<'a>(
+        actions: impl IntoIterator<Item = &'a std::sync::Arc<RelativizedAction>>,
+        symbol_table: &SymbolTable,
+    ) -> String {
+        let formatted: Vec<String> = actions
+            .into_iter()
             .map(|ra| ra.format_with_symbols(symbol_table))
             .collect();
 
+        if formatted.is_empty() {
+            return String::from("ε");
+        }
+
         formatted.join(", ")
     }
+
+    /// Atributo extra das arestas DOT que não mudam o estado (tracejadas)
+    fn self_loop_style(from: usize, to: usize) -> &'static str {
+        if from == to {
+            ", style = dashed"
+        } else {
+            ""
+        }
+    }
+
+    /// Rótulo de uma transição: lista plana `(s, a, r)` ou agrupada por emissor
+    fn transition_label(
+        transition: &Transition,
+        symbol_table: &SymbolTable,
+        group_by_party: bool,
+    ) -> String {
+        if group_by_party {
+            Self::format_actions_by_party(transition.action_refs(), symbol_table)
+        } else {
+            Self::format_actions(transition.action_refs(), symbol_table)
+        }
+    }
+
+    /// Formata as ações agrupadas por emissor, na ordem em que cada emissor aparece
+    /// (`buyer: {pay}; seller: {ship}`)
+    ///
+    /// Dentro do grupo só aparece a ação (com `!` se negada); o receptor é omitido.
+    /// Ações sem emissor ficam no grupo `GLOBAL`.
+    ///
+    /// # Argumentos
+    /// * `actions` - Ações da transição
+    /// * `symbol_table` - Tabela de símbolos (já travada pelo chamador)
+    ///
+    /// # Retorna
+    /// O rótulo agrupado, ou `ε` se não houver ações
+    fn format_actions_by_party<'a>(
+        actions: impl IntoIterator<Item = &'a std::sync::Arc<RelativizedAction>>,
+        symbol_table: &SymbolTable,
+    ) -> String {
+        let mut groups: Vec<(&str, Vec<String>)> = Vec::new();
+        for ra in actions {
+            let sender = symbol_table.individual_name(ra.sender);
+            let action = if ra.negation {
+                format!("!{}", ra.action.format_with_symbols(symbol_table))
+            } else {
+                ra.action.format_with_symbols(symbol_table)
+            };
+            match groups.iter_mut().find(|(name, _)| *name == sender) {
+                Some((_, list)) => list.push(action),
+                None => groups.push((sender, vec![action])),
+            }
+        }
+
+        if groups.is_empty() {
+            return String::from("ε");
+        }
+
+        groups
+            .iter()
+            .map(|(sender, list)| format!("{}: {{{}}}", sender, list.join(", ")))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+// ==================== recallrc ====================
+
+/// Arquivo de opções lido antes da linha de comando (ver `apply_rc_file`)
+pub const RC_FILE_NAME: &str = ".recallrc";
+
+/// Chaves booleanas do `.recallrc` e a opção equivalente
+const RC_FLAGS: [(&str, &str); 3] = [
+    ("verbose", "-v"),
+    ("no_pruning", "-n"),
+    ("continue", "-c"),
+];
+
+/// Chaves numéricas do `.recallrc` e a opção equivalente (seguida do valor)
+const RC_NUMBERS: [(&str, &str); 7] = [
+    ("threads", "--threads"),
+    ("batch_size", "--batch-size"),
+    ("tooltip_length", "--tooltip-length"),
+    ("max_clause_size", "--max-clause-size"),
+    ("max_action_depth", "--max-action-depth"),
+    ("timeout", "--timeout"),
+    ("memory_limit", "--memory-limit"),
+];
+
+/// Acrescenta aos argumentos as opções do `.recallrc` (TOML), se houver
+///
+/// Procura o arquivo na pasta do contrato e, se não houver, em `recall/.recallrc` dentro
+/// da pasta de configuração do usuário (`dirs::config_dir`: `~/.config` no Linux,
+/// `~/Library/Application Support` no macOS, `%APPDATA%` no Windows). As opções do arquivo entram logo após o contrato, antes
+/// das da linha de comando, então estas prevalecem: um valor repetido na linha de comando
+/// substitui o do arquivo, e os booleanos se desfazem com `--no-verbose`, `--prunning` e
+/// `--no-continue`. `--no-rc` ignora o arquivo. Chaves aceitas: `verbose`,
+/// `no_pruning`, `continue` (booleanos), `threads`, `batch_size`, `tooltip_length`,
+/// `max_clause_size`, `max_action_depth`, `timeout` (segundos), `memory_limit` (MB)
+/// (números), `formats` (lista ou texto separado
+/// por vírgulas) e `global_log` (texto).
+///
+/// # Argumentos
+/// * `args` - Argumentos da linha de comando, começando pelo contrato
+///
+/// # Retorna
+/// Os argumentos a passar para `parse_command_line`, ou a mensagem de erro do arquivo
+pub fn apply_rc_file(args: &[String]) -> Result<Vec<String>, String> {
+    let folder = args
+        .first()
+        .and_then(|contract| LogPath::new(contract).parent())
+        .unwrap_or(LogPath::new(""))
+        .to_path_buf();
+    apply_rc_file_in(args, &folder)
+}
+
+/// `apply_rc_file` procurando o `.recallrc` local em `folder`, e não na pasta do
+/// contrato (ex.: `--clause`, cujo contrato fica no diretório temporário)
+///
+/// # Argumentos
+/// * `args` - Argumentos da linha de comando, começando pelo contrato
+/// * `folder` - Pasta do `.recallrc` local
+pub fn apply_rc_file_in(args: &[String], folder: &LogPath) -> Result<Vec<String>, String> {
+    let mut args = args.to_vec();
+    if let Some(index) = args.iter().position(|arg| arg == "--no-rc") {
+        args.remove(index);
+        return Ok(args);
+    }
+    if args.is_empty() || args[0].starts_with('-') {
+        return Ok(args);
+    }
+
+    let local = folder.join(RC_FILE_NAME);
+    let user = dirs::config_dir().map(|dir| dir.join("recall").join(RC_FILE_NAME));
+    let Some(path) = std::iter::once(local).chain(user).find(|p| p.is_file()) else {
+        return Ok(args);
+    };
+
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let rc_args = parse_rc(&content).map_err(|e| format!("{}: {}", path.display(), e))?;
+    args.splice(1..1, rc_args);
+    Ok(args)
+}
+
+/// Converte o conteúdo de um `.recallrc` em opções de linha de comando
+fn parse_rc(content: &str) -> Result<Vec<String>, String> {
+    let table: toml::Table = content.parse().map_err(|e: toml::de::Error| e.to_string())?;
+    let mut args = Vec::new();
+
+    for (key, value) in &table {
+        if let Some((_, flag)) = RC_FLAGS.iter().find(|(name, _)| name == key) {
+            match value.as_bool() {
+                Some(true) => args.push(flag.to_string()),
+                Some(false) => {}
+                None => return Err(format!("{} must be true or false", key)),
+            }
+        } else if let Some((_, option)) = RC_NUMBERS.iter().find(|(name, _)| name == key) {
+            match value.as_integer().filter(|n| *n >= 0) {
+                Some(number) => {
+                    args.push(option.to_string());
+                    args.push(number.to_string());
+                }
+                None => return Err(format!("{} must be a non-negative number", key)),
+            }
+        } else if key == "formats" {
+            let names: Vec<String> = match value {
+                toml::Value::String(list) => vec![list.clone()],
+                toml::Value::Array(items) => items
+                    .iter()
+                    .map(|item| item.as_str().map(|s| s.to_string()))
+                    .collect::<Option<_>>()
+                    .ok_or("formats must be a list of format names")?,
+                _ => return Err("formats must be a list of format names".to_string()),
+            };
+            args.push(format!("--format={}", names.join(",")));
+        } else if key == "global_log" {
+            let mode = value.as_str().ok_or("global_log must be a text")?;
+            args.push(format!("--global-log={}", mode));
+        } else {
+            return Err(format!("unknown key {}", key));
+        }
+    }
+
+    Ok(args)
 }
 
 pub fn parse_command_line(args: &[String]) -> RunConfiguration {
@@ -1171,10 +3563,7 @@ pub fn parse_command_line(args: &[String]) -> RunConfiguration {
     let contract_path = LogPath::new(&args[0]);
     config.set_contract_file_name(args[0].clone());
 
-    let file_stem = contract_path
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .unwrap_or("contract");
+    let file_stem = FileUtil::contract_stem(&args[0]);
 
     let parent = contract_path.parent().unwrap_or(LogPath::new(""));
 
@@ -1252,6 +3641,102 @@ pub fn parse_command_line(args: &[String]) -> RunConfiguration {
             continue;
         }
 
+        if let Some(mode) = arg.strip_prefix("--global-log=") {
+            match GlobalLogMode::parse(mode) {
+                Some(mode) => config.set_global_log_mode(mode),
+                None => {
+                    eprintln!("Invalid global log mode: {}", mode);
+                    print_usage();
+                    std::process::exit(1);
+                }
+            }
+
+            i += 1;
+            continue;
+        }
+
+        if let Some(list) = arg.strip_prefix("--format=") {
+            let mut formats = Vec::new();
+            for name in list.split(',').filter(|n| !n.trim().is_empty()) {
+                match ExportFormat::parse(name) {
+                    Some(format) => {
+                        if format == ExportFormat::Dot {
+                            config.set_export_automaton(true);
+                        }
+                        if !formats.contains(&format) {
+                            formats.push(format);
+                        }
+                    }
+                    None => {
+                        eprintln!("Unknown export format: {}", name);
+                        print_usage();
+                        std::process::exit(1);
+                    }
+                }
+            }
+            config.set_export_formats(formats);
+
+            i += 1;
+            continue;
+        }
+
+        if let Some(list) = arg.strip_prefix("--emit-logs=") {
+            let mut log_types = Vec::new();
+            for name in list.split(',').filter(|n| !n.trim().is_empty()) {
+                match LogType::parse(name) {
+                    Some(log_type) => {
+                        if !log_types.contains(&log_type) {
+                            log_types.push(log_type);
+                        }
+                    }
+                    None => {
+                        eprintln!("Unknown log type: {}", name);
+                        print_usage();
+                        std::process::exit(1);
+                    }
+                }
+            }
+            config.set_emitted_log_types(log_types);
+
+            i += 1;
+            continue;
+        }
+
+        if let Some(limit) = arg.strip_prefix("--satisfaction-paths=") {
+            match limit.parse::<usize>() {
+                Ok(limit) => config.set_satisfaction_paths(Some(limit)),
+                Err(_) => {
+                    eprintln!("Invalid path limit: {}", limit);
+                    print_usage();
+                    std::process::exit(1);
+                }
+            }
+
+            i += 1;
+            continue;
+        }
+
+        if let Some(id) = arg.strip_prefix("--explain=") {
+            match id.trim_start_matches('T').parse::<usize>() {
+                Ok(id) => config.set_explain_transition(Some(id)),
+                Err(_) => {
+                    eprintln!("Invalid transition id: {}", id);
+                    print_usage();
+                    std::process::exit(1);
+                }
+            }
+
+            i += 1;
+            continue;
+        }
+
+        if let Some(trace_file) = arg.strip_prefix("--trace=") {
+            config.set_trace_file_name(Some(trace_file.to_string()));
+
+            i += 1;
+            continue;
+        }
+
         match arg.as_str() {
             "-h" | "--help" => {
                 print_usage();
@@ -1260,6 +3745,9 @@ pub fn parse_command_line(args: &[String]) -> RunConfiguration {
             "-v" | "--verbose" => {
                 config.set_log_level(LogLevel::Verbose);
             }
+            "--no-verbose" => {
+                config.set_log_level(LogLevel::Normal);
+            }
             "-g" => {
                 config.set_export_automaton(true);
                 config.set_export_decompositions(true);
@@ -1267,15 +3755,250 @@ pub fn parse_command_line(args: &[String]) -> RunConfiguration {
             "-n" | "--no-prunning" => {
                 config.set_use_prunning(false);
             }
+            "--prunning" => {
+                config.set_use_prunning(true);
+            }
             "-c" | "--continue" => {
                 config.set_continue_on_conflict(true);
             }
+            "--no-continue" => {
+                config.set_continue_on_conflict(false);
+            }
             "-m" => {
                 //config.set_export_automaton(true);
                 config.set_export_min_automaton(true);
             }
-            "-t" => {
-                config.set_test(true);
+            "-t" => {
+                config.set_test(true);
+            }
+            "--export-contract" => {
+                config.set_export_contract(true);
+            }
+            "--csv-only" => {
+                config.set_csv_only(true);
+            }
+            "--events" => {
+                config.set_events(true);
+            }
+            "--ast" => {
+                config.set_print_ast(true);
+            }
+            "--stats" => {
+                config.set_stats(true);
+            }
+            "--sort-symbols" => {
+                config.set_sort_symbols(true);
+            }
+            "--anonymize" => {
+                config.set_anonymize(true);
+            }
+            "--states" => {
+                config.set_print_states(true);
+            }
+            "--tags" => {
+                config.set_deontic_tags(true);
+            }
+            "--collapse-epsilon" => {
+                config.set_collapse_epsilon(true);
+            }
+            "--group-by-party" => {
+                config.set_group_by_party(true);
+            }
+            "--estimate" => {
+                config.set_estimate(true);
+            }
+            "--deny-warnings" => {
+                config.set_deny_warnings(true);
+            }
+            "--strict" => {
+                config.set_strict(true);
+            }
+            "--watch" => {
+                config.set_watch(true);
+            }
+            "--include-disabled" => {
+                config.set_include_disabled(true);
+            }
+            "--certificate" => {
+                config.set_certificate(true);
+            }
+            "--cycles" => {
+                config.set_cycles(true);
+            }
+            "--conflicts-csv" => {
+                config.set_conflicts_csv(true);
+            }
+            "--decision-table" => {
+                config.set_decision_table(true);
+            }
+            "--conflict-subgraph" => {
+                config.set_conflict_subgraph(true);
+            }
+            "--minimal-conflict" => {
+                config.set_minimal_conflict(true);
+            }
+            "--no-idle" => {
+                config.set_no_idle(true);
+            }
+            "--echo-command" => {
+                config.set_echo_command(true);
+            }
+            "--threads" => {
+                match args.get(i + 1).and_then(|v| v.parse::<usize>().ok()) {
+                    Some(threads) => config.set_threads(threads),
+                    None => {
+                        eprintln!("--threads requires a number (0 = all cores)");
+                        print_usage();
+                        std::process::exit(1);
+                    }
+                }
+                i += 1;
+            }
+            "--max-action-depth" => {
+                match args.get(i + 1).and_then(|v| v.parse::<usize>().ok()) {
+                    Some(depth) if depth > 0 => config.set_max_action_depth(depth),
+                    _ => {
+                        eprintln!("--max-action-depth requires a positive number");
+                        print_usage();
+                        std::process::exit(1);
+                    }
+                }
+                i += 1;
+            }
+            "--max-clause-size" => {
+                match args.get(i + 1).and_then(|v| v.parse::<usize>().ok()) {
+                    Some(size) if size > 0 => config.set_max_clause_size(Some(size)),
+                    _ => {
+                        eprintln!("--max-clause-size requires a positive number");
+                        print_usage();
+                        std::process::exit(1);
+                    }
+                }
+                i += 1;
+            }
+            "--timeout" => {
+                match args.get(i + 1).and_then(|v| v.parse::<u64>().ok()) {
+                    Some(seconds) if seconds > 0 => config.set_timeout(Some(seconds)),
+                    _ => {
+                        eprintln!("--timeout requires a positive number of seconds");
+                        print_usage();
+                        std::process::exit(1);
+                    }
+                }
+                i += 1;
+            }
+            "--memory-limit" => {
+                match args.get(i + 1).and_then(|v| v.parse::<u64>().ok()) {
+                    Some(mb) if mb > 0 => config.set_memory_limit(Some(mb)),
+                    _ => {
+                        eprintln!("--memory-limit requires a positive number of MB");
+                        print_usage();
+                        std::process::exit(1);
+                    }
+                }
+                i += 1;
+            }
+            "--break-on" => {
+                match args.get(i + 1) {
+                    Some(text) if !text.is_empty() => config.set_break_on(Some(text.clone())),
+                    _ => {
+                        eprintln!("--break-on requires a clause text");
+                        print_usage();
+                        std::process::exit(1);
+                    }
+                }
+                i += 1;
+            }
+            "--break-halt" => {
+                config.set_break_halt(true);
+            }
+            "--merge" => {
+                match args.get(i + 1) {
+                    Some(file) if !file.starts_with('-') => {
+                        config.set_merge_file_name(Some(file.clone()))
+                    }
+                    _ => {
+                        eprintln!("--merge requires a contract file");
+                        print_usage();
+                        std::process::exit(1);
+                    }
+                }
+                i += 1;
+            }
+            "--log-dir" => {
+                match args.get(i + 1) {
+                    Some(dir) if !dir.starts_with('-') => {
+                        let dir = LogPath::new(dir);
+                        config.set_result_file_name(
+                            dir.join(format!("{}.result", file_stem))
+                                .to_string_lossy()
+                                .into_owned(),
+                        );
+                        config.set_global_log_filename(
+                            dir.join(format!("{}.log", file_stem))
+                                .to_string_lossy()
+                                .into_owned(),
+                        );
+                    }
+                    _ => {
+                        eprintln!("--log-dir requires a directory");
+                        print_usage();
+                        std::process::exit(1);
+                    }
+                }
+                i += 1;
+            }
+            "--verify" => {
+                config.set_verify(true);
+            }
+            "--party-matched-conflicts" => {
+                config.set_party_matched_conflicts(true);
+            }
+            "--delimiter" => {
+                match args.get(i + 1).and_then(|v| parse_csv_delimiter(v)) {
+                    Some(delimiter) => config.set_csv_delimiter(delimiter),
+                    None => {
+                        eprintln!("--delimiter requires a single character (or \"tab\")");
+                        print_usage();
+                        std::process::exit(1);
+                    }
+                }
+                i += 1;
+            }
+            "--baseline" => {
+                match args.get(i + 1) {
+                    Some(file) if !file.starts_with('-') => {
+                        config.set_baseline_file_name(Some(file.clone()))
+                    }
+                    _ => {
+                        eprintln!("--baseline requires a CSV file");
+                        print_usage();
+                        std::process::exit(1);
+                    }
+                }
+                i += 1;
+            }
+            "--tooltip-length" => {
+                match args.get(i + 1).and_then(|v| v.parse::<usize>().ok()) {
+                    Some(length) => config.set_tooltip_length(length),
+                    None => {
+                        eprintln!("--tooltip-length requires a number (0 = no limit)");
+                        print_usage();
+                        std::process::exit(1);
+                    }
+                }
+                i += 1;
+            }
+            "--batch-size" => {
+                match args.get(i + 1).and_then(|v| v.parse::<usize>().ok()) {
+                    Some(size) => config.set_batch_size(size),
+                    None => {
+                        eprintln!("--batch-size requires a number (0 = automatic)");
+                        print_usage();
+                        std::process::exit(1);
+                    }
+                }
+                i += 1;
             }
             _ => {
                 eprintln!("Unknown option: {}", arg);
@@ -1296,23 +4019,121 @@ pub fn print_usage() {
     println!("    recall <CONTRACT_FILE> [OPTIONS]\n");
     println!("OPTIONS:");
     println!("    -h, --help          Print this message and exit");
+    println!("    --no-rc             Ignores the .recallrc file (contract folder, then recall/ in");
+    println!("                        the user config folder: ~/.config on Linux, ~/Library/");
+    println!("                        Application Support on macOS, %APPDATA% on Windows), whose");
+    println!("                        options come before these");
     println!("    -v, --verbose       Turn on the verbose mode");
     println!("    -g                  Exports the automaton into a graphviz file");
     println!("                        Default filename is <CONTRACT_FILE>.dot");
     println!("    -n, --no-prunning   Don't use the prunning method");
     println!("    -c, --continue      Continues the analysis if a conflict is found");
+    println!("    --no-verbose, --prunning, --no-continue");
+    println!("                        Undo -v, -n and -c (e.g. when set in the .recallrc)");
     println!("    -m                  Export minimized automaton");
-    println!("    -t                  Test mode (outputs CSV metrics)\n");
+    println!("    -t                  Test mode (outputs CSV metrics)");
+    println!("    --csv-only          Prints only the CSV metrics line; log lines still go to the");
+    println!("                        log files and errors to stderr");
+    println!("    --events            Prints live events (e.g. conflict-found) as EVENT_JSON lines,");
+    println!("                        for the interface");
+    println!("    --conflicts-csv     Writes one row per conflict to <contract>_conflicts.csv");
+    println!("    --decision-table    Writes each choice of actions in the initial state and its");
+    println!("                        outcome to <contract>_decisions.csv");
+    println!("    --conflict-subgraph Writes only the shortest paths to conflicting states");
+    println!("                        to <contract>_conflicts.dot");
+    println!("    --export-contract   Writes the initial clause, conflicts and fingerprint");
+    println!("                        to <contract>_contract.txt");
+    println!("    --stats             Prints contract statistics (incl. commented-out clauses)");
+    println!("    --include-disabled  Analyses clauses marked with @disabled as well");
+    println!("    --merge <FILE>      Analyses the union of the contract with FILE (clauses and conflicts)");
+    println!("    --sort-symbols      Numbers symbols by name instead of by parse order");
+    println!("    --anonymize         Renames actions to a1, a2, ... and individuals to p1, p2, ...");
+    println!("                        in the summary and every export");
+    println!("    --states            Prints the automaton states as an aligned table");
+    println!("    --tags              Lists the deontic tags in force in any state");
+    println!("    --collapse-epsilon  Merges states linked by epsilon transitions in the -g graph");
+    println!("    --group-by-party    Groups DOT/Mermaid transition labels by sender (buyer: {{pay}}; seller: {{ship}})");
+    println!("    --no-idle           Only declared actions get transitions: no implicit \"do nothing\"");
+    println!("                        branch, so an unmet obligation may have no violating transition");
+    println!("    --tooltip-length <N>");
+    println!("                        Truncates node tooltips in DOT graphs to N characters (default");
+    println!("                        200, 0 = no limit); -g also writes <contract>_clauses.txt");
+    println!("    --cycles            Reports cycles (strongly connected components) in the summary");
+    println!("    --echo-command      Writes the normalized command line and contract fingerprint");
+    println!("                        to the .result header, to reproduce the run later");
+    println!("    --certificate       Prints a conflict-free certificate instead of the plain verdict");
+    println!("    --deny-warnings     Exits with code 3 after the analysis if the contract has warnings");
+    println!("    --strict            Also warns about actions used with different relativizations");
+    println!("                        and about obligations and prohibitions without a penalty");
+    println!("    --ast               Prints the parsed clauses as an indented tree and exits");
+    println!("    --estimate          Prints a size estimate and exits without building the automaton");
+    println!("    --minimal-conflict  Prints a minimal set of clauses whose initial state conflicts and exits");
+    println!("    --delimiter <C>     Column delimiter of the CSV metrics line (default ';',");
+    println!("                        \"tab\" for a tab)");
+    println!("    --log-dir <DIR>     Writes the .result and .log files to DIR instead of next to");
+    println!("                        the contract");
+    println!("    --verify            Checks after the construction that no two states have");
+    println!("                        equivalent clauses (a state deduplication failure)");
+    println!("    --party-matched-conflicts");
+    println!("                        Declared global conflicts only hold between norms on the same");
+    println!("                        parties: O(A,a,B) no longer conflicts with O(C,b,D)");
+    println!("    --baseline <CSV>    Compares states, transitions and conflict count with the row");
+    println!("                        for this file in a batch report and exits with code 8 if");
+    println!("                        they differ");
+    println!("    --watch             Re-runs the analysis every time the contract file is saved");
+    println!("    --threads <N>       Caps the analysis at N threads (default 0 = all cores)");
+    println!("    --max-clause-size <N>");
+    println!("                        Aborts if a state's clause grows past N nodes");
+    println!("    --timeout <SECS>    Aborts the construction after SECS seconds (exit code 10)");
+    println!("    --memory-limit <MB> Aborts once the process uses more than MB megabytes (exit");
+    println!("                        code 137; default: derived from the system RAM and swap)");
+    println!("    --max-action-depth <N>");
+    println!("                        Rejects actions nested more than N levels deep (default 1000)");
+    println!("    --break-on <TEXT>   Dumps every new state whose clause contains TEXT (tags,");
+    println!("                        incoming transition, concurrent actions)");
+    println!("    --break-halt        Stops the construction at the first --break-on match");
+    println!("    --batch-size <N>    Transitions computed per parallel batch (default 500,");
+    println!("                        0 = based on the number of threads). Larger batches use");
+    println!("                        more cores but keep more states in memory at once");
+    println!("    --global-log=<MODE> How the shared log file is opened: append (default),");
+    println!("                        truncate, timestamp (one file per run) or rotate:<MB>");
+    println!("    --format=<LIST>     Exports the automaton in each listed format");
+    println!("                        (comma-separated: dot,json,mermaid,graphml,text,smv,html)");
+    println!("    --emit-logs=<LIST>  Log types sent to the interface as events (comma-separated:");
+    println!("                        minimal,necessary,additional; default minimal,necessary)");
+    println!("    --satisfaction-paths=<N>");
+    println!("                        Lists up to N simple paths to satisfaction states");
+    println!("    --explain=<ID>      Explains transition ID: its action set and the clauses");
+    println!("                        before and after it");
+    println!("    --trace=<FILE>      Runs the JSON trace in FILE over the automaton");
+    println!("                        (list of steps, each a list of [sender, action, receiver])\n");
     println!("EXAMPLES:");
     println!("    recall contract.rcl");
     println!("        Analyzes a contract in the file 'contract.rcl'");
     println!("    recall contract.rcl -g");
-    println!("        Analyzes the contract and writes automaton in a file\n");
+    println!("        Analyzes the contract and writes automaton in a file");
+    println!("    recall --example <DIR>");
+    println!("        Writes the bundled example contracts into DIR");
+    println!("    recall --clause \"O(a)_/O(b)/_\" -g");
+    println!("        Analyzes a single clause as a one-clause contract (written to the");
+    println!("        temp directory, where the exported files also go)\n");
     println!("Please report issues to: edson.luiz.pilati@uel.br / bonifacio@uel.br");
     println!("More information: https://recall-site.github.io/");
 }
 
-pub fn print_result(automaton: &Automaton, ms: u64, rss: u64, total: u64) -> String {
+/// Resumo final da análise
+///
+/// Se `certificate` for informado e o contrato for livre de conflitos, ele substitui a
+/// linha `[CONFLICT-FREE]` (ver `print_certificate`).
+pub fn print_result(
+    automaton: &Automaton,
+    ms: u64,
+    cpu_ms: u64,
+    rss: u64,
+    total: u64,
+    certificate: Option<&str>,
+    cycles: Option<&[Vec<usize>]>,
+) -> String {
     let mut output = String::new();
 
     output.push_str("\n-------------------------------------------------------\n\n");
@@ -1325,6 +4146,8 @@ pub fn print_result(automaton: &Automaton, ms: u64, rss: u64, total: u64) -> Str
             ConsoleColors::RESET
         ));
         output.push_str(&print_trace(automaton));
+    } else if let Some(certificate) = certificate {
+        output.push_str(certificate);
     } else {
         output.push_str(&format!(
             "{}[CONFLICT-FREE] {}The analyzed contract is conflict-free.{}\n",
@@ -1334,9 +4157,42 @@ pub fn print_result(automaton: &Automaton, ms: u64, rss: u64, total: u64) -> Str
         ));
     }
 
+    let counts = automaton.situation_counts();
+    output.push_str(&format!(
+        "\nStates: {} satisfaction, {} violating, {} conflicting, {} conflict-free, {} not checked\n",
+        counts.satisfaction,
+        counts.violating,
+        counts.conflicting,
+        counts.conflict_free,
+        counts.not_checked
+    ));
+    output.push_str(&format!(
+        "Self-loops: {} transition(s) that keep the state\n",
+        automaton.self_loops().len()
+    ));
+
+    if let Some(cycles) = cycles {
+        if cycles.is_empty() {
+            output.push_str("Cycles: none\n");
+        } else {
+            output.push_str(&format!(
+                "{}[LIVENESS] {}{} cycle(s) where an execution may never reach satisfaction:{}\n",
+                ConsoleColors::FG_YELLOW,
+                ConsoleColors::FG_WHITE,
+                cycles.len(),
+                ConsoleColors::RESET
+            ));
+            for cycle in cycles {
+                let states: Vec<String> = cycle.iter().map(|id| format!("s{}", id)).collect();
+                output.push_str(&format!("   - {{{}}}\n", states.join(", ")));
+            }
+        }
+    }
+
     output.push_str("\n-------------------------------------------------------\n");
 
     output.push_str(&format!("Completed in {}ms\n", ms));
+    output.push_str(&format!("CPU time: {}ms\n", cpu_ms));
     output.push_str(&format!("Max RAM: {}MB\n", rss));
     output.push_str(&format!("Max Total Memory: {}MB\n", total));
 
@@ -1345,6 +4201,119 @@ pub fn print_result(automaton: &Automaton, ms: u64, rss: u64, total: u64) -> Str
     output
 }
 
+/// Certificado de ausência de conflitos, para arquivamento
+///
+/// Lista a impressão digital do contrato, indivíduos, ações e a contagem de estados,
+/// atestando que todos os estados alcançáveis foram verificados.
+///
+/// # Argumentos
+/// * `automaton` - Autômato construído para o contrato
+/// * `contract` - O contrato analisado
+///
+/// # Retorna
+/// O certificado ou, se houver conflito ou algum estado não verificado (construção
+/// interrompida), as linhas informando por que ele não pode ser emitido
+pub fn print_certificate(automaton: &Automaton, contract: &Contract) -> String {
+    if automaton.conflict_found {
+        return String::from("No certificate: a conflict was found in the analyzed contract.\n");
+    }
+
+    let unchecked = automaton
+        .states
+        .iter()
+        .filter(|s| s.situation == StateSituation::NotChecked)
+        .count();
+    let conflicting = automaton.get_conflicts().len();
+    if unchecked > 0 || conflicting > 0 {
+        let mut refusal = String::new();
+        if conflicting > 0 {
+            refusal.push_str(&format!("No certificate: {} conflicting state(s).\n", conflicting));
+        }
+        if unchecked > 0 {
+            refusal.push_str(&format!(
+                "No certificate: {} reachable state(s) were not checked for conflicts.\n",
+                unchecked
+            ));
+        }
+        return refusal;
+    }
+
+    let fingerprint = contract.fingerprint();
+
+    let table = SymbolTable::lock();
+    let mut individuals: Vec<String> = contract
+        .individuals
+        .iter()
+        .map(|&id| {
+            table
+                .get_symbol_by_id(id)
+                .map(|s| s.value.clone())
+                .unwrap_or_else(|| id.to_string())
+        })
+        .collect();
+    individuals.sort();
+    let mut actions: Vec<String> = contract
+        .actions
+        .iter()
+        .map(|a| a.format_with_symbols(&table))
+        .collect();
+    actions.sort();
+    drop(table);
+
+    let mut output = String::new();
+    output.push_str(&format!(
+        "{}[CONFLICT-FREE CERTIFICATE]{}\n",
+        ConsoleColors::FG_GREEN,
+        ConsoleColors::RESET
+    ));
+    output.push_str(&format!("Contract: {}\n", fingerprint));
+    output.push_str(&format!("Issued: {}\n", Local::now().format("%Y-%m-%d %H:%M:%S")));
+    output.push_str(&format!("Individuals ({}): {}\n", individuals.len(), individuals.join(", ")));
+    output.push_str(&format!("Actions ({}): {}\n", actions.len(), actions.join(", ")));
+    output.push_str(&format!("Reachable states: {}\n", automaton.states.len()));
+    output.push_str(&format!("Transitions: {}\n", automaton.transitions.len()));
+    output.push_str("All reachable states were checked for conflicts and none is conflicting.\n");
+
+    output
+}
+
+/// Árvore de cada cláusula do contrato (`--ast`), em ordem alfabética do texto da
+/// cláusula para que a numeração não dependa da ordem do conjunto
+pub fn print_ast(contract: &Contract) -> String {
+    let mut output = String::new();
+
+    let mut clauses: Vec<&Clause> = contract.clauses.iter().collect();
+    clauses.sort_by_cached_key(|clause| clause.to_string());
+    for (index, clause) in clauses.into_iter().enumerate() {
+        output.push_str(&format!("Clause {}:\n", index + 1));
+        output.push_str(&clause.format_tree(1));
+    }
+
+    output
+}
+
+pub fn print_stats(contract: &Contract) -> String {
+    let mut output = String::new();
+
+    output.push_str("\n-------------------------------------------------------\n");
+    output.push_str("Contract statistics\n");
+    output.push_str(&format!("Clauses: {}\n", contract.clauses.len()));
+    output.push_str(&format!("Disabled clauses: {}\n", contract.disabled_clauses.len()));
+    for clause in &contract.disabled_clauses {
+        output.push_str(&format!("   - {}\n", clause));
+    }
+    output.push_str(&format!("Individuals: {}\n", contract.individuals.len()));
+    output.push_str(&format!("Actions: {}\n", contract.actions.len()));
+    output.push_str(&format!(
+        "Conflicts: {} global, {} relativized\n",
+        contract.global_conflicts.len(),
+        contract.relativized_conflicts.len()
+    ));
+    output.push_str("-------------------------------------------------------\n");
+
+    output
+}
+
 pub fn print_trace(automaton: &Automaton) -> String {
     let mut output = String::new();
     output.push_str("\n-------------------------------------------------------\n");
@@ -1365,43 +4334,38 @@ pub fn print_trace(automaton: &Automaton) -> String {
 
         let mut current_state = state.clone();
 
-        while !current_state.trace.is_empty() {
-            if let Some(&transition_id) = current_state.trace.first() {
-                if let Some(transition) = automaton.get_transition_by_id(transition_id) {
-                    trace_summary.push_str(&format!("(s{})", transition.to));
+        while let Some(transition_id) = current_state.trace {
+            if let Some(transition) = automaton.get_transition_by_id(transition_id) {
+                trace_summary.push_str(&format!("(s{})", transition.to));
+
+                trace_details.push_str(&format!(
+                    "{}(s{}){}",
+                    ConsoleColors::FG_YELLOW,
+                    transition.to,
+                    ConsoleColors::RESET
+                ));
 
-                    trace_details.push_str(&format!(
-                        "{}(s{}){}",
-                        ConsoleColors::FG_YELLOW,
-                        transition.to,
-                        ConsoleColors::RESET
-                    ));
+                if let Some(to_state) = automaton.get_state_by_id(transition.to) {
+                    trace_details.push_str(&format!(" - {}\n", to_state));
+                }
 
-                    if let Some(to_state) = automaton.get_state_by_id(transition.to) {
-                        trace_details.push_str(&format!(" - {}\n", to_state));
-                    }
+                trace_details.push_str(&format!(
+                    "{}<T{}> - {}[{}]{}\n",
+                    ConsoleColors::FG_RED,
+                    transition.id,
+                    ConsoleColors::FG_BLUE,
+                    transition
+                        .action_refs()
+                        .map(|a| a.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    ConsoleColors::RESET
+                ));
 
-                    trace_details.push_str(&format!(
-                        "{}<T{}> - {}[{}]{}\n",
-                        ConsoleColors::FG_RED,
-                        transition.id,
-                        ConsoleColors::FG_BLUE,
-                        transition
-                            .actions()
-                            .iter()
-                            .map(|a| a.to_string())
-                            .collect::<Vec<_>>()
-                            .join(", "),
-                        ConsoleColors::RESET
-                    ));
-
-                    trace_summary.push_str(&format!("<--T{}--", transition.id));
-
-                    if let Some(from_state) = automaton.get_state_by_id(transition.from) {
-                        current_state = from_state.clone();
-                    } else {
-                        break;
-                    }
+                trace_summary.push_str(&format!("<--T{}--", transition.id));
+
+                if let Some(from_state) = automaton.get_state_by_id(transition.from) {
+                    current_state = from_state.clone();
                 } else {
                     break;
                 }
@@ -1433,36 +4397,325 @@ pub fn print_trace(automaton: &Automaton) -> String {
 
 pub fn get_automaton_data(
     time: u64,
+    cpu_time: u64,
     memory: u64,
     automaton: &Automaton,
     contract: &Contract,
+    delimiter: char,
 ) -> String {
     let automaton_size_mb = estimate_automaton_size(automaton) as f64 / (1024.0 * 1024.0);
 
-    format!(
-        "{};{};{};{};{};{};{};{:.2};{:.2};success",
-        time,
-        automaton.states.len(),
-        automaton.transitions.len(),
-        contract.individuals.len(),
-        contract.actions.len(),
-        if automaton.conflict_found { 1 } else { 0 },
-        automaton.get_conflicts().len(),
-        automaton_size_mb,
-        memory as f64
-    )
+    let counts = automaton.situation_counts();
+
+    [
+        time.to_string(),
+        automaton.states.len().to_string(),
+        automaton.transitions.len().to_string(),
+        contract.individuals.len().to_string(),
+        contract.actions.len().to_string(),
+        (if automaton.conflict_found { 1 } else { 0 }).to_string(),
+        automaton.get_conflicts().len().to_string(),
+        format!("{:.2}", automaton_size_mb),
+        format!("{:.2}", memory as f64),
+        counts.satisfaction.to_string(),
+        counts.violating.to_string(),
+        counts.conflicting.to_string(),
+        counts.conflict_free.to_string(),
+        counts.not_checked.to_string(),
+        cpu_time.to_string(),
+        "success".to_string(),
+    ]
+    .join(&delimiter.to_string())
+}
+
+/// Hash FNV-1a de 64 bits de `text`, em hexadecimal (16 dígitos)
+///
+/// Estável entre execuções e versões, ao contrário do `Hasher` padrão.
+pub fn fnv1a_hex(text: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in text.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
+/// Prepara um texto livre (nome de arquivo, mensagem de erro) para virar um campo CSV
+///
+/// O delimitador vira o outro separador comum (`,` ou `;`) e quebras de linha viram
+/// espaços, para que o campo não quebre as colunas da linha.
+///
+/// # Argumentos
+/// * `text` - Texto do campo
+/// * `delimiter` - Delimitador das colunas
+pub fn escape_csv_field(text: &str, delimiter: char) -> String {
+    let replacement = if delimiter == ',' { ";" } else { "," };
+    text.replace(delimiter, replacement)
+        .replace("\r\n", " ")
+        .replace('\n', " ")
+}
+
+/// Delimitador de um CSV gerado pela ferramenta, lido do cabeçalho
+///
+/// Os relatórios começam pela coluna `file`; o caractere seguinte é o delimitador.
+/// Sem cabeçalho reconhecível, assume `DEFAULT_CSV_DELIMITER`.
+pub fn detect_csv_delimiter(header: &str) -> char {
+    header
+        .trim_start()
+        .strip_prefix("file")
+        .and_then(|rest| rest.chars().next())
+        .unwrap_or(DEFAULT_CSV_DELIMITER)
+}
+
+/// Colunas de referência comparadas por `Baseline`, com a posição de cada uma na linha
+/// `RESULT_CSV` de `get_automaton_data`
+const BASELINE_COLUMNS: [(&str, usize); 3] = [("states", 1), ("transitions", 2), ("conflict_count", 6)];
+
+/// Métricas de referência de um relatório em lote (`<pasta>_<data>.csv`)
+///
+/// Usada por `--baseline` para conferir uma execução contra resultados gravados antes,
+/// seja desta versão ou da ferramenta original em Java. As colunas são localizadas pelo
+/// cabeçalho, então a ordem e a presença de outras colunas não importam.
+#[derive(Debug, Clone, Default)]
+pub struct Baseline {
+    rows: HashMap<String, Vec<String>>,
+}
+
+impl Baseline {
+    /// Lê um relatório em lote, com o delimitador indicado pelo cabeçalho
+    ///
+    /// # Argumentos
+    /// * `content` - Conteúdo do CSV, com cabeçalho
+    ///
+    /// # Retorna
+    /// Erro se o cabeçalho não tiver a coluna `file` ou alguma de `BASELINE_COLUMNS`
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let mut lines = content.lines();
+        let header_line = lines.next().unwrap_or_default();
+        let delimiter = detect_csv_delimiter(header_line);
+        let header: Vec<&str> = header_line.split(delimiter).map(str::trim).collect();
+        let column = |name: &str| {
+            header
+                .iter()
+                .position(|h| *h == name)
+                .ok_or_else(|| format!("Baseline has no '{}' column", name))
+        };
+
+        let file_column = column("file")?;
+        let columns = BASELINE_COLUMNS
+            .iter()
+            .map(|(name, _)| column(name))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut rows = HashMap::new();
+        for line in lines.filter(|l| !l.trim().is_empty()) {
+            let fields: Vec<&str> = line.split(delimiter).map(str::trim).collect();
+            let Some(file) = fields.get(file_column) else {
+                continue;
+            };
+            let values = columns
+                .iter()
+                .map(|&c| fields.get(c).copied().unwrap_or_default().to_string())
+                .collect();
+            rows.insert(file.to_string(), values);
+        }
+
+        Ok(Baseline { rows })
+    }
+
+    /// Compara uma linha `RESULT_CSV` com a referência do mesmo arquivo
+    ///
+    /// # Argumentos
+    /// * `file_name` - Nome do arquivo do contrato, sem o diretório, como no relatório
+    /// * `data` - Linha gerada por `get_automaton_data`
+    /// * `delimiter` - Delimitador usado em `data`
+    ///
+    /// # Retorna
+    /// `None` se o arquivo não está na referência; senão uma descrição por métrica
+    /// diferente (vazia quando tudo confere)
+    pub fn compare(&self, file_name: &str, data: &str, delimiter: char) -> Option<Vec<String>> {
+        let expected = self.rows.get(file_name)?;
+        let fields: Vec<&str> = data.split(delimiter).map(str::trim).collect();
+
+        Some(
+            BASELINE_COLUMNS
+                .iter()
+                .zip(expected)
+                .filter_map(|((name, index), expected)| {
+                    let actual = fields.get(*index).copied().unwrap_or_default();
+                    (actual != expected).then(|| format!("{}: {} (baseline {})", name, actual, expected))
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Lista os conflitos do autômato em JSON (linha `CONFLICTS_JSON:` do analisador)
+pub fn get_conflicts_json(automaton: &Automaton) -> String {
+    let mut conflicts = automaton.get_conflicts();
+    conflicts.sort_by_key(|s| s.id);
+
+    let table = SymbolTable::lock();
+
+    let entries: Vec<serde_json::Value> = conflicts
+        .iter()
+        .map(|state| conflict_json(state, &table))
+        .collect();
+
+    serde_json::Value::Array(entries).to_string()
+}
+
+/// Conflito de um estado como `{state, tag, conflicting_tags}`
+///
+/// Usado tanto na lista final (`CONFLICTS_JSON:`) quanto no evento `conflict-found`.
+pub fn conflict_json(state: &State, table: &SymbolTable) -> serde_json::Value {
+    let (tag, conflicting_tags) = match state.conflict_information {
+        Some(ref info) => {
+            let mut conflicting: Vec<String> = info
+                .conflicting_tags
+                .iter()
+                .map(|t| t.format_with_symbols(table))
+                .collect();
+            conflicting.sort();
+            (info.tag.format_with_symbols(table), conflicting)
+        }
+        None => (String::new(), Vec::new()),
+    };
+    serde_json::json!({
+        "state": state.id,
+        "tag": tag,
+        "conflicting_tags": conflicting_tags,
+    })
+}
+
+/// Lê um traço em JSON e o converte para ações relativizadas usando a tabela de símbolos
+///
+/// O arquivo contém uma lista de passos; cada passo é uma lista de triplas
+/// `[remetente, ação, destinatário]` com os nomes usados no contrato.
+///
+/// # Argumentos
+/// * `path` - Caminho do arquivo com o traço
+///
+/// # Retorna
+/// Os passos do traço ou uma mensagem de erro indicando o símbolo desconhecido
+pub fn load_trace(path: &str) -> Result<Vec<Vec<RelativizedAction>>, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read trace file {}: {}", path, e))?;
+    let steps: Vec<Vec<[String; 3]>> =
+        serde_json::from_str(&content).map_err(|e| format!("Invalid trace file: {}", e))?;
+
+    let table = SymbolTable::lock();
+    let lookup = |name: &str, symbol_type: SymbolType| {
+        table
+            .get_id(name, symbol_type)
+            .ok_or_else(|| format!("Unknown {} in trace: {}", symbol_type, name))
+    };
+
+    steps
+        .iter()
+        .map(|step| {
+            step.iter()
+                .map(|[sender, action, receiver]| {
+                    Ok(RelativizedAction::new(
+                        lookup(sender.as_str(), SymbolType::Individual)?,
+                        BasicAction::with_value(lookup(action.as_str(), SymbolType::Action)?),
+                        lookup(receiver.as_str(), SymbolType::Individual)?,
+                    ))
+                })
+                .collect::<Result<Vec<_>, String>>()
+        })
+        .collect()
+}
+
+/// Caminhos até estados de satisfação em JSON (linha `SATISFACTION_PATHS_JSON:` do analisador)
+///
+/// Cada caminho é uma lista de passos com a transição, os estados e as ações rotuladas.
+pub fn get_satisfaction_paths_json(automaton: &Automaton, paths: &[Vec<usize>]) -> String {
+    let table = SymbolTable::lock();
+    let entries: Vec<serde_json::Value> = paths
+        .iter()
+        .map(|path| {
+            let steps: Vec<serde_json::Value> = path
+                .iter()
+                .filter_map(|&id| automaton.get_transition_by_id(id))
+                .map(|t| {
+                    serde_json::json!({
+                        "transition": t.id,
+                        "from": t.from,
+                        "to": t.to,
+                        "actions": AutomatonExporter::format_actions(t.action_refs(), &table),
+                    })
+                })
+                .collect();
+            serde_json::Value::Array(steps)
+        })
+        .collect();
+
+    serde_json::Value::Array(entries).to_string()
+}
+
+/// Tags deônticas em JSON (linha `DEONTIC_TAGS_JSON:` do analisador)
+///
+/// Cada tag vira `{tag, type, party, action}`, ordenadas por parte e ação;
+/// `party` é `null` em tags globais.
+pub fn get_deontic_tags_json(tags: &FxHashSet<DeonticTag>) -> String {
+    let table = SymbolTable::lock();
+    let name = |id: i32| {
+        table
+            .get_symbol_by_id(id)
+            .map(|s| s.value.clone())
+            .unwrap_or_else(|| "?".to_string())
+    };
+
+    let mut entries: Vec<(Option<String>, String, serde_json::Value)> = tags
+        .iter()
+        .map(|tag| {
+            let party = match tag.relativization {
+                RelativizationType::Global => None,
+                _ => Some(name(tag.sender)),
+            };
+            let action = tag.action.format_with_symbols(&table);
+            let entry = serde_json::json!({
+                "tag": tag.format_with_symbols(&table),
+                "type": tag.deontic_type.short_symbol(),
+                "party": party,
+                "action": action,
+            });
+            (party, action, entry)
+        })
+        .collect();
+    entries.sort_by(|a, b| (&a.0, &a.1, a.2["tag"].as_str()).cmp(&(&b.0, &b.1, b.2["tag"].as_str())));
+
+    serde_json::Value::Array(entries.into_iter().map(|(_, _, entry)| entry).collect()).to_string()
+}
+
+/// Resultado de um traço em JSON (linha `TRACE_JSON:` do analisador)
+pub fn get_trace_json(outcome: &TraceOutcome) -> String {
+    let value = match *outcome {
+        TraceOutcome::Stuck { state, step } => serde_json::json!({
+            "outcome": outcome.name(),
+            "state": state,
+            "step": step,
+        }),
+        TraceOutcome::Satisfaction { state, steps }
+        | TraceOutcome::Violating { state, steps }
+        | TraceOutcome::Conflicting { state, steps }
+        | TraceOutcome::Pending { state, steps } => serde_json::json!({
+            "outcome": outcome.name(),
+            "state": state,
+            "steps": steps,
+        }),
+    };
+    value.to_string()
 }
 
 pub fn estimate_automaton_size(automaton: &Automaton) -> usize {
     let mut total = std::mem::size_of_val(automaton);
 
     let states_capacity = automaton.states.capacity();
+    // O trace é um `Option<usize>` dentro do próprio `State`, sem alocação à parte
     total += states_capacity * (std::mem::size_of::<State>() + 16);
 
-    for state in &automaton.states {
-        total += state.trace.capacity() * std::mem::size_of::<usize>();
-    }
-
     let transitions_capacity = automaton.transitions.capacity();
     total += transitions_capacity * (std::mem::size_of::<Transition>() + 16);
 
@@ -1472,6 +4725,29 @@ pub fn estimate_automaton_size(automaton: &Automaton) -> usize {
     total
 }
 
+/// Tempo de CPU consumido pelo processo até agora, somando todas as threads
+///
+/// Comparado ao tempo de relógio, indica o aproveitamento do paralelismo: com N
+/// threads ocupadas o tempo de CPU tende a N vezes o de relógio. Em plataformas onde
+/// o `sysinfo` não obtém o processo o valor é 0.
+///
+/// # Retorna
+/// O tempo de CPU em milissegundos
+pub fn process_cpu_time_ms() -> u64 {
+    use sysinfo::ProcessRefreshKind;
+
+    let Ok(pid) = sysinfo::get_current_pid() else {
+        return 0;
+    };
+    let mut sys = System::new();
+    sys.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[pid]),
+        true,
+        ProcessRefreshKind::nothing().with_cpu(),
+    );
+    sys.process(pid).map_or(0, |process| process.accumulated_cpu_time())
+}
+
 pub fn get_system_memory_info() -> (u64, u64) {
     use sysinfo::System;
 
@@ -1502,6 +4778,31 @@ pub fn calculate_safe_memory_limit(total_ram_mb: u64, total_swap_mb: u64) -> u64
     }
 }
 
+/// RAM e swap totais do sistema e o limite que o `MemoryGuard` do analisador aplica
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct SystemMemory {
+    pub ram_mb: u64,
+    pub swap_mb: u64,
+    pub safe_limit_mb: u64,
+}
+
+impl SystemMemory {
+    /// Memória informada e o limite calculado para ela (ver `calculate_safe_memory_limit`)
+    pub fn new(ram_mb: u64, swap_mb: u64) -> Self {
+        SystemMemory {
+            ram_mb,
+            swap_mb,
+            safe_limit_mb: calculate_safe_memory_limit(ram_mb, swap_mb),
+        }
+    }
+
+    /// Memória atual do sistema
+    pub fn current() -> Self {
+        let (ram_mb, swap_mb) = get_system_memory_info();
+        Self::new(ram_mb, swap_mb)
+    }
+}
+
 pub struct ConsoleColors;
 
 impl ConsoleColors {
@@ -1512,3 +4813,730 @@ impl ConsoleColors {
     pub const FG_BLUE: &'static str = "\u{001B}[34m";
     pub const FG_WHITE: &'static str = "\u{001B}[37m";
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{build, build_with, config, parse, serial, temp_dir, RecordingSink};
+
+    /// Symbols `(value, id)` and clauses of `src` parsed into an empty table,
+    /// renumbered with `normalize` when `sort` is set.
+    fn symbol_ids(src: &str, sort: bool) -> (Vec<(String, i32)>, FxHashSet<Clause>) {
+        SymbolTable::lock().clear();
+        let mut contract = parse(src);
+        if sort {
+            let ids = SymbolTable::lock().normalize();
+            contract.remap_symbols(&ids);
+        }
+        let table = SymbolTable::lock();
+        let mut symbols: Vec<(String, i32)> = table
+            .get_actions()
+            .into_iter()
+            .chain(table.get_individuals())
+            .map(|s| (s.value.clone(), s.id))
+            .collect();
+        symbols.sort();
+        (symbols, contract.clauses)
+    }
+
+    #[test]
+    fn sorted_symbol_ids_ignore_clause_order() {
+        let _table = serial();
+        let forward = "{buyer,seller}O(pay); {seller,buyer}F(cancel);";
+        let reordered = "{seller,buyer}F(cancel); {buyer,seller}O(pay);";
+
+        assert_ne!(symbol_ids(forward, false).0, symbol_ids(reordered, false).0);
+        assert_eq!(symbol_ids(forward, true), symbol_ids(reordered, true));
+    }
+
+    #[test]
+    fn states_table_aligns_columns() {
+        let _table = serial();
+        let automaton = build(
+            "{buyer,seller}O(pay);
+             {buyer,seller}[pay]({seller,buyer}O(deliver_the_goods_within_thirty_days_of_the_payment));",
+        );
+        let table = AutomatonExporter::dump_states_table(&automaton);
+        let lines: Vec<&str> = table.lines().collect();
+
+        let id_width = lines[1].find(' ').unwrap();
+        assert_eq!(lines[0], format!("{:>id_width$}  SIT  CLAUSE", "ID"));
+        assert_eq!(lines[1], format!("{}  ---  {}", "-".repeat(id_width), "-".repeat(60)));
+
+        let rows = &lines[2..2 + automaton.states.len()];
+        for row in rows {
+            let (id, rest) = row.split_at(id_width);
+            assert!(id.trim().parse::<usize>().is_ok(), "{}", row);
+            assert_eq!(&rest[..2], "  ", "{}", row);
+            assert!("VSCFN".contains(&rest[2..3]), "{}", row);
+            assert_eq!(&rest[3..7], "    ", "{}", row);
+            assert!(rest[7..].chars().count() <= 60, "{}", row);
+        }
+        assert!(rows.iter().any(|row| row.ends_with("...")));
+        assert!(table.contains("SIT: V = violating"));
+    }
+
+    #[test]
+    fn symbol_table_survives_a_poisoned_lock() {
+        let _table = serial();
+        let id = SymbolTable::lock().add_symbol("poison_probe".to_string(), SymbolType::Action);
+
+        let result = std::thread::spawn(|| {
+            let _guard = SymbolTable::lock();
+            panic!("panic while holding the symbol table");
+        })
+        .join();
+        assert!(result.is_err());
+        assert!(SymbolTable::instance().lock().is_err());
+
+        let table = SymbolTable::lock();
+        assert_eq!(table.get_id("poison_probe", SymbolType::Action), Some(id));
+    }
+
+    #[test]
+    fn collapsed_dot_merges_states_joined_by_epsilon() {
+        let _table = serial();
+        let (buyer, pay, seller) = {
+            let mut table = SymbolTable::lock();
+            (
+                table.add_symbol("buyer".to_string(), SymbolType::Individual),
+                table.add_symbol("pay".to_string(), SymbolType::Action),
+                table.add_symbol("seller".to_string(), SymbolType::Individual),
+            )
+        };
+        let source = Arc::new(vec![Arc::new(RelativizedAction::new(buyer, BasicAction::with_value(pay), seller))]);
+
+        let mut automaton = Automaton::new(Contract::default());
+        let mut states = Vec::new();
+        for situation in [StateSituation::ConflictFree, StateSituation::ConflictFree, StateSituation::Satisfaction] {
+            let mut state = State::with_auto_id(None);
+            state.situation = situation;
+            states.push(state.id);
+            automaton.add_state(state);
+        }
+        let [start, idle, done] = states[..] else { unreachable!() };
+        automaton.initial = automaton.get_state_by_id(start).cloned();
+        let epsilon = ActionSet::from_indices(&[], 1);
+        automaton.add_transition(Transition::new(start, idle, epsilon.clone(), source.clone()));
+        automaton.add_transition(Transition::new(idle, idle, epsilon, source.clone()));
+        automaton.add_transition(Transition::new(idle, done, ActionSet::from_indices(&[0], 1), source));
+
+        let plain = AutomatonExporter::dump_to_dot_with(&automaton, false, None, 0, false);
+        assert!(plain.contains(&format!("S{} -> S{} ", start, idle)));
+        assert!(plain.contains(&format!("S{} -> S{} ", idle, idle)));
+
+        let collapsed = AutomatonExporter::dump_to_dot_with(&automaton, true, None, 0, false);
+        assert!(!collapsed.contains(&format!("S{} ", idle)));
+        assert!(!collapsed.contains(&format!("S{} -> S{} ", start, start)));
+        assert!(collapsed.contains(&format!("start -> S{}\n", start)));
+        assert!(collapsed.contains(&format!("S{} -> S{} [ label = \"", start, done)));
+        assert_eq!(automaton.transitions.len(), 3);
+    }
+
+    #[test]
+    fn certificate_lists_states_and_individuals() {
+        let _table = serial();
+        let src = "{buyer,seller}O(pay); {buyer,seller}[pay]({seller,carrier}O(deliver));";
+        let contract = parse(src);
+        let automaton = build(src);
+
+        let certificate = print_certificate(&automaton, &contract);
+        assert!(certificate.contains("[CONFLICT-FREE CERTIFICATE]"));
+        assert!(certificate.contains(&format!("Contract: {}\n", contract.fingerprint())));
+        assert!(certificate.contains("Individuals (3): buyer, carrier, seller\n"));
+        assert!(certificate.contains(&format!("Reachable states: {}\n", automaton.states.len())));
+        assert!(certificate.contains("none is conflicting"));
+
+        // Construction halted before the obligation to deliver was checked
+        let halted = build_with(src, &["--break-on", "OBLIGATION(deliver)", "--break-halt"]);
+        assert!(!halted.conflict_found);
+        let refusal = print_certificate(&halted, &contract);
+        assert_eq!(refusal, "No certificate: 1 reachable state(s) were not checked for conflicts.\n");
+
+        let conflicting = build("{buyer,seller}O(pay); {buyer,seller}F(pay);");
+        assert!(print_certificate(&conflicting, &contract).starts_with("No certificate: a conflict was found"));
+    }
+
+    #[test]
+    fn examples_are_written_and_parse() {
+        let _table = serial();
+        let dir = crate::test_support::temp_dir("examples");
+        let written = FileUtil::write_examples(&dir.join("new").to_string_lossy()).unwrap();
+
+        assert_eq!(written.len(), EXAMPLE_CONTRACTS.len());
+        assert_eq!(written.len(), 3);
+        for path in &written {
+            let contract = crate::load_contract(&crate::test_support::config(path, &[]))
+                .unwrap_or_else(|e| panic!("{} should parse: {}", path, e));
+            assert!(!contract.clauses.is_empty());
+        }
+    }
+
+    #[test]
+    fn valid_masks_order_is_deterministic() {
+        let config = crate::test_support::config(&crate::test_support::write_contract("masks.rcl", ""), &[]);
+        let mut logger = Logger::new(config.clone()).unwrap();
+        let action = |value| BasicAction { value, ..BasicAction::skip() };
+        let actions: Vec<Arc<RelativizedAction>> =
+            (1..=5).map(|value| Arc::new(RelativizedAction::new(1, action(value), 2))).collect();
+        let conflicts = [Conflict::new(action(1), action(2), ConflictType::Global)];
+
+        let mut runs = Vec::new();
+        for order in [actions.clone(), actions.iter().rev().cloned().collect()] {
+            let set: FxHashSet<_> = order.into_iter().collect();
+            let concurrent = ContractUtil::calculate_concurrent_relativized_actions(set, &conflicts, &config, &mut logger);
+            runs.push(concurrent.valid_sets);
+        }
+
+        assert_eq!(runs[0], runs[1]);
+        let masks: Vec<u32> = runs[0]
+            .iter()
+            .map(|set| match set {
+                ActionSet::Mask(mask) => *mask,
+                other => panic!("expected a mask, got {:?}", other),
+            })
+            .collect();
+        // Ties in the number of actions are broken by the mask itself
+        assert!(masks.windows(2).all(|w| {
+            let (a, b) = (w[0].count_ones(), w[1].count_ones());
+            a > b || (a == b && w[0] < w[1])
+        }));
+    }
+
+    #[test]
+    fn system_memory_limit_matches_the_guard() {
+        for (ram_mb, swap_mb) in [(2048, 0), (8192, 2048), (16384, 0), (65536, 8192)] {
+            let memory = SystemMemory::new(ram_mb, swap_mb);
+            assert_eq!(memory.safe_limit_mb, calculate_safe_memory_limit(ram_mb, swap_mb));
+            assert!(memory.safe_limit_mb <= (ram_mb + swap_mb).max(1024));
+        }
+
+        let current = SystemMemory::current();
+        assert!(current.ram_mb > 0);
+        assert_eq!(current.safe_limit_mb, calculate_safe_memory_limit(current.ram_mb, current.swap_mb));
+    }
+
+    #[test]
+    fn rapid_saves_trigger_one_reanalysis() {
+        let path = crate::test_support::write_contract("watched.rcl", "{buyer,seller}O(pay);");
+        let (done_tx, done_rx) = std::sync::mpsc::channel();
+
+        let watched = path.clone();
+        std::thread::spawn(move || {
+            let mut seen = String::new();
+            let result = FileUtil::watch(&watched, Duration::from_millis(300), || {
+                seen = std::fs::read_to_string(&watched).unwrap();
+                false
+            });
+            let _ = done_tx.send((result.is_ok(), seen));
+        });
+
+        // Three saves in quick succession, once the watcher had time to start
+        std::thread::sleep(Duration::from_millis(300));
+        for action in ["pay", "ship", "deliver"] {
+            std::fs::write(&path, format!("{{buyer,seller}}O({});", action)).unwrap();
+            std::thread::sleep(Duration::from_millis(20));
+        }
+
+        let (ok, seen) = done_rx.recv_timeout(Duration::from_secs(10)).expect("watch should react to the change");
+        assert!(ok);
+        // Debounced: the re-analysis only starts after the last save
+        assert_eq!(seen, "{buyer,seller}O(deliver);");
+    }
+
+    #[test]
+    fn contract_artifact_has_the_initial_clause_and_every_conflict() {
+        let _table = serial();
+        let src = "conflict { global { (ship, hold) }; relativized { (pay, cancel) }; };
+                   {buyer,seller}O(pay);
+                   {buyer,seller}[pay]({seller,buyer}O(ship));";
+        let contract = parse(src);
+        let automaton = build(src);
+
+        let dump = AutomatonExporter::dump_contract(&automaton, &contract);
+        let initial = automaton.initial.as_ref().and_then(|s| s.clause.as_ref()).unwrap();
+        assert!(dump.contains(&format!("Fingerprint: {}", contract.fingerprint())));
+        assert!(dump.contains(&format!("Initial clause:\n{}\n", initial)), "{}", dump);
+
+        let conflicts = contract.get_all_conflicts();
+        assert_eq!(conflicts.len(), 2);
+        assert!(dump.contains("Conflicts (2):"));
+        for conflict in &conflicts {
+            assert!(dump.contains(&format!("{}\n", conflict)), "{}", dump);
+        }
+    }
+
+    #[test]
+    fn ast_tree_indents_each_nesting_level() {
+        let _table = serial();
+        let contract =
+            parse("{buyer,seller}[pay]({seller,buyer}O(ship)_/{seller,buyer}O(refund)/_);\n{buyer,seller}O(pay);");
+        let tree = print_ast(&contract);
+        assert_eq!(tree.lines().filter(|l| l.starts_with("Clause ")).count(), 2);
+        assert_eq!(print_ast(&contract), tree);
+
+        // Dynamic -> clause: -> obligation -> penalty: -> obligation, two spaces per level
+        let dynamic = tree.lines().skip_while(|l| !l.contains("DYNAMIC"));
+        let depths: Vec<(usize, &str)> = dynamic
+            .take(8)
+            .map(|l| ((l.len() - l.trim_start().len()) / 2, l.trim()))
+            .collect();
+        assert_eq!(
+            depths,
+            [
+                (1, "DYNAMIC {buyer,seller}"),
+                (2, "action: pay"),
+                (2, "clause:"),
+                (3, "OBLIGATION {seller,buyer}"),
+                (4, "action: ship"),
+                (4, "penalty:"),
+                (5, "OBLIGATION {seller,buyer}"),
+                (6, "action: refund"),
+            ],
+            "{}",
+            tree
+        );
+    }
+
+    #[test]
+    fn truncated_global_log_keeps_only_the_current_run() {
+        let path = crate::test_support::write_contract("logged.rcl", "{buyer,seller}O(pay);");
+        let run = |mode: &str, text: &str| {
+            let config = crate::test_support::config(&path, &[&format!("--global-log={}", mode)]);
+            Logger::new(config.clone()).expect("logger").log(LogType::Minimal, text);
+            std::fs::read_to_string(config.global_log_filename()).unwrap()
+        };
+
+        run("append", "first run");
+        let log = run("append", "second run");
+        assert!(log.contains("first run") && log.contains("second run"));
+
+        let log = run("truncate", "third run");
+        assert!(log.contains("third run"));
+        assert!(!log.contains("first run") && !log.contains("second run"), "{}", log);
+        assert_eq!(log.lines().count(), 1);
+    }
+
+    #[test]
+    fn dot_exports_begin_with_a_metadata_comment() {
+        let _table = serial();
+        let src = "{buyer,seller}O(pay);\n{buyer,seller}[pay]({seller,buyer}O(ship));";
+        let automaton = build(src);
+        let source = DotSource {
+            contract_file: "sale.rcl",
+            fingerprint: parse(src).fingerprint(),
+            title: None,
+            description: None,
+        };
+
+        for dot in [
+            AutomatonExporter::dump_to_dot_with(&automaton, false, Some(&source), 0, false),
+            AutomatonExporter::dump_to_min_dot(&automaton, Some(&source), 0, false),
+        ] {
+            let header: Vec<&str> = dot.lines().take_while(|l| !l.starts_with("digraph")).collect();
+            assert_eq!(header.first(), Some(&"/*"), "{}", dot);
+            assert_eq!(header.last(), Some(&" */"));
+            assert!(header.contains(&format!(" * States: {}", automaton.states.len()).as_str()));
+            assert!(header.contains(&format!(" * Transitions: {}", automaton.transitions.len()).as_str()));
+            assert!(header.contains(&" * Contract: sale.rcl"));
+            assert!(header.contains(&format!(" * Fingerprint: {}", source.fingerprint).as_str()));
+        }
+
+        let dot = AutomatonExporter::dump_to_dot(&automaton);
+        assert!(dot.starts_with("/*\n"));
+        assert!(!dot.contains("Contract:"));
+    }
+
+    #[test]
+    fn conflicts_csv_has_one_row_per_conflict() {
+        let _table = serial();
+        let automaton = crate::test_support::build_with(include_str!("../examples/conflicting.rcl"), &["-c"]);
+        let conflicts = automaton.get_conflicts();
+        assert!(!conflicts.is_empty());
+
+        let csv = AutomatonExporter::dump_conflicts_csv(&automaton);
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("state_id;conflicting_tag;conflicts_with;source_type;trace_length"));
+        let rows: Vec<Vec<&str>> = lines.map(|l| l.split(';').collect()).collect();
+        assert_eq!(rows.len(), conflicts.len());
+
+        for row in &rows {
+            assert_eq!(row.len(), 5, "{:?}", row);
+            let id: usize = row[0].parse().unwrap();
+            assert!(conflicts.iter().any(|s| s.id == id));
+            // Tags are written with the symbol names, not ids
+            assert!(row[1].contains("deliver") && row[2].contains("deliver"), "{:?}", row);
+            assert!(["global", "relativized", "directed"].contains(&row[3]));
+            assert_eq!(row[4].parse::<usize>().unwrap(), automaton.trace_to(id).len());
+        }
+    }
+
+    #[test]
+    fn smv_export_starts_at_the_initial_state_with_one_case_per_source() {
+        let _table = serial();
+        let automaton = build("{buyer,seller}O(pay)_/{buyer,seller}O(fine)/_;\n{buyer,seller}[pay]({seller,buyer}O(ship));");
+        let smv = AutomatonExporter::dump_to_smv(&automaton);
+        let initial = automaton.initial.as_ref().unwrap().id;
+
+        let init: Vec<&str> = smv.lines().filter(|l| l.trim_start().starts_with("init(state)")).collect();
+        assert_eq!(init, [format!("    init(state) := {};", initial)]);
+
+        let sources: FxHashSet<usize> = automaton.transitions.iter().map(|t| t.from).collect();
+        let cases: Vec<usize> = smv
+            .lines()
+            .filter_map(|l| l.trim_start().strip_prefix("state = "))
+            .map(|l| l.split(' ').next().unwrap().parse().unwrap())
+            .collect();
+        assert_eq!(cases.len(), sources.len(), "{}", smv);
+        assert_eq!(cases.iter().copied().collect::<FxHashSet<usize>>(), sources);
+        // States without transitions stay put
+        assert!(smv.contains("        TRUE : state;\n"));
+        assert!(smv.contains("    violating := state in {"));
+    }
+
+    #[test]
+    fn echoed_command_parses_back_to_the_same_configuration() {
+        let path = crate::test_support::write_contract("echo.rcl", "{buyer,seller}O(pay);");
+        let logs = crate::test_support::temp_dir("echo_logs");
+        let logs = logs.to_string_lossy();
+        let runs: [&[&str]; 3] = [
+            &[],
+            &["-v", "-n", "-c", "-m", "--no-idle", "--stats", "--tags", "--cycles", "--echo-command"],
+            &[
+                "--format=json,smv",
+                "--global-log=rotate:5",
+                "--satisfaction-paths=3",
+                "--explain=2",
+                "--delimiter",
+                "tab",
+                "--threads",
+                "2",
+                "--batch-size",
+                "64",
+                "--tooltip-length",
+                "40",
+                "--max-clause-size",
+                "9",
+                "--timeout",
+                "30",
+                "--memory-limit",
+                "512",
+                "--max-action-depth",
+                "5",
+                "--break-on",
+                "pay",
+                "--log-dir",
+                &logs,
+            ],
+        ];
+
+        for args in runs {
+            let mut command = vec![path.clone()];
+            command.extend(args.iter().map(|a| a.to_string()));
+            let config = crate::parse_command_line(&command);
+
+            let echoed = config.command_line();
+            assert_eq!(crate::parse_command_line(&echoed), config, "{:?}", echoed);
+            // Normalized: echoing the echoed command changes nothing
+            assert_eq!(crate::parse_command_line(&echoed).command_line(), echoed);
+        }
+    }
+
+    #[test]
+    fn long_clause_tooltip_is_truncated_on_one_line() {
+        let _table = serial();
+        let src: String = (0..60).map(|i| format!("{{buyer,seller}}O(action_{});\n", i)).collect();
+        // Only the initial state: its clause is the composition of all 60 obligations
+        let automaton = Automaton::new(parse(&src));
+        let clause = automaton.initial.as_ref().unwrap().clause.as_ref().unwrap().to_string();
+        assert!(clause.chars().count() > 1000);
+
+        let tooltip = regex::Regex::new(r#"tooltip="((?:[^"\\]|\\.)*)""#).unwrap();
+        for dot in [
+            AutomatonExporter::dump_to_dot_with(&automaton, false, None, 80, false),
+            AutomatonExporter::dump_to_min_dot(&automaton, None, 80, false),
+        ] {
+            let node = dot.lines().find(|l| l.contains("tooltip=")).expect("node line");
+            let text = &tooltip.captures(node).expect("quoted tooltip")[1];
+            assert!(text.ends_with("..."), "{}", text);
+            assert_eq!(text.chars().count(), 80 + 3);
+            assert!(clause.starts_with(text.trim_end_matches("...")));
+            assert!(!text.contains('\n'));
+        }
+
+        // The full clause goes to the sidecar file, keyed by state id
+        let initial = automaton.initial.as_ref().unwrap().id;
+        let clauses = AutomatonExporter::dump_state_clauses(&automaton);
+        assert_eq!(clauses, format!("S{}: {}\n", initial, clause));
+    }
+
+    #[test]
+    fn cpu_time_covers_every_busy_thread() {
+        use rayon::prelude::*;
+
+        let threads = std::thread::available_parallelism().map_or(1, |n| n.get()).min(4);
+        let cpu_start = process_cpu_time_ms();
+        let wall_start = std::time::Instant::now();
+        (0..threads).into_par_iter().for_each(|_| {
+            let spin = std::time::Instant::now();
+            while spin.elapsed() < Duration::from_millis(300) {
+                std::hint::black_box(0u64);
+            }
+        });
+        let wall_ms = wall_start.elapsed().as_millis() as u64;
+        let cpu_end = process_cpu_time_ms();
+        let cpu_ms = cpu_end.saturating_sub(cpu_start);
+
+        // Platforms where sysinfo can't read the process report 0
+        if cpu_end == 0 {
+            return;
+        }
+        if threads < 2 {
+            // One core runs the threads in turn: CPU time can only approach wall time
+            assert!(cpu_ms >= wall_ms / 2, "cpu {}ms, wall {}ms", cpu_ms, wall_ms);
+        } else {
+            assert!(cpu_ms >= wall_ms, "cpu {}ms < wall {}ms with {} threads", cpu_ms, wall_ms, threads);
+        }
+    }
+
+    #[test]
+    fn ansi_stripping_removes_cursor_moves_and_keeps_plain_text() {
+        let plain = "Estado s3 → ✓ conflito {buyer,seller}O(pay); 100% [ok]";
+        assert_eq!(Logger::strip_ansi_codes(plain), plain);
+
+        let colored = format!("{}[CONFLICT]{} found", ConsoleColors::FG_RED, ConsoleColors::RESET);
+        assert_eq!(Logger::strip_ansi_codes(&colored), "[CONFLICT] found");
+        // Cursor up two lines, clear the line, then back to column 1
+        assert_eq!(Logger::strip_ansi_codes("\x1B[2A\x1B[2Kprogresso: 50%\x1B[1G"), "progresso: 50%");
+    }
+
+    #[test]
+    fn grouped_label_lists_each_sender_once() {
+        let _table = serial();
+        let automaton = build("{buyer,seller}O(pay);\n{seller,buyer}O(ship);");
+        let table = SymbolTable::lock();
+        let both = automaton
+            .transitions
+            .iter()
+            .find(|t| {
+                let senders: FxHashSet<i32> =
+                    t.action_refs().filter(|a| !a.negation).map(|a| a.sender).collect();
+                senders.len() == 2
+            })
+            .expect("transition where both parties act");
+
+        // Senders appear in symbol id order, which depends on the tests run before
+        let either = |label: &str, first: &str, second: &str, separator: &str| {
+            label == format!("{}{}{}", first, separator, second)
+                || label == format!("{}{}{}", second, separator, first)
+        };
+        let flat = AutomatonExporter::transition_label(both, &table, false);
+        assert!(either(&flat, "(buyer, pay, seller)", "(seller, ship, buyer)", ", "), "{}", flat);
+        let grouped = AutomatonExporter::transition_label(both, &table, true);
+        assert!(either(&grouped, "buyer: {pay}", "seller: {ship}", "; "), "{}", grouped);
+        drop(table);
+
+        let mermaid = AutomatonExporter::dump_to_mermaid_with(&automaton, true);
+        assert!(mermaid.contains(&format!("-->|\"{}\"|", grouped)), "{}", mermaid);
+    }
+
+    #[test]
+    fn only_the_chosen_log_types_reach_the_event_sink() {
+        let path = crate::test_support::write_contract("emit_logs.rcl", "{a,b}O(pay);");
+        let emitted = |args: &[&str]| {
+            let sink = Arc::new(RecordingSink::default());
+            let config = crate::test_support::config(&path, args);
+            let logger = Logger::new(config).unwrap().with_event_sink(sink.clone());
+            logger.log(LogType::Minimal, "minimal");
+            logger.log(LogType::Necessary, "necessary");
+            logger.log(LogType::Additional, "additional");
+            sink.payloads("log-message")
+                .iter()
+                .map(|payload| format!("{} {}", payload["log_type"], payload["message"]))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(emitted(&[]), [r#""Minimal" "minimal""#, r#""Necessary" "necessary""#]);
+        assert_eq!(emitted(&["--emit-logs=additional"]), [r#""Additional" "additional""#]);
+        assert!(emitted(&["--emit-logs="]).is_empty());
+    }
+
+    #[test]
+    fn text_and_json_exports_name_the_initial_state() {
+        let _table = serial();
+        let automaton = build("{buyer,seller}O(pay);\n{buyer,seller}[pay]({seller,buyer}O(deliver));");
+        let initial = automaton.initial.as_ref().unwrap().id;
+
+        let text = AutomatonExporter::dump_to_text(&automaton);
+        let q0: Vec<&str> = text.lines().filter_map(|l| l.strip_prefix("Q0:")).collect();
+        assert_eq!(q0, [initial.to_string()], "{}", text);
+
+        let json: serde_json::Value =
+            serde_json::from_str(&AutomatonExporter::dump_to_json(&automaton)).unwrap();
+        assert_eq!(json["initial"], initial);
+    }
+
+    #[test]
+    fn html_report_embeds_the_diagram_and_counts_the_states() {
+        let _table = serial();
+        let automaton = build("{buyer,seller}O(pay);\n{buyer,seller}F(pay);");
+        assert!(automaton.conflict_found);
+        let html = AutomatonExporter::dump_to_html(&automaton);
+
+        assert!(html.starts_with("<!DOCTYPE html>") && html.trim_end().ends_with("</html>"));
+        let states = format!("<tr><th>States</th><td>{}</td></tr>", automaton.states.len());
+        assert!(html.contains(&states), "{}", html);
+        assert_eq!(html.matches("<svg ").count(), 1);
+        assert_eq!(html.matches("<circle ").count(), automaton.states.len());
+        for state in &automaton.states {
+            assert!(html.contains(&format!(">S{}</text>", state.id)));
+        }
+        assert!(html.contains("<h2>Trace</h2>"));
+        assert_eq!(html.matches("<li>S").count(), automaton.get_conflicts().len());
+
+        // Self-contained: nothing is loaded from elsewhere
+        let without_namespace = html.replace("xmlns=\"http://www.w3.org/2000/svg\"", "");
+        assert!(!without_namespace.contains("http") && !html.contains("<link"));
+    }
+
+    #[test]
+    fn global_actions_are_labelled_without_unknown_parties() {
+        let _table = serial();
+        let automaton = build("O(pay);\nF(ship);");
+
+        let dot = AutomatonExporter::dump_to_dot(&automaton);
+        let labels: Vec<&str> = dot.lines().filter(|l| l.contains("label = ")).collect();
+        assert!(labels.iter().any(|l| l.contains("(GLOBAL, pay, GLOBAL)")), "{}", dot);
+        assert!(labels.iter().all(|l| !l.contains('?')), "{}", dot);
+
+        let table = SymbolTable::lock();
+        let pay = table.get_id("pay", SymbolType::Action).unwrap();
+        let action = RelativizedAction::new(-1, BasicAction::with_value(pay), -1);
+        assert_eq!(action.format_with_symbols(&table), "(GLOBAL, pay, GLOBAL)");
+        assert_eq!(table.individual_name(i32::MAX), "?");
+    }
+
+    #[test]
+    fn recallrc_options_apply_unless_the_command_line_overrides_them() {
+        let folder = temp_dir("recallrc");
+        std::fs::write(folder.join(RC_FILE_NAME), "verbose = true\nno_pruning = true\ncontinue = true\nthreads = 2\n").unwrap();
+        let contract = folder.join("contract.rcl").to_string_lossy().into_owned();
+        let run = |args: &[&str]| {
+            let mut command = vec![contract.clone()];
+            command.extend(args.iter().map(|a| a.to_string()));
+            parse_command_line(&apply_rc_file(&command).unwrap())
+        };
+
+        let from_file = run(&[]);
+        assert_eq!(from_file.log_level(), LogLevel::Verbose);
+        assert!(!from_file.is_use_prunning());
+        assert!(from_file.is_continue_on_conflict());
+        assert_eq!(from_file.threads(), 2);
+
+        // The command line comes after the file, so it wins, booleans included
+        let overridden = run(&["--no-verbose", "--prunning", "--no-continue", "--threads", "4"]);
+        assert_eq!(overridden.log_level(), LogLevel::Normal);
+        assert!(overridden.is_use_prunning());
+        assert!(!overridden.is_continue_on_conflict());
+        assert_eq!(overridden.threads(), 4);
+
+        let ignored = run(&["--no-rc"]);
+        assert_eq!(ignored.log_level(), LogLevel::Normal);
+        assert!(ignored.is_use_prunning() && !ignored.is_continue_on_conflict());
+        assert_eq!(ignored.threads(), 0);
+
+        std::fs::write(folder.join(RC_FILE_NAME), "timeout = 60\nmemory_limit = 2048\n").unwrap();
+        let limits = run(&[]);
+        assert_eq!(limits.timeout(), Some(60));
+        assert_eq!(limits.memory_limit(), Some(2048));
+        assert_eq!(run(&["--timeout", "5"]).timeout(), Some(5));
+
+        std::fs::write(folder.join(RC_FILE_NAME), "memory_limit = -1\n").unwrap();
+        assert!(apply_rc_file(std::slice::from_ref(&contract)).unwrap_err().contains("memory_limit must be a non-negative number"));
+        std::fs::write(folder.join(RC_FILE_NAME), "max_memory = 1\n").unwrap();
+        assert!(apply_rc_file(std::slice::from_ref(&contract)).unwrap_err().ends_with("unknown key max_memory"));
+
+        std::fs::write(folder.join(RC_FILE_NAME), "verbose = \"yes\"\n").unwrap();
+        assert!(apply_rc_file(std::slice::from_ref(&contract)).unwrap_err().contains("verbose must be true or false"));
+    }
+
+    #[test]
+    fn conflict_subgraph_keeps_only_the_paths_to_conflicts() {
+        let _table = serial();
+        let automaton = build(
+            "{buyer,seller}O(pay);\n{buyer,seller}[pay]({seller,buyer}O(ship) ^ {seller,buyer}F(ship));",
+        );
+        assert!(automaton.conflict_found);
+        let initial = automaton.initial.as_ref().unwrap().id;
+        let conflicting: Vec<usize> = automaton.get_conflicts().iter().map(|s| s.id).collect();
+
+        let dot = AutomatonExporter::dump_conflict_subgraph_dot(&automaton);
+        let nodes: Vec<usize> = dot
+            .lines()
+            .filter(|l| l.starts_with("node [shape = circle") || l.starts_with("node [shape = doublecircle"))
+            .map(|l| l.rsplit_once("; S").unwrap().1.trim_end_matches(" ;").parse().unwrap())
+            .collect();
+        assert!(nodes.contains(&initial), "{}", dot);
+        assert!(conflicting.iter().any(|id| nodes.contains(id)));
+        assert!(nodes.len() < automaton.states.len());
+        assert_eq!(dot.matches("shape = doublecircle").count(), conflicting.len());
+
+        // Every drawn transition links two drawn states
+        for line in dot.lines().filter(|l| l.starts_with('\t')) {
+            let (from, rest) = line.trim().trim_start_matches('S').split_once(" -> S").unwrap();
+            let to = rest.split_once(' ').unwrap().0;
+            assert!(nodes.contains(&from.parse().unwrap()) && nodes.contains(&to.parse().unwrap()), "{}", line);
+        }
+
+        // The header counts what is drawn, not the whole automaton
+        let edges = dot.lines().filter(|l| l.starts_with('\t')).count();
+        assert!(dot.contains(&format!(" * States: {}\n", nodes.len())), "{}", dot);
+        assert!(dot.contains(&format!(" * Transitions: {}\n", edges)), "{}", dot);
+        assert!(edges < automaton.transitions.len());
+
+        // Same options as the full diagram
+        let grouped = AutomatonExporter::dump_conflict_subgraph_dot_with(&automaton, None, 5, true);
+        assert!(grouped.contains("[ label = \"buyer: {pay}\" ]"), "{}", grouped);
+        assert!(!dot.contains("buyer: {"), "{}", dot);
+        for (_, tooltip) in grouped.lines().filter_map(|l| l.split_once("tooltip=\"")) {
+            let text = tooltip.split_once('"').unwrap().0;
+            assert!(text.ends_with("...") && text.chars().count() == 8, "{}", text);
+        }
+    }
+
+    #[test]
+    fn conflict_free_subgraph_is_only_the_initial_state() {
+        let _table = serial();
+        let automaton = build("{buyer,seller}O(pay);\n{buyer,seller}[pay]({seller,buyer}O(ship));");
+        assert!(!automaton.conflict_found);
+        let initial = automaton.initial.as_ref().unwrap().id;
+
+        let dot = AutomatonExporter::dump_conflict_subgraph_dot(&automaton);
+        assert!(dot.contains(" * States: 1\n * Transitions: 0\n"), "{}", dot);
+        assert!(dot.contains(&format!("; S{} ;\n", initial)));
+        assert_eq!(dot.matches("]; S").count(), 1);
+        assert!(!dot.lines().any(|l| l.starts_with('\t')), "{}", dot);
+    }
+
+    #[test]
+    fn configuration_summary_labels_the_resolved_settings() {
+        let configured = config("deal.rcl", &["-v", "-g", "-m", "-n", "-c", "--threads", "2"]).to_string();
+        for label in [
+            "Contract: deal.rcl",
+            "Verbose: on",
+            "Pruning: off",
+            "Continue-on-conflict: on",
+            "Export: dot+min+decompositions,",
+            "Threads: 2,",
+        ] {
+            assert!(configured.contains(label), "{} not in {}", label, configured);
+        }
+
+        let defaults = config("deal.rcl", &[]).to_string();
+        for label in ["Verbose: off", "Pruning: on", "Continue-on-conflict: off", "Export: none", "Threads: all cores"] {
+            assert!(defaults.contains(label), "{} not in {}", label, defaults);
+        }
+        // No raw field names as in `{:?}`
+        assert!(!defaults.contains("use_prunning") && !defaults.contains("result_file_name"));
+    }
+}
@@ -0,0 +1,608 @@
+//! End-to-end checks of the analyzer sidecar's command line and stdout protocol.
+
+use std::path::PathBuf;
+use std::process::{Command, Output};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static RUN_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Fresh empty directory for one analyzer run.
+fn run_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "recall_cli_{}_{}_{}",
+        std::process::id(),
+        RUN_COUNTER.fetch_add(1, Ordering::SeqCst),
+        name
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Writes `src` as `<name>.rcl` in a fresh directory and returns its path.
+fn contract(name: &str, src: &str) -> PathBuf {
+    let path = run_dir(name).join(format!("{}.rcl", name));
+    std::fs::write(&path, src).unwrap();
+    path
+}
+
+/// Runs the analyzer on `path` with `args`, ignoring any `.recallrc`.
+fn analyze(path: &PathBuf, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_analyzer_engine"))
+        .arg(path)
+        .arg("--no-rc")
+        .args(args)
+        .output()
+        .expect("analyzer should start")
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+const CONFLICT_FREE: &str = include_str!("../examples/conflict_free.rcl");
+const CONFLICTING: &str = include_str!("../examples/conflicting.rcl");
+
+/// Parses the JSON after `marker` on the first stdout line that carries it.
+fn marker_json(output: &Output, marker: &str) -> serde_json::Value {
+    let out = stdout(output);
+    let line = out
+        .lines()
+        .find_map(|l| l.strip_prefix(marker))
+        .unwrap_or_else(|| panic!("no {} line", marker));
+    serde_json::from_str(line).unwrap()
+}
+
+#[test]
+fn summary_block_is_contiguous() {
+    let output = analyze(&contract("summary", CONFLICT_FREE), &[]);
+    assert!(output.status.success());
+    let out = stdout(&output);
+    let lines: Vec<&str> = out.lines().collect();
+
+    let start = lines.iter().position(|l| *l == "FINAL_SUMMARY_START").unwrap();
+    let end = lines.iter().position(|l| *l == "FINAL_SUMMARY_END").unwrap();
+    assert!(start < end);
+    assert_eq!(lines.iter().filter(|l| **l == "FINAL_SUMMARY_START").count(), 1);
+
+    let block = &lines[start + 1..end];
+    assert!(block.iter().any(|l| l.contains("[CONFLICT-FREE]")));
+    assert!(block.iter().any(|l| l.starts_with("Completed in")));
+    assert!(block.iter().all(|l| !l.contains("_JSON:") && !l.starts_with("RESULT_CSV:")));
+
+    let csv = lines.iter().position(|l| l.starts_with("RESULT_CSV:")).unwrap();
+    assert!(csv > end);
+}
+
+#[test]
+fn csv_only_prints_just_the_metrics_line() {
+    let path = contract("csv_only", CONFLICT_FREE);
+    let full = stdout(&analyze(&path, &[]));
+    let output = analyze(&path, &["--csv-only"]);
+    assert!(output.status.success());
+
+    let out = stdout(&output);
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines.len(), 1, "{}", out);
+
+    // Same metrics as RESULT_CSV; only the timings may differ between runs
+    let result_csv = full.lines().find_map(|l| l.strip_prefix("RESULT_CSV:")).unwrap();
+    let fields: Vec<&str> = lines[0].split(';').collect();
+    let expected: Vec<&str> = result_csv.split(';').collect();
+    assert_eq!(fields.len(), expected.len());
+    assert_eq!(fields[1..7], expected[1..7]);
+    assert_eq!(fields.last(), Some(&"success"));
+}
+
+#[test]
+fn comma_delimiter_separates_the_metrics_line() {
+    let path = contract("comma_delimiter", CONFLICT_FREE);
+    let output = analyze(&path, &["--csv-only", "--delimiter", ","]);
+    assert!(output.status.success());
+
+    let out = stdout(&output);
+    let fields: Vec<&str> = out.trim().split(',').collect();
+    assert_eq!(fields.len(), 16, "{}", out);
+    assert!(!out.contains(';'), "{}", out);
+    assert_eq!(fields.last(), Some(&"success"));
+}
+
+#[test]
+fn result_line_is_preceded_by_the_schema_version() {
+    let output = analyze(&contract("schema", CONFLICT_FREE), &[]);
+    let out = stdout(&output);
+    let schema_line = format!("CSV_SCHEMA:{}", recall_lib::utils::CSV_SCHEMA_VERSION);
+    let schema = out.lines().position(|l| l == schema_line);
+    let result = out.lines().position(|l| l.starts_with("RESULT_CSV:"));
+    assert!(schema.is_some() && schema < result, "{}", out);
+
+    // What the batch reads back, column count included
+    let (data, _) = recall_lib::batch::batch_output(&out, String::new()).unwrap();
+    assert_eq!(data.split(';').count(), 16);
+}
+
+#[test]
+fn in_process_batch_matches_the_sidecar() {
+    use recall_lib::batch::{analyze_batch_in_process, batch_output, in_memory_args, output_fingerprint};
+
+    let contracts = [
+        ("small_conflict_free", CONFLICT_FREE),
+        ("small_conflicting", CONFLICTING),
+        ("small_penalty", "{buyer,seller}O(pay)_/{buyer,seller}O(fine)/_;\n{seller,buyer}F(cancel);"),
+        ("small_titled", "title \"Deal\";\n{buyer,seller}O(pay);"),
+        ("small_broken", "{buyer,seller}O(pay"),
+    ];
+    let log_dir = run_dir("in_process_logs");
+    let files: Vec<Vec<String>> = contracts
+        .iter()
+        .map(|(name, src)| {
+            let path = contract(name, src).to_string_lossy().into_owned();
+            in_memory_args(&path, &log_dir, true, &["--no-rc".to_string()], &Default::default())
+        })
+        .collect();
+
+    let in_process = analyze_batch_in_process(&files);
+    for (args, in_process) in files.iter().zip(in_process) {
+        let in_process = in_process.unwrap_or_else(|| panic!("{} went to the sidecar", args[0]));
+        let output = Command::new(env!("CARGO_BIN_EXE_analyzer_engine"))
+            .args(args)
+            .output()
+            .expect("analyzer should start");
+        let sidecar_out = stdout(&output);
+        let sidecar = batch_output(&sidecar_out, String::from_utf8_lossy(&output.stderr).into_owned());
+
+        assert_eq!(in_process.fingerprint, output_fingerprint(&sidecar_out), "{}", args[0]);
+        let ((data, summary), (sidecar_data, sidecar_summary)) = match (in_process.outcome, sidecar) {
+            (Ok(in_process), Ok(sidecar)) => (in_process, sidecar),
+            (Err(error), Err(sidecar_error)) if args[0].ends_with("small_broken.rcl") => {
+                assert_eq!(error, sidecar_error.trim());
+                continue;
+            }
+            (in_process, sidecar) => panic!("{}: {:?} vs {:?}", args[0], in_process, sidecar),
+        };
+        // Wall time, size, memory and CPU time differ from run to run
+        let fields: Vec<&str> = data.split(';').collect();
+        let sidecar_fields: Vec<&str> = sidecar_data.split(';').collect();
+        for i in [1, 2, 3, 4, 5, 6, 9, 10, 11, 12, 13, 15] {
+            assert_eq!(fields[i], sidecar_fields[i], "{} field {}", args[0], i);
+        }
+        let before_timings = |summary: &str| summary.split("Completed in").next().unwrap().trim().to_string();
+        assert_eq!(before_timings(&summary), before_timings(&sidecar_summary), "{}", args[0]);
+        assert_eq!(summary.contains("Contract: Deal"), args[0].ends_with("small_titled.rcl"));
+    }
+}
+
+#[test]
+fn format_json_and_text_write_both_exports() {
+    let path = contract("formats", CONFLICT_FREE);
+    let output = analyze(&path, &["--format=json,text"]);
+    assert!(output.status.success());
+
+    let json = std::fs::read_to_string(path.with_file_name("formats.json")).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(json["conflict_found"], false);
+    assert!(!json["states"].as_array().unwrap().is_empty());
+    assert!(json["initial"].is_u64());
+
+    let text = std::fs::read_to_string(path.with_file_name("formats.txt")).unwrap();
+    assert!(text.starts_with("A:"));
+    assert!(text.lines().any(|l| l.starts_with("I:")));
+}
+
+#[test]
+fn unknown_format_is_rejected() {
+    let output = analyze(&contract("bad_format", CONFLICT_FREE), &["--format=json,pdf"]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn zero_or_missing_limits_are_usage_errors() {
+    let path = contract("bad_limits", CONFLICT_FREE);
+    for (args, message) in [
+        (&["--timeout", "0"][..], "--timeout requires a positive number"),
+        (&["--memory-limit", "0"][..], "--memory-limit requires a positive number"),
+        (&["--memory-limit", "-5"][..], "--memory-limit requires a positive number"),
+        (&["--timeout"][..], "--timeout requires a positive number"),
+    ] {
+        let output = analyze(&path, args);
+        assert_eq!(output.status.code(), Some(1), "{:?}", args);
+        assert!(String::from_utf8_lossy(&output.stderr).contains(message), "{:?}", args);
+    }
+    assert!(analyze(&path, &["--timeout", "60", "--memory-limit", "4096"]).status.success());
+}
+
+#[test]
+fn conflicts_json_lists_each_conflict() {
+    let output = analyze(&contract("conflicts", CONFLICTING), &[]);
+    let conflicts = marker_json(&output, "CONFLICTS_JSON:");
+    let conflicts = conflicts.as_array().unwrap();
+    assert_eq!(conflicts.len(), 1);
+    assert_eq!(conflicts[0]["tag"], "F(seller,deliver,buyer)");
+    assert_eq!(conflicts[0]["conflicting_tags"], serde_json::json!(["O(seller,deliver,buyer)"]));
+
+    let output = analyze(&contract("no_conflicts", CONFLICT_FREE), &[]);
+    assert_eq!(marker_json(&output, "CONFLICTS_JSON:"), serde_json::json!([]));
+}
+
+#[test]
+fn trace_reaches_satisfaction_or_violation() {
+    let path = contract("trace", CONFLICT_FREE);
+    let run = |name: &str, steps: &str| {
+        let trace = path.with_file_name(name);
+        std::fs::write(&trace, steps).unwrap();
+        let output = analyze(&path, &[&format!("--trace={}", trace.display())]);
+        marker_json(&output, "TRACE_JSON:")
+    };
+
+    let paid = run("paid.json", r#"[[["buyer","pay","seller"]], [["seller","deliver","buyer"]]]"#);
+    assert_eq!(paid["outcome"], "satisfaction");
+    assert_eq!(paid["steps"], 2);
+
+    let idle = run("idle.json", "[[]]");
+    assert_eq!(idle["outcome"], "violating");
+    assert_eq!(idle["steps"], 1);
+}
+
+#[test]
+fn gzip_contract_yields_the_same_automaton() {
+    use std::io::Write;
+
+    let plain = contract("plain", CONFLICT_FREE);
+    let compressed = run_dir("compressed").join("compressed.rcl.gz");
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(CONFLICT_FREE.as_bytes()).unwrap();
+    std::fs::write(&compressed, encoder.finish().unwrap()).unwrap();
+
+    let automaton = |path: &PathBuf, stem: &str| {
+        let output = analyze(path, &["--format=json"]);
+        assert!(output.status.success());
+        // Derived names drop `.gz` as well as `.rcl`
+        let json = std::fs::read_to_string(path.with_file_name(format!("{}.json", stem))).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+        (json["states"].clone(), json["transitions"].clone(), json["initial"].clone())
+    };
+
+    assert_eq!(automaton(&plain, "plain"), automaton(&compressed, "compressed"));
+}
+
+#[test]
+fn deny_warnings_fails_on_a_warning() {
+    let path = contract(
+        "warning",
+        "conflict { global { (pay, cancel), (cancel, pay) }; };
+         {buyer,seller}O(pay);
+         {seller,buyer}F(cancel);",
+    );
+
+    let output = analyze(&path, &[]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(stdout(&output).contains("Duplicate conflict declaration"));
+
+    let output = analyze(&path, &["--deny-warnings"]);
+    assert_eq!(output.status.code(), Some(3));
+}
+
+#[test]
+fn missing_penalties_are_warned_only_under_strict() {
+    let path = contract("deny_conflict_free", CONFLICT_FREE);
+    let output = analyze(&path, &["--deny-warnings"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(!stdout(&output).contains("has no penalty"));
+
+    // Formatted from the parsed clause, so anonymized names apply to it too
+    let output = analyze(&path, &["--strict", "--anonymize"]);
+    let out = stdout(&output);
+    assert_eq!(out.matches("has no penalty").count(), 2, "{}", out);
+    for name in ["buyer", "seller", "pay", "deliver"] {
+        assert!(!out.contains(name), "{} in anonymized output", name);
+    }
+
+    let output = analyze(&path, &["--strict", "--deny-warnings"]);
+    assert_eq!(output.status.code(), Some(3));
+}
+
+#[test]
+fn in_memory_batch_writes_nothing_to_the_folder() {
+    let folder = run_dir("in_memory");
+    std::fs::write(folder.join("conflict_free.rcl"), CONFLICT_FREE).unwrap();
+    std::fs::write(folder.join("conflicting.rcl"), CONFLICTING).unwrap();
+    std::fs::write(folder.join("batch_config.json"), r#"{ "conflicting.rcl": ["-c"] }"#).unwrap();
+    let listing = || {
+        let mut names: Vec<String> = std::fs::read_dir(&folder)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    };
+    let before = listing();
+
+    let log_dir = run_dir("in_memory_logs");
+    let config = recall_lib::batch::load_batch_config(&folder).unwrap();
+    let files = recall_lib::batch::list_contract_files(&folder).unwrap();
+    for file in &files {
+        let args = recall_lib::batch::in_memory_args(file, &log_dir, true, &[], &config);
+        let output = Command::new(env!("CARGO_BIN_EXE_analyzer_engine"))
+            .args(&args)
+            .arg("--no-rc")
+            .output()
+            .unwrap();
+        assert!(stdout(&output).contains("RESULT_CSV:"), "{}", stdout(&output));
+    }
+
+    assert_eq!(listing(), before);
+    assert_eq!(std::fs::read_dir(&log_dir).unwrap().count(), 2 * files.len());
+}
+
+#[test]
+fn clause_size_limit_exits_with_state_limit() {
+    let output = analyze(&contract("state_limit", CONFLICT_FREE), &["--max-clause-size", "1"]);
+    assert_eq!(output.status.code(), Some(7));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("CRITICAL: Clause size limit exceeded"));
+}
+
+#[test]
+fn anonymized_dot_hides_names_but_keeps_the_graph() {
+    let src = "conflict { global { (pay, ship), (ship, pay) }; };
+               {buyer,seller}O(pay)_/{seller,buyer}P(fine)/_;
+               {buyer,seller}[pay]({seller,buyer}O(deliver)_/{buyer,seller}P(fine)/_);";
+    let names = ["buyer", "seller", "pay", "deliver", "ship", "fine"];
+
+    let plain = contract("plain", src);
+    assert!(analyze(&plain, &["--format=dot"]).status.success());
+    let plain_dot = std::fs::read_to_string(plain.with_extension("dot")).unwrap();
+
+    let anonymous = contract("anonymous", src);
+    let output = analyze(&anonymous, &["--format=dot", "--anonymize"]);
+    assert!(output.status.success());
+    let anonymous_dot = std::fs::read_to_string(anonymous.with_extension("dot")).unwrap();
+
+    let out = stdout(&output);
+    assert!(out.contains("Duplicate conflict declaration"));
+    assert!(names.iter().any(|name| plain_dot.contains(name)));
+    for name in names {
+        assert!(!anonymous_dot.contains(name), "{} in anonymized DOT", name);
+        assert!(!out.contains(name), "{} in anonymized output", name);
+    }
+
+    // Same nodes and edges, in the same order; only the labels (and the header) differ
+    let shape = |dot: &str| -> Vec<String> {
+        dot.lines()
+            .skip_while(|line| !line.starts_with("digraph"))
+            .map(|line| line.split('[').next().unwrap_or("").trim().to_string())
+            .collect()
+    };
+    assert_eq!(shape(&plain_dot), shape(&anonymous_dot));
+}
+
+#[test]
+fn events_are_printed_only_for_the_interface() {
+    let path = contract("events", CONFLICTING);
+
+    let output = analyze(&path, &["-v"]);
+    let out = stdout(&output);
+    assert!(!out.contains("EVENT_JSON:"));
+    assert!(out.contains("Conflict found in state"));
+
+    let output = analyze(&path, &["--events"]);
+    let event = marker_json(&output, "EVENT_JSON:");
+    assert_eq!(event["event"], "conflict-found");
+    assert_eq!(event["payload"]["tag"], "F(seller,deliver,buyer)");
+}
+
+#[test]
+fn permission_only_contract_is_vacuous_but_passes_deny_warnings() {
+    let path = contract("vacuous", "{buyer,seller}P(pay);\n{seller}P(ship);");
+
+    let output = analyze(&path, &["--deny-warnings"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(stdout(&output).contains("WARNING: contract may be vacuously satisfied"));
+}
+
+#[test]
+fn merged_contracts_are_analysed_together() {
+    let delivery = contract("delivery", "{seller,buyer}O(deliver);");
+    let ban = contract("ban", "{seller,buyer}F(deliver);");
+    assert!(stdout(&analyze(&delivery, &[])).contains("[CONFLICT-FREE]"));
+
+    let output = analyze(&delivery, &["--merge", ban.to_str().unwrap()]);
+    assert!(output.status.success());
+    assert!(stdout(&output).contains("[CONFLICT]"));
+    assert!(!marker_json(&output, "CONFLICTS_JSON:").as_array().unwrap().is_empty());
+}
+
+#[test]
+fn metrics_report_wall_and_cpu_time() {
+    let output = analyze(&contract("timing", CONFLICT_FREE), &[]);
+    let out = stdout(&output);
+    assert!(out.lines().any(|l| l.starts_with("Completed in")));
+    assert!(out.lines().any(|l| l.starts_with("CPU time:") && l.ends_with("ms")));
+
+    // wall_ms first, cpu_ms right before the status
+    let csv = out.lines().find_map(|l| l.strip_prefix("RESULT_CSV:")).unwrap();
+    let fields: Vec<&str> = csv.split(';').collect();
+    assert_eq!(fields.len(), 16);
+    assert!(fields[0].parse::<u64>().is_ok());
+    assert!(fields[14].parse::<u64>().is_ok());
+    assert_eq!(fields[15], "success");
+}
+
+#[test]
+fn title_is_echoed_in_the_json_export_and_summary() {
+    let path = contract(
+        "titled",
+        &format!("title \"Sale\";\ndescription \"Buyer pays\";\n{}", CONFLICT_FREE),
+    );
+    let output = analyze(&path, &["--format=json"]);
+    assert!(output.status.success());
+    assert!(stdout(&output).contains("Contract: Sale"));
+
+    let json = std::fs::read_to_string(path.with_file_name("titled.json")).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(json["contract"]["title"], "Sale");
+    assert_eq!(json["contract"]["description"], "Buyer pays");
+}
+
+#[cfg(unix)]
+#[test]
+fn interrupt_flushes_the_logs_and_exits_with_130() {
+    // 18 concurrent obligations take far longer than the wait below
+    let src: String = (0..18).map(|i| format!("{{buyer,seller}}O(a{});\n", i)).collect();
+    let path = contract("interrupted", &src);
+    let child = Command::new(env!("CARGO_BIN_EXE_analyzer_engine"))
+        .arg(&path)
+        .arg("--no-rc")
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .expect("analyzer should start");
+
+    std::thread::sleep(std::time::Duration::from_millis(1000));
+    assert_eq!(unsafe { libc::kill(child.id() as libc::pid_t, libc::SIGINT) }, 0);
+    let output = child.wait_with_output().unwrap();
+
+    assert_eq!(output.status.code(), Some(130));
+    assert!(stdout(&output).contains("[INTERRUPTED]"));
+    for log in ["interrupted.log", "interrupted.result"] {
+        let content = std::fs::read_to_string(path.with_file_name(log)).unwrap();
+        assert!(content.contains("[INTERRUPTED] Analysis stopped by the user"), "{}: {}", log, content);
+    }
+}
+
+#[test]
+fn baseline_passes_when_metrics_match_and_fails_otherwise() {
+    let path = contract("baseline", CONFLICTING);
+    let csv = stdout(&analyze(&path, &[]))
+        .lines()
+        .find_map(|l| l.strip_prefix("RESULT_CSV:").map(str::to_string))
+        .unwrap();
+    let fields: Vec<&str> = csv.split(';').collect();
+    let (states, transitions, conflicts) = (fields[1], fields[2], fields[6]);
+
+    // Columns are found by the header, in any order and next to unrelated ones
+    let baseline = path.with_file_name("baseline.csv");
+    let write = |states: &str| {
+        std::fs::write(
+            &baseline,
+            format!(
+                "file,conflict_count,obs,transitions,states\nother.rcl,0,,1,1\nbaseline.rcl,{},,{},{}\n",
+                conflicts, transitions, states
+            ),
+        )
+        .unwrap();
+    };
+
+    write(states);
+    let output = analyze(&path, &["--baseline", baseline.to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let wrong = (states.parse::<usize>().unwrap() + 1).to_string();
+    write(&wrong);
+    let output = analyze(&path, &["--baseline", baseline.to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(8));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains(&format!("Baseline mismatch in baseline.rcl: states: {} (baseline {})", states, wrong)),
+        "{}",
+        stderr
+    );
+    assert!(!stderr.contains("transitions:"));
+}
+
+#[test]
+fn break_on_dumps_the_matching_state_and_halts_on_request() {
+    let path = contract("break_on", CONFLICT_FREE);
+    let watched = "{seller,buyer}OBLIGATION(deliver)_/F/_";
+    let states = |output: &Output| -> usize {
+        let out = stdout(output);
+        let csv = out.lines().find_map(|l| l.strip_prefix("RESULT_CSV:")).unwrap();
+        csv.split(';').nth(1).unwrap().parse().unwrap()
+    };
+    let full = analyze(&path, &[]);
+
+    // Only the delivery state matches; the dump names its incoming transition
+    let dumped = analyze(&path, &["--break-on", watched]);
+    let out = stdout(&dumped);
+    assert_eq!(out.matches("BREAK: state").count(), 1, "{}", out);
+    assert!(out.contains("   Incoming: T") && out.contains("   Concurrent actions: {(buyer, pay, seller)}"));
+    assert!(out.contains("   Tags: O(seller,deliver,buyer)"));
+    assert!(!out.contains("construction halted"));
+    assert_eq!(states(&dumped), states(&full));
+
+    let halted = analyze(&path, &["--break-on", watched, "--break-halt"]);
+    assert!(halted.status.success());
+    assert!(stdout(&halted).contains("BREAK: construction halted (--break-halt)"));
+    assert!(states(&halted) < states(&full));
+
+    let unmatched = stdout(&analyze(&path, &["--break-on", "refund", "--break-halt"]));
+    assert!(!unmatched.contains("BREAK:"));
+}
+
+#[test]
+fn single_clause_is_analysed_as_a_contract() {
+    let output = Command::new(env!("CARGO_BIN_EXE_analyzer_engine"))
+        .args(["--clause", "{buyer,seller}O(pay)", "--no-rc", "--csv-only"])
+        .output()
+        .expect("analyzer should start");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let out = stdout(&output);
+    let fields: Vec<&str> = out.trim().split(';').collect();
+    // Initial obligation, then paid (satisfaction) or not (violation)
+    assert_eq!(fields[1], "3");
+    assert_eq!(fields[5], "0");
+    assert_eq!((fields[9], fields[10]), ("1", "1"));
+}
+
+/// Files left in the temp dir by the `--clause` run of the analyzer process `pid`.
+fn clause_leftovers(pid: u32) -> Vec<String> {
+    let stem = format!("recall_clause_{}", pid);
+    std::fs::read_dir(std::env::temp_dir())
+        .unwrap()
+        .flatten()
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| name.strip_prefix(&stem).is_some_and(|rest| rest.starts_with(['.', '_'])))
+        .collect()
+}
+
+#[test]
+fn clause_temp_files_are_removed_on_success_and_on_error() {
+    let cwd = run_dir("clause_rc");
+    let analyzer = |clause: &str, args: &[&str]| {
+        let child = Command::new(env!("CARGO_BIN_EXE_analyzer_engine"))
+            .args(["--clause", clause])
+            .args(args)
+            .current_dir(&cwd)
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .expect("analyzer should start");
+        let pid = child.id();
+        (pid, child.wait_with_output().unwrap())
+    };
+
+    // Exports land next to the temp contract too
+    let (pid, output) = analyzer("{buyer,seller}O(pay)", &["--no-rc", "-g", "--export-contract"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(stdout(&output).contains("Automaton exported to"));
+    assert_eq!(clause_leftovers(pid), Vec::<String>::new());
+
+    // The `.recallrc` comes from the working directory: here it makes the run fail
+    std::fs::write(cwd.join(".recallrc"), "max_clause_size = 1\n").unwrap();
+    let (pid, output) = analyzer("{buyer,seller}O(pay) ^ {buyer,seller}[pay]({seller,buyer}O(ship))", &[]);
+    assert_eq!(output.status.code(), Some(7), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(clause_leftovers(pid), Vec::<String>::new());
+
+    let (pid, output) = analyzer("{buyer,seller}O(pay", &["--no-rc"]);
+    assert_eq!(output.status.code(), Some(4));
+    assert_eq!(clause_leftovers(pid), Vec::<String>::new());
+}
+
+#[test]
+fn clause_option_without_a_clause_is_a_usage_error() {
+    let output = Command::new(env!("CARGO_BIN_EXE_analyzer_engine"))
+        .arg("--clause")
+        .output()
+        .expect("analyzer should start");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--clause"));
+}
@@ -29,6 +29,7 @@ export interface ProgressEvent {
   status: string;
   result: string | null;
   time_ms: number | null;
+  eta_ms: number | null;
   progress: number;
 }
 
@@ -142,7 +143,8 @@ export const AnalysisProvider: React.FC<{ children: React.ReactNode }> = ({ chil
       const fileName = event.payload.file.split(/[\\/]/).pop() || event.payload.file;
 
       if (event.payload.status === "Processing") {
-        addBatchLog(`Processing: ${fileName}`, "info");
+        const eta = event.payload.eta_ms != null ? ` (ETA ${Math.ceil(event.payload.eta_ms / 1000)}s)` : "";
+        addBatchLog(`Processing: ${fileName}${eta}`, "info");
       } else if (event.payload.status === "Success") {
         addBatchLog(`Completed: ${fileName}`, "success");
         if (event.payload.result) {
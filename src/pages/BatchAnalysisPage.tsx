@@ -157,7 +157,8 @@ export function BatchAnalysisPage() {
         folderPath,
         exportAutomaton: exportNormal,
         exportMinAutomaton: exportMin,
-        usePruning: usePruning
+        usePruning: usePruning,
+        failFast: false
       });
       addLog("Analysis process finished.", "success");
       